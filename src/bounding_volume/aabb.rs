@@ -2,7 +2,7 @@
 
 use na::{Translation, POrd, Translate, Bounded};
 use na;
-use bounding_volume::BoundingVolume;
+use bounding_volume::{BoundingVolume, HasSurfaceArea, SweptBoundingVolume};
 use math::{Scalar, Point, Vect};
 
 /// Trait of objects that can be bounded by an AABB.
@@ -141,6 +141,44 @@ impl<N, P, V> BoundingVolume<N> for AABB<P>
     }
 }
 
+impl<N, P, V> HasSurfaceArea<N> for AABB<P>
+    where N: Scalar,
+          P: Point<N, V> {
+    #[inline]
+    fn surface_area(&self) -> N {
+        let extents = self.maxs - self.mins;
+        let dim     = na::dim::<P>();
+        let mut res = na::zero::<N>();
+
+        for i in range(0, dim) {
+            let mut face: N = na::one();
+
+            for j in range(0, dim) {
+                if i != j {
+                    face = face * extents[j];
+                }
+            }
+
+            res = res + face;
+        }
+
+        res + res
+    }
+}
+
+impl<N, P, V> SweptBoundingVolume<V> for AABB<P>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> {
+    #[inline]
+    fn swept(&self, dv: &V) -> AABB<P> {
+        AABB {
+            mins: na::inf(&self.mins, &(self.mins + *dv)),
+            maxs: na::sup(&self.maxs, &(self.maxs + *dv))
+        }
+    }
+}
+
 impl<N, P, V> Translation<V> for AABB<P>
     where N: Scalar,
           P: Point<N, V>,
@@ -1,12 +1,18 @@
+use na::Transform;
 use bounding_volume::{AABB, HasAABB};
 use bounding_volume::aabb_utils;
 use shape::BezierCurve;
-use math::Matrix;
 use math::{Scalar, Point, Vect};
 
-impl HasAABB for BezierCurve {
+impl<N, P, V, M> HasAABB<P, M> for BezierCurve<P>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Transform<P> {
     #[inline]
-    fn aabb(&self, m: &Matrix) -> AABB {
-        aabb_utils::point_cloud_aabb(m, self.control_points())
+    fn aabb(&self, m: &M) -> AABB<P> {
+        let (min, max) = aabb_utils::point_cloud_aabb(m, self.control_points());
+
+        AABB::new(min, max)
     }
 }
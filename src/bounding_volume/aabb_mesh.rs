@@ -13,7 +13,7 @@ impl<N, P, V, M, E> HasAABB<P, M> for Mesh<N, P, V, E>
           E: MeshElement<P> {
     #[inline]
     fn aabb(&self, m: &M) -> AABB<P> {
-        let bv              = self.bvt().root_bounding_volume().unwrap();
+        let bv              = self.flat_bvt().root_bounding_volume().unwrap();
         let ls_center       = na::orig::<P>() + bv.translation();
         let center          = m.transform(&ls_center);
         let half_extents    = (*bv.maxs() - *bv.mins()) * na::cast::<f64, N>(0.5);
@@ -1,8 +1,8 @@
-use na::Transform;
+use na::{Transform, Rotation, Norm, Identity, BaseFloat};
 use na;
 use support_map::SupportMap;
-use bounding_volume::AABB;
-use math::{Scalar, Point, Vect};
+use bounding_volume::{AABB, HasAABB, HasBoundingSphere, BoundingVolume};
+use math::{Scalar, Point, Vect, Isometry};
 
 
 
@@ -49,3 +49,38 @@ pub fn point_cloud_aabb<N, P, V, M>(m: &M, pts: &[P]) -> (P, P)
 
     (min, max)
 }
+
+/// Computes an AABB enclosing a shape moving from the position `m1` to the position `m2`.
+///
+/// The translation is assumed to be interpolated linearly, and the rotation along the shortest
+/// arc. The AABBs of the shape at both positions are merged, and then enlarged by the largest
+/// distance between a point of the rotating shape and its chord.
+pub fn swept_aabb<N, P, V, AV, M, G>(g: &G, m1: &M, m2: &M) -> AABB<P>
+    where N:  Scalar,
+          P:  Point<N, V>,
+          V:  Vect<N>,
+          AV: Norm<N>,
+          M:  Isometry<N, P, V> + Rotation<AV>,
+          G:  HasAABB<P, M> + HasBoundingSphere<N, P, Identity> {
+    let aabb1 = g.aabb(m1);
+    let aabb2 = g.aabb(m2);
+    let res   = aabb1.merged(&aabb2);
+
+    // Angle of the relative rotation between both positions.
+    let relative = *m2 * na::inv(m1).expect("The transformation `m1` must be inversible.");
+    let angle    = na::norm(&na::rotation(&relative));
+
+    if na::is_zero(&angle) {
+        res
+    }
+    else {
+        // Largest distance between the rotation center and a point of the shape.
+        let local   = g.bounding_sphere(&Identity::new());
+        let radius  = na::norm(local.center().as_vec()) + local.radius();
+        let _1: N   = na::one();
+        let _0_5: N = na::cast(0.5f64);
+        let angle   = angle.min(BaseFloat::pi());
+
+        res.loosened(radius * (_1 - (angle * _0_5).cos()))
+    }
+}
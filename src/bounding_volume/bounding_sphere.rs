@@ -2,7 +2,8 @@
 
 use na::{Translation, Norm, Transform, Translate, FloatVec};
 use na;
-use bounding_volume::BoundingVolume;
+use bounding_volume::{BoundingVolume, HasSurfaceArea, SweptBoundingVolume};
+use volumetric;
 use math::{Scalar, Point, Vect};
 
 /// Trait implemented by objects having a bounding sphere.
@@ -141,6 +142,28 @@ impl<N, P, V> BoundingVolume<N> for BoundingSphere<N, P>
     }
 }
 
+impl<N, P, V> HasSurfaceArea<N> for BoundingSphere<N, P>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: FloatVec<N> + Translate<P> {
+    #[inline]
+    fn surface_area(&self) -> N {
+        volumetric::ball_surface(na::dim::<P>(), self.radius.clone())
+    }
+}
+
+impl<N, P, V> SweptBoundingVolume<V> for BoundingSphere<N, P>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> {
+    #[inline]
+    fn swept(&self, dv: &V) -> BoundingSphere<N, P> {
+        let _0_5: N = na::cast(0.5f64);
+
+        BoundingSphere::new(self.center + *dv * _0_5, self.radius + na::norm(dv) * _0_5)
+    }
+}
+
 impl<N, P, V> Translation<V> for BoundingSphere<N, P>
     where N: Scalar,
           P: Point<N, V>,
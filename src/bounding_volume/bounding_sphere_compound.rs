@@ -1,25 +1,36 @@
-use na::{Transform, FloatVec, Translate};
-use bounding_volume::BoundingVolume;
+use na::{Transform, Translate};
+use na;
 use bounding_volume::{BoundingSphere, HasBoundingSphere};
+use bounding_volume;
 use shape::Compound;
-use math::{Scalar, Point};
+use math::{Scalar, Point, Vect};
 
 
 impl<N, P, V, M, M2> HasBoundingSphere<N, P, M2> for Compound<N, P, V, M>
     where N: Scalar,
           P: Point<N, V>,
-          V: FloatVec<N> + Translate<P>,
+          V: Vect<N> + Translate<P>,
           M2: Transform<P> {
+    /// An approximation of the smallest sphere enclosing the bounding spheres of the parts.
+    ///
+    /// The center is the one of the smallest sphere enclosing the centers of the parts bounding
+    /// spheres. The radius is then the smallest one that encloses the parts bounding spheres. Its
+    /// excess over the optimal radius is at most the difference between the largest and the
+    /// smallest part radii, so the result is exact when all the parts have the same radius.
     #[inline]
     fn bounding_sphere(&self, m: &M2) -> BoundingSphere<N, P> {
-        let shapes = self.shapes();
+        let spheres: Vec<BoundingSphere<N, P>> =
+            self.shapes().iter().map(|&(ref t, ref s)| s.bounding_sphere(t)).collect();
+        let centers: Vec<P> = spheres.iter().map(|s| s.center().clone()).collect();
 
-        let mut res = shapes[0].ref1().bounding_sphere(shapes[0].ref0());
+        // The smallest sphere enclosing the parts centers, enlarged to enclose the parts.
+        let (center, _) = bounding_volume::point_cloud_minimal_bounding_sphere(centers.as_slice());
+        let mut radius  = na::zero::<N>();
 
-        for &(ref t, ref s) in shapes.slice_from(1).iter() {
-            res.merge(&s.bounding_sphere(t));
+        for s in spheres.iter() {
+            radius = radius.max(na::dist(&center, s.center()) + s.radius());
         }
 
-        BoundingSphere::new(m.transform(res.center()), res.radius())
+        BoundingSphere::new(m.transform(&center), radius)
     }
 }
@@ -12,7 +12,7 @@ impl<N, P, V, M> HasBoundingSphere<N, P, M> for Convex<P>
           M: Transform<P> {
     #[inline]
     fn bounding_sphere(&self, m: &M) -> BoundingSphere<N, P> {
-        let (center, radius) = bounding_volume::point_cloud_bounding_sphere(self.points());
+        let (center, radius) = bounding_volume::point_cloud_minimal_bounding_sphere(self.points());
 
         BoundingSphere::new(m.transform(&center), radius)
     }
@@ -13,7 +13,7 @@ impl<N, P, V, M, E> HasBoundingSphere<N, P, M> for Mesh<N, P, V, E>
           E: MeshElement<P> {
     #[inline]
     fn bounding_sphere(&self, m: &M) -> BoundingSphere<N, P> {
-        let (center, radius) = bounding_volume::point_cloud_bounding_sphere(self.vertices().as_slice());
+        let (center, radius) = bounding_volume::point_cloud_minimal_bounding_sphere(self.vertices().as_slice());
 
         BoundingSphere::new(m.transform(&center), radius)
     }
@@ -1,3 +1,5 @@
+use std::num::Float;
+use std::rand::{IsaacRng, Rng};
 use na::Norm;
 use na;
 use utils;
@@ -33,3 +35,181 @@ pub fn point_cloud_bounding_sphere<N, P, V>(pts: &[P]) -> (P, N)
           V: Vect<N> {
     point_cloud_bounding_sphere_with_center(pts, utils::center(pts))
 }
+
+/// Computes the smallest bounding sphere of the specified set of point.
+///
+/// This uses the randomized algorithm of Welzl and runs in expected linear time. The points are
+/// shuffled with a fixed seed so that the result is deterministic. Use
+/// `point_cloud_bounding_sphere` for a faster, but looser, bounding sphere centered at the
+/// centroid of the points.
+// FIXME: return a bounding sphere?
+pub fn point_cloud_minimal_bounding_sphere<N, P, V>(pts: &[P]) -> (P, N)
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> {
+    assert!(!pts.is_empty(), "Cannot compute the bounding sphere of an empty point cloud.");
+
+    let mut rng      = IsaacRng::new_unseeded();
+    let mut shuffled = pts.to_vec();
+    let mut support  = Vec::with_capacity(na::dim::<V>() + 1);
+
+    rng.shuffle(shuffled.as_mut_slice());
+
+    let (center, sqradius) = welzl(shuffled.as_slice(), &mut support);
+
+    (center, sqradius.sqrt())
+}
+
+// Computes the smallest sphere enclosing `pts` and having all the points of `support` on its
+// boundary. The recursion depth is bounded by the dimension since each level adds one point to
+// the support set.
+fn welzl<N, P, V>(pts: &[P], support: &mut Vec<P>) -> (P, N)
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> {
+    let mut res = circumsphere(support.as_slice());
+
+    if support.len() == na::dim::<V>() + 1 {
+        return res;
+    }
+
+    for i in range(0, pts.len()) {
+        if !sphere_contains(&res, &pts[i]) {
+            support.push(pts[i].clone());
+            res = welzl(pts.slice_to(i), support);
+            let _ = support.pop();
+        }
+    }
+
+    res
+}
+
+#[inline]
+fn sphere_contains<N, P, V>(sphere: &(P, N), pt: &P) -> bool
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> {
+    let _eps: N = Float::epsilon();
+    let _1: N   = na::one();
+    let tol     = _eps * na::cast(100.0f64);
+
+    na::sqdist(sphere.ref0(), pt) <= *sphere.ref1() * (_1 + tol) + tol
+}
+
+// Computes the smallest sphere having all the given points on its boundary. Returns the center and
+// the squared radius. The squared radius is negative if the set of points is empty.
+fn circumsphere<N, P, V>(pts: &[P]) -> (P, N)
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> {
+    if pts.is_empty() {
+        return (na::orig(), -na::one::<N>())
+    }
+
+    let k = pts.len() - 1;
+
+    if k == 0 {
+        return (pts[0].clone(), na::zero())
+    }
+
+    // The center is `pts[0] + sum(lambda_j * (pts[j] - pts[0]))`, where the `lambda_j` solve the
+    // system `2 * (vi . vj) * lambda_j = vi . vi`.
+    let vs: Vec<V> = pts.slice_from(1).iter().map(|p| *p - pts[0]).collect();
+    let _2: N      = na::cast(2.0f64);
+    let mut mat    = Vec::from_fn(k * k, |id| na::dot(&vs[id / k], &vs[id % k]) * _2);
+    let mut rhs    = Vec::from_fn(k, |i| na::sqnorm(&vs[i]));
+
+    match solve(k, mat.as_mut_slice(), rhs.as_mut_slice()) {
+        Some(()) => {
+            let mut offset = na::zero::<V>();
+
+            for j in range(0, k) {
+                offset = offset + vs[j] * rhs[j];
+            }
+
+            (pts[0] + offset, na::sqnorm(&offset))
+        },
+        None => {
+            // Degenerate support set: enlarge the sphere of the other points to contain the last
+            // one.
+            let (center, sqradius) = circumsphere(pts.slice_to(k));
+            let sqdist             = na::sqdist(&center, &pts[k]);
+
+            (center, sqradius.max(sqdist))
+        }
+    }
+}
+
+// Solves the `k`x`k` row-major linear system `mat * x = rhs` using a gaussian elimination with
+// partial pivoting. The solution is written to `rhs`.
+fn solve<N: Scalar>(k: uint, mat: &mut [N], rhs: &mut [N]) -> Option<()> {
+    let _eps: N = Float::epsilon();
+
+    for col in range(0, k) {
+        let mut pivot = col;
+
+        for row in range(col + 1, k) {
+            if mat[row * k + col].abs() > mat[pivot * k + col].abs() {
+                pivot = row;
+            }
+        }
+
+        if mat[pivot * k + col].abs() <= _eps {
+            return None;
+        }
+
+        if pivot != col {
+            for j in range(0, k) {
+                mat.swap(pivot * k + j, col * k + j);
+            }
+
+            rhs.swap(pivot, col);
+        }
+
+        for row in range(col + 1, k) {
+            let factor = mat[row * k + col] / mat[col * k + col];
+
+            for j in range(col, k) {
+                mat[row * k + j] = mat[row * k + j] - factor * mat[col * k + j];
+            }
+
+            rhs[row] = rhs[row] - factor * rhs[col];
+        }
+    }
+
+    for col in range(0, k).rev() {
+        let mut acc = rhs[col];
+
+        for j in range(col + 1, k) {
+            acc = acc - mat[col * k + j] * rhs[j];
+        }
+
+        rhs[col] = acc / mat[col * k + col];
+    }
+
+    Some(())
+}
+
+#[cfg(test)]
+mod test {
+    use na::Pnt3;
+    use na;
+    use bounding_volume;
+
+    #[test]
+    fn test_minimal_bounding_sphere() {
+        let pts = [
+            Pnt3::new(-1.0f64, 0.0, 0.0), Pnt3::new(1.0, 0.0, 0.0),
+            Pnt3::new(0.0, 0.5, 0.0), Pnt3::new(0.0, 0.0, 0.5),
+            Pnt3::new(0.9, 0.0, 0.0), Pnt3::new(0.9, 0.0, 0.0),
+            Pnt3::new(0.95, 0.1, 0.0), Pnt3::new(0.95, -0.1, 0.0)
+        ];
+
+        let (center, radius) = bounding_volume::point_cloud_minimal_bounding_sphere(&pts);
+        let (_, loose_radius) = bounding_volume::point_cloud_bounding_sphere(&pts);
+
+        assert!(na::approx_eq(&center, &na::orig()));
+        assert!(na::approx_eq(&radius, &1.0));
+        assert!(loose_radius > radius);
+    }
+}
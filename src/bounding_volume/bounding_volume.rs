@@ -4,6 +4,16 @@ pub trait HasBoundingVolume<BV> {
     fn bounding_volume(&self) -> BV;
 }
 
+/// Trait of bounding volumes having a surface area.
+///
+/// This is needed by the Surface Area Heuristic used to build and optimize bounding volume trees.
+pub trait HasSurfaceArea<N> {
+    /// The surface area of this bounding volume.
+    ///
+    /// In 2d, this is the perimeter of the bounding volume.
+    fn surface_area(&self) -> N;
+}
+
 /// Trait of bounding volumes.
 ///
 /// Bounding volumes are coarse approximations of shapes. It usually have constant time
@@ -34,3 +44,62 @@ pub trait BoundingVolume<N> {
     /// Creates a new, tightened version, of this bounding volume.
     fn tightened(&self, N) -> Self;
 }
+
+/// Trait of bounding volumes that can be swept along a displacement.
+///
+/// This is useful to enclose fast moving objects between two time steps.
+pub trait SweptBoundingVolume<V> {
+    /// Computes a bounding volume enclosing this one at every position along the displacement
+    /// `dv`.
+    fn swept(&self, dv: &V) -> Self;
+}
+
+#[cfg(test)]
+mod test {
+    use na::{Translation, Pnt3, Vec3, Iso3};
+    use na;
+    use bounding_volume::{BoundingVolume, SweptBoundingVolume, HasAABB, AABB, BoundingSphere, OBB, KDOP,
+                          DOP14};
+    use bounding_volume;
+    use shape::Cuboid;
+
+    fn check_swept<BV>(bv: BV, dv: Vec3<f64>)
+        where BV: BoundingVolume<f64> + SweptBoundingVolume<Vec3<f64>> + Translation<Vec3<f64>> {
+        // Loosened to absorb the rounding errors of the rotated volumes.
+        let swept = bv.swept(&dv).loosened(1.0e-9);
+
+        assert!(swept.contains(&bv));
+        assert!(swept.contains(&Translation::append_translation_cpy(&bv, &dv)));
+        assert!(swept.contains(&Translation::append_translation_cpy(&bv, &(dv * 0.5))));
+    }
+
+    #[test]
+    fn test_swept_contains_both_poses() {
+        let dvs = [ Vec3::new(3.0f64, -1.0, 0.5), Vec3::new(-2.0f64, 0.0, 0.0), na::zero() ];
+
+        for dv in dvs.iter() {
+            check_swept(AABB::new(Pnt3::new(-1.0f64, 0.0, 1.0), Pnt3::new(2.0, 0.5, 3.0)), *dv);
+            check_swept(BoundingSphere::new(Pnt3::new(1.0f64, 2.0, 3.0), 1.5), *dv);
+            check_swept(OBB::new(Iso3::new(Vec3::new(1.0f64, 0.0, 0.0), Vec3::new(0.3, 0.7, -0.2)),
+                                 Vec3::new(2.0, 0.5, 1.0)), *dv);
+
+            let mut kdop = KDOP::new_invalid(DOP14);
+
+            kdop.take_point(&Vec3::new(0.0f64, 0.0, 0.0));
+            kdop.take_point(&Vec3::new(1.0f64, 2.0, 0.0));
+            kdop.take_point(&Vec3::new(0.0f64, 1.0, 3.0));
+            check_swept(kdop, *dv);
+        }
+    }
+
+    #[test]
+    fn test_swept_aabb_contains_both_poses() {
+        let cuboid = Cuboid::new(Vec3::new(2.0f64, 0.5, 1.0));
+        let m1     = Iso3::new(Vec3::new(1.0f64, 0.0, 0.0), Vec3::new(0.0, 0.0, 0.0));
+        let m2     = Iso3::new(Vec3::new(4.0f64, 1.0, -2.0), Vec3::new(0.3, 1.2, -0.5));
+        let swept  = bounding_volume::swept_aabb(&cuboid, &m1, &m2);
+
+        assert!(swept.contains(&cuboid.aabb(&m1)));
+        assert!(swept.contains(&cuboid.aabb(&m2)));
+    }
+}
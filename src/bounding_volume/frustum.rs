@@ -0,0 +1,202 @@
+//! View frustum.
+
+use na::{Pnt3, Vec3, Mat4};
+use na;
+use bounding_volume::{AABB, BoundingSphere};
+use shape::Plane;
+use math::Scalar;
+
+/// The position of a bounding volume relative to a frustum.
+#[deriving(Show, PartialEq, Eq, Clone, Encodable, Decodable)]
+pub enum Containment {
+    /// The bounding volume is completely inside of the frustum.
+    Inside,
+    /// The bounding volume is completely outside of the frustum.
+    Outside,
+    /// The bounding volume intersects the boundary of the frustum.
+    Intersecting
+}
+
+/// Trait of bounding volumes that can be classified against a frustum.
+pub trait FrustumClassifiable<N> {
+    /// Computes the position of this bounding volume relative to `frustum`.
+    ///
+    /// This test is conservative: some bounding volumes near the frustum corners may be
+    /// classified as `Intersecting` even if they are outside of the frustum.
+    fn classify_with_frustum(&self, frustum: &Frustum<N>) -> Containment;
+}
+
+/// A convex volume bounded by six planes, typically the view volume of a camera.
+///
+/// Each plane is stored as its outward normal `n` and its offset `d`. A point `x` is inside of
+/// the frustum if `dot(n, x) <= d` for every plane.
+#[deriving(Show, PartialEq, Clone, Encodable, Decodable)]
+pub struct Frustum<N> {
+    normals: Vec<Vec3<N>>,
+    offsets: Vec<N>
+}
+
+impl<N: Scalar> Frustum<N> {
+    /// Creates a frustum from six planes.
+    ///
+    /// Each plane is given by a point it passes through, and its normal. The normals must point
+    /// toward the outside of the frustum.
+    pub fn new(planes: &[(Pnt3<N>, Plane<Vec3<N>>)]) -> Frustum<N> {
+        assert!(planes.len() == 6, "A frustum must have exactly six planes.");
+
+        let normals = planes.iter().map(|&(_, ref plane)| plane.normal().clone()).collect();
+        let offsets = planes.iter().map(|&(ref pt, ref plane)| {
+            na::dot(plane.normal(), pt.as_vec())
+        }).collect();
+
+        Frustum {
+            normals: normals,
+            offsets: offsets
+        }
+    }
+
+    /// Extracts the frustum of a view-projection matrix.
+    ///
+    /// The matrix is assumed to map the frustum to the clip-space cube `[-w, w]^3`, with the
+    /// column-vector convention of OpenGL.
+    pub fn new_with_matrix(view_proj: &Mat4<N>) -> Frustum<N> {
+        let m = view_proj;
+
+        // Each row is the plane `a . (x, 1) >= 0`.
+        let rows = [
+            [ m.m41 + m.m11, m.m42 + m.m12, m.m43 + m.m13, m.m44 + m.m14 ], // left
+            [ m.m41 - m.m11, m.m42 - m.m12, m.m43 - m.m13, m.m44 - m.m14 ], // right
+            [ m.m41 + m.m21, m.m42 + m.m22, m.m43 + m.m23, m.m44 + m.m24 ], // bottom
+            [ m.m41 - m.m21, m.m42 - m.m22, m.m43 - m.m23, m.m44 - m.m24 ], // top
+            [ m.m41 + m.m31, m.m42 + m.m32, m.m43 + m.m33, m.m44 + m.m34 ], // near
+            [ m.m41 - m.m31, m.m42 - m.m32, m.m43 - m.m33, m.m44 - m.m34 ]  // far
+        ];
+
+        let mut normals = Vec::with_capacity(6);
+        let mut offsets = Vec::with_capacity(6);
+
+        for row in rows.iter() {
+            let normal = Vec3::new(-row[0], -row[1], -row[2]);
+            let length = na::norm(&normal);
+
+            normals.push(normal / length);
+            offsets.push(row[3] / length);
+        }
+
+        Frustum {
+            normals: normals,
+            offsets: offsets
+        }
+    }
+
+    /// The outward normals of the planes of this frustum.
+    #[inline]
+    pub fn normals(&self) -> &[Vec3<N>] {
+        self.normals.as_slice()
+    }
+
+    /// The offsets of the planes of this frustum along their normals.
+    #[inline]
+    pub fn offsets(&self) -> &[N] {
+        self.offsets.as_slice()
+    }
+
+    /// Tests if a point is inside of this frustum.
+    #[inline]
+    pub fn contains_point(&self, pt: &Pnt3<N>) -> bool {
+        range(0u, 6).all(|i| na::dot(&self.normals[i], pt.as_vec()) <= self.offsets[i])
+    }
+
+    /// Computes the position of an AABB relative to this frustum.
+    pub fn classify_aabb(&self, aabb: &AABB<Pnt3<N>>) -> Containment {
+        let center       = aabb.center();
+        let half_extents = aabb.half_extents();
+        let mut res      = Containment::Inside;
+
+        for i in range(0u, 6) {
+            let dist   = na::dot(&self.normals[i], center.as_vec()) - self.offsets[i];
+            let radius = na::dot(&na::abs(&self.normals[i]), &half_extents);
+
+            if dist > radius {
+                return Containment::Outside;
+            }
+
+            if dist > -radius {
+                res = Containment::Intersecting;
+            }
+        }
+
+        res
+    }
+
+    /// Computes the position of a bounding sphere relative to this frustum.
+    pub fn classify_bounding_sphere(&self, bs: &BoundingSphere<N, Pnt3<N>>) -> Containment {
+        let radius  = bs.radius();
+        let mut res = Containment::Inside;
+
+        for i in range(0u, 6) {
+            let dist = na::dot(&self.normals[i], bs.center().as_vec()) - self.offsets[i];
+
+            if dist > radius {
+                return Containment::Outside;
+            }
+
+            if dist > -radius {
+                res = Containment::Intersecting;
+            }
+        }
+
+        res
+    }
+
+    /// Tests if an AABB intersects or is inside of this frustum.
+    #[inline]
+    pub fn intersects_aabb(&self, aabb: &AABB<Pnt3<N>>) -> bool {
+        self.classify_aabb(aabb) != Containment::Outside
+    }
+
+    /// Tests if a bounding sphere intersects or is inside of this frustum.
+    #[inline]
+    pub fn intersects_bounding_sphere(&self, bs: &BoundingSphere<N, Pnt3<N>>) -> bool {
+        self.classify_bounding_sphere(bs) != Containment::Outside
+    }
+}
+
+impl<N: Scalar> FrustumClassifiable<N> for AABB<Pnt3<N>> {
+    #[inline]
+    fn classify_with_frustum(&self, frustum: &Frustum<N>) -> Containment {
+        frustum.classify_aabb(self)
+    }
+}
+
+impl<N: Scalar> FrustumClassifiable<N> for BoundingSphere<N, Pnt3<N>> {
+    #[inline]
+    fn classify_with_frustum(&self, frustum: &Frustum<N>) -> Containment {
+        frustum.classify_bounding_sphere(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use na::{Pnt3, Vec3, Mat4};
+    use na;
+    use bounding_volume::{AABB, BoundingSphere, Frustum, Containment};
+
+    #[test]
+    fn test_frustum_classification() {
+        // The orthographic projection of the cube `[-1, 1]^3`.
+        let frustum: Frustum<f64> = Frustum::new_with_matrix(&na::one::<Mat4<f64>>());
+
+        let inside  = AABB::new(Pnt3::new(-0.5, -0.5, -0.5), Pnt3::new(0.5, 0.5, 0.5));
+        let inter   = AABB::new(Pnt3::new(0.5, 0.5, 0.5), Pnt3::new(1.5, 1.5, 1.5));
+        let outside = AABB::new(Pnt3::new(1.5, 0.0, 0.0), Pnt3::new(2.5, 0.5, 0.5));
+
+        assert!(frustum.classify_aabb(&inside) == Containment::Inside);
+        assert!(frustum.classify_aabb(&inter) == Containment::Intersecting);
+        assert!(frustum.classify_aabb(&outside) == Containment::Outside);
+
+        let bs = BoundingSphere::new(Pnt3::new(0.0, 0.0, 1.5), 0.4);
+        assert!(frustum.classify_bounding_sphere(&bs) == Containment::Outside);
+        assert!(frustum.contains_point(&(na::orig::<Pnt3<f64>>() + Vec3::new(0.9, -0.9, 0.0))));
+    }
+}
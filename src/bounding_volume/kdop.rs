@@ -0,0 +1,643 @@
+//! Discrete Oriented Polytope.
+
+use std::fmt::{mod, Show, Formatter};
+use serialize::{Encodable, Decodable, Encoder, Decoder};
+use na::{Translation, Transform, Bounded, Zero, Vec2, Vec3};
+use na;
+use support_map::SupportMap;
+use bounding_volume::{BoundingVolume, HasSurfaceArea, SweptBoundingVolume};
+use math::{Scalar, Point, Vect};
+
+/// Trait of objects that can be bounded by a k-DOP.
+pub trait HasKDOP<N, V, D, M> {
+    /// The object’s k-DOP with the given set of directions.
+    fn kdop(&self, &M, &D) -> KDOP<N, V, D>;
+}
+
+/// Trait of the sets of directions a k-DOP is made of.
+///
+/// A k-DOP is bounded by `k / 2` pairs of parallel planes orthogonal to those directions.
+pub trait KDOPDirections<V>: Clone {
+    /// The number of directions of this set, that is, half of the number of faces of the k-DOP.
+    fn ndirections(&self) -> uint;
+
+    /// The i-th direction of this set.
+    ///
+    /// The returned vector must be normalized. The first `na::dim::<V>()` directions must be the
+    /// canonical basis vectors.
+    fn direction(&self, i: uint) -> V;
+}
+
+/// The 8-DOP directions: the two axes and the two diagonals of the plane.
+#[deriving(Show, PartialEq, Clone, Encodable, Decodable)]
+pub struct DOP8;
+
+/// The 14-DOP directions: the three axes and the four diagonals of the unit cube.
+#[deriving(Show, PartialEq, Clone, Encodable, Decodable)]
+pub struct DOP14;
+
+/// The 18-DOP directions: the three axes and the six diagonals of the faces of the unit cube.
+#[deriving(Show, PartialEq, Clone, Encodable, Decodable)]
+pub struct DOP18;
+
+/// The 26-DOP directions: the directions of both the 14-DOP and the 18-DOP.
+#[deriving(Show, PartialEq, Clone, Encodable, Decodable)]
+pub struct DOP26;
+
+static DIRECTIONS2: [[f64, ..2], ..4] = [
+    [ 1.0, 0.0 ], [ 0.0, 1.0 ],
+    [ 1.0, 1.0 ], [ 1.0, -1.0 ]
+];
+
+static AXES3: [[f64, ..3], ..3] = [
+    [ 1.0, 0.0, 0.0 ], [ 0.0, 1.0, 0.0 ], [ 0.0, 0.0, 1.0 ]
+];
+
+static CORNERS3: [[f64, ..3], ..4] = [
+    [ 1.0, 1.0, 1.0 ], [ 1.0, -1.0, 1.0 ], [ 1.0, 1.0, -1.0 ], [ 1.0, -1.0, -1.0 ]
+];
+
+static EDGES3: [[f64, ..3], ..6] = [
+    [ 1.0, 1.0, 0.0 ], [ 1.0, -1.0, 0.0 ],
+    [ 1.0, 0.0, 1.0 ], [ 1.0, 0.0, -1.0 ],
+    [ 0.0, 1.0, 1.0 ], [ 0.0, 1.0, -1.0 ]
+];
+
+#[inline]
+fn dir3<N: Scalar>(d: &[f64, ..3]) -> Vec3<N> {
+    na::normalize(&Vec3::new(na::cast(d[0]), na::cast(d[1]), na::cast(d[2])))
+}
+
+impl<N: Scalar> KDOPDirections<Vec2<N>> for DOP8 {
+    #[inline]
+    fn ndirections(&self) -> uint {
+        4
+    }
+
+    #[inline]
+    fn direction(&self, i: uint) -> Vec2<N> {
+        let d = &DIRECTIONS2[i];
+
+        na::normalize(&Vec2::new(na::cast(d[0]), na::cast(d[1])))
+    }
+}
+
+impl<N: Scalar> KDOPDirections<Vec3<N>> for DOP14 {
+    #[inline]
+    fn ndirections(&self) -> uint {
+        7
+    }
+
+    #[inline]
+    fn direction(&self, i: uint) -> Vec3<N> {
+        if i < 3 { dir3(&AXES3[i]) } else { dir3(&CORNERS3[i - 3]) }
+    }
+}
+
+impl<N: Scalar> KDOPDirections<Vec3<N>> for DOP18 {
+    #[inline]
+    fn ndirections(&self) -> uint {
+        9
+    }
+
+    #[inline]
+    fn direction(&self, i: uint) -> Vec3<N> {
+        if i < 3 { dir3(&AXES3[i]) } else { dir3(&EDGES3[i - 3]) }
+    }
+}
+
+impl<N: Scalar> KDOPDirections<Vec3<N>> for DOP26 {
+    #[inline]
+    fn ndirections(&self) -> uint {
+        13
+    }
+
+    #[inline]
+    fn direction(&self, i: uint) -> Vec3<N> {
+        if i < 3 {
+            dir3(&AXES3[i])
+        }
+        else if i < 7 {
+            dir3(&CORNERS3[i - 3])
+        }
+        else {
+            dir3(&EDGES3[i - 7])
+        }
+    }
+}
+
+// The largest number of directions of a k-DOP, reached by the 26-DOP.
+static MAX_DIRECTIONS: uint = 13;
+
+// The lower or upper bounds of the slabs of a k-DOP.
+//
+// They are stored inline with a fixed capacity so that k-DOPs, which are created for every
+// object of a broad phase, do not require any heap allocation.
+struct Bounds<N> {
+    len:    uint,
+    values: [N, ..MAX_DIRECTIONS]
+}
+
+impl<N: Copy> Bounds<N> {
+    fn new(len: uint, value: N) -> Bounds<N> {
+        assert!(len <= MAX_DIRECTIONS, "A k-DOP cannot have more than 13 directions.");
+
+        Bounds {
+            len:    len,
+            values: [value, ..MAX_DIRECTIONS]
+        }
+    }
+
+    fn new_with_slice(values: &[N], default: N) -> Bounds<N> {
+        let mut res = Bounds::new(values.len(), default);
+
+        for (i, v) in values.iter().enumerate() {
+            res.values[i] = *v;
+        }
+
+        res
+    }
+
+    #[inline]
+    fn len(&self) -> uint {
+        self.len
+    }
+
+    #[inline]
+    fn as_slice(&self) -> &[N] {
+        self.values.slice_to(self.len)
+    }
+
+    #[inline]
+    fn as_mut_slice(&mut self) -> &mut [N] {
+        self.values.slice_to_mut(self.len)
+    }
+}
+
+impl<N: Copy> Index<uint, N> for Bounds<N> {
+    #[inline]
+    fn index(&self, i: &uint) -> &N {
+        &self.as_slice()[*i]
+    }
+}
+
+impl<N: Copy> IndexMut<uint, N> for Bounds<N> {
+    #[inline]
+    fn index_mut(&mut self, i: &uint) -> &mut N {
+        &mut self.as_mut_slice()[*i]
+    }
+}
+
+impl<N: Copy> Clone for Bounds<N> {
+    #[inline]
+    fn clone(&self) -> Bounds<N> {
+        Bounds {
+            len:    self.len,
+            values: self.values
+        }
+    }
+}
+
+impl<N: Copy + PartialEq> PartialEq for Bounds<N> {
+    #[inline]
+    fn eq(&self, other: &Bounds<N>) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<N: Copy + Show> Show for Bounds<N> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.as_slice().fmt(f)
+    }
+}
+
+impl<E, S: Encoder<E>, N: Copy + Encodable<S, E>> Encodable<S, E> for Bounds<N> {
+    fn encode(&self, s: &mut S) -> Result<(), E> {
+        self.as_slice().encode(s)
+    }
+}
+
+impl<E, S: Decoder<E>, N: Copy + Zero + Decodable<S, E>> Decodable<S, E> for Bounds<N> {
+    fn decode(d: &mut S) -> Result<Bounds<N>, E> {
+        let values: Vec<N> = try!(Decodable::decode(d));
+
+        if values.len() > MAX_DIRECTIONS {
+            Err(d.error("A k-DOP cannot have more than 13 directions."))
+        }
+        else {
+            Ok(Bounds::new_with_slice(values.as_slice(), na::zero()))
+        }
+    }
+}
+
+/// A k-DOP (Discrete Oriented Polytope).
+///
+/// It is the intersection of the slabs `mins[i] <= dot(x, d_i) <= maxs[i]` where the `d_i` are the
+/// directions given by `D`. At most 13 directions are supported.
+#[deriving(Show, PartialEq, Clone, Encodable, Decodable)]
+pub struct KDOP<N, V, D> {
+    directions: D,
+    mins:       Bounds<N>,
+    maxs:       Bounds<N>
+}
+
+impl<N, V, D> KDOP<N, V, D>
+    where N: Scalar,
+          V: Vect<N>,
+          D: KDOPDirections<V> {
+    /// Creates a new k-DOP.
+    ///
+    /// # Arguments:
+    ///   * `directions` - the directions of the slabs bounding the k-DOP.
+    ///   * `mins` - the lower bound of each slab. Its length must be `directions.ndirections()`.
+    ///   * `maxs` - the upper bound of each slab. Each component must be greater than the
+    ///   related component of `mins`.
+    pub fn new(directions: D, mins: &[N], maxs: &[N]) -> KDOP<N, V, D> {
+        assert!(mins.len() == directions.ndirections() && maxs.len() == directions.ndirections());
+        assert!(mins.iter().zip(maxs.iter()).all(|(a, b)| *a <= *b));
+
+        KDOP {
+            directions: directions,
+            mins:       Bounds::new_with_slice(mins, na::zero()),
+            maxs:       Bounds::new_with_slice(maxs, na::zero())
+        }
+    }
+
+    /// Creates an invalid k-DOP with all its slabs empty.
+    /// This is useful to build k-DOPs using merges.
+    pub fn new_invalid(directions: D) -> KDOP<N, V, D> {
+        let _max: N = Bounded::max_value();
+        let n       = directions.ndirections();
+
+        KDOP {
+            directions: directions,
+            mins:       Bounds::new(n, _max),
+            maxs:       Bounds::new(n, -_max)
+        }
+    }
+
+    /// The directions of the slabs of this k-DOP.
+    #[inline]
+    pub fn directions(&self) -> &D {
+        &self.directions
+    }
+
+    /// The lower bound of each slab of this k-DOP.
+    #[inline]
+    pub fn mins(&self) -> &[N] {
+        self.mins.as_slice()
+    }
+
+    /// The upper bound of each slab of this k-DOP.
+    #[inline]
+    pub fn maxs(&self) -> &[N] {
+        self.maxs.as_slice()
+    }
+
+    /// Enlarges this k-DOP so that it contains the point `pt`.
+    #[inline]
+    pub fn take_point(&mut self, pt: &V) {
+        for i in range(0, self.mins.len()) {
+            let proj = na::dot(pt, &self.directions.direction(i));
+
+            self.mins[i] = self.mins[i].min(proj);
+            self.maxs[i] = self.maxs[i].max(proj);
+        }
+    }
+}
+
+/// Computes the k-DOP of a support mapped shape.
+pub fn implicit_shape_kdop<N, P, V, M, D, G>(m: &M, i: &G, directions: &D) -> KDOP<N, V, D>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          D: KDOPDirections<V>,
+          G: SupportMap<P, V, M> {
+    let mut res = KDOP::new_invalid(directions.clone());
+
+    for k in range(0, directions.ndirections()) {
+        let dir = directions.direction(k);
+
+        res.maxs[k] = na::dot(i.support_point(m, &dir).as_vec(), &dir);
+        res.mins[k] = na::dot(i.support_point(m, &-dir).as_vec(), &dir);
+    }
+
+    res
+}
+
+/// Computes the k-DOP of a set of points.
+pub fn point_cloud_kdop<N, P, V, M, D>(m: &M, pts: &[P], directions: &D) -> KDOP<N, V, D>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Transform<P>,
+          D: KDOPDirections<V> {
+    let mut res = KDOP::new_invalid(directions.clone());
+
+    for pt in pts.iter() {
+        res.take_point(m.transform(pt).as_vec());
+    }
+
+    res
+}
+
+impl<N, V, D> BoundingVolume<N> for KDOP<N, V, D>
+    where N: Scalar,
+          V: Vect<N>,
+          D: KDOPDirections<V> {
+    /// Tests the overlap of every pair of slabs of the two k-DOPs.
+    ///
+    /// Both k-DOPs must have the same directions.
+    #[inline]
+    fn intersects(&self, other: &KDOP<N, V, D>) -> bool {
+        range(0, self.mins.len()).all(|i| self.mins[i] <= other.maxs[i] && other.mins[i] <= self.maxs[i])
+    }
+
+    #[inline]
+    fn contains(&self, other: &KDOP<N, V, D>) -> bool {
+        range(0, self.mins.len()).all(|i| self.mins[i] <= other.mins[i] && other.maxs[i] <= self.maxs[i])
+    }
+
+    #[inline]
+    fn merge(&mut self, other: &KDOP<N, V, D>) {
+        for i in range(0, self.mins.len()) {
+            self.mins[i] = self.mins[i].min(other.mins[i]);
+            self.maxs[i] = self.maxs[i].max(other.maxs[i]);
+        }
+    }
+
+    #[inline]
+    fn merged(&self, other: &KDOP<N, V, D>) -> KDOP<N, V, D> {
+        let mut res = self.clone();
+        res.merge(other);
+
+        res
+    }
+
+    #[inline]
+    fn loosen(&mut self, amount: N) {
+        assert!(amount >= na::zero(), "The loosening margin must be positive.");
+
+        for i in range(0, self.mins.len()) {
+            self.mins[i] = self.mins[i] - amount;
+            self.maxs[i] = self.maxs[i] + amount;
+        }
+    }
+
+    #[inline]
+    fn loosened(&self, amount: N) -> KDOP<N, V, D> {
+        let mut res = self.clone();
+        res.loosen(amount);
+
+        res
+    }
+
+    #[inline]
+    fn tighten(&mut self, amount: N) {
+        assert!(amount >= na::zero(), "The tightening margin must be positive.");
+
+        for i in range(0, self.mins.len()) {
+            self.mins[i] = self.mins[i] + amount;
+            self.maxs[i] = self.maxs[i] - amount;
+            assert!(self.mins[i] <= self.maxs[i], "The tightening margin is to large.");
+        }
+    }
+
+    #[inline]
+    fn tightened(&self, amount: N) -> KDOP<N, V, D> {
+        let mut res = self.clone();
+        res.tighten(amount);
+
+        res
+    }
+}
+
+impl<N, V, D> HasSurfaceArea<N> for KDOP<N, V, D>
+    where N: Scalar,
+          V: Vect<N>,
+          D: KDOPDirections<V> {
+    /// An approximation of the surface area of this k-DOP.
+    ///
+    /// This is the surface area of the AABB given by the slabs orthogonal to the canonical axes.
+    #[inline]
+    fn surface_area(&self) -> N {
+        let dim     = na::dim::<V>();
+        let mut res = na::zero::<N>();
+
+        for i in range(0, dim) {
+            let mut face: N = na::one();
+
+            for j in range(0, dim) {
+                if i != j {
+                    face = face * (self.maxs[j] - self.mins[j]);
+                }
+            }
+
+            res = res + face;
+        }
+
+        res + res
+    }
+}
+
+impl<N, V, D> SweptBoundingVolume<V> for KDOP<N, V, D>
+    where N: Scalar,
+          V: Vect<N>,
+          D: KDOPDirections<V> {
+    #[inline]
+    fn swept(&self, dv: &V) -> KDOP<N, V, D> {
+        let mut res = self.clone();
+
+        for i in range(0, res.mins.len()) {
+            let shift = na::dot(dv, &res.directions.direction(i));
+
+            if shift < na::zero() {
+                res.mins[i] = res.mins[i] + shift;
+            }
+            else {
+                res.maxs[i] = res.maxs[i] + shift;
+            }
+        }
+
+        res
+    }
+}
+
+impl<N, V, D> Translation<V> for KDOP<N, V, D>
+    where N: Scalar,
+          V: Vect<N>,
+          D: KDOPDirections<V> {
+    /// The center of the AABB given by the slabs orthogonal to the canonical axes.
+    #[inline]
+    fn translation(&self) -> V {
+        let _0_5: N = na::cast(0.5f64);
+        let mut res = na::zero::<V>();
+
+        for i in range(0, na::dim::<V>()) {
+            res[i] = (self.mins[i] + self.maxs[i]) * _0_5;
+        }
+
+        res
+    }
+
+    #[inline]
+    fn inv_translation(&self) -> V {
+        -self.translation()
+    }
+
+    #[inline]
+    fn append_translation(&mut self, dv: &V) {
+        for i in range(0, self.mins.len()) {
+            let shift = na::dot(dv, &self.directions.direction(i));
+
+            self.mins[i] = self.mins[i] + shift;
+            self.maxs[i] = self.maxs[i] + shift;
+        }
+    }
+
+    #[inline]
+    fn append_translation_cpy(kdop: &KDOP<N, V, D>, dv: &V) -> KDOP<N, V, D> {
+        let mut res = kdop.clone();
+        res.append_translation(dv);
+
+        res
+    }
+
+    #[inline]
+    fn prepend_translation(&mut self, dv: &V) {
+        self.append_translation(dv)
+    }
+
+    #[inline]
+    fn prepend_translation_cpy(kdop: &KDOP<N, V, D>, dv: &V) -> KDOP<N, V, D> {
+        Translation::append_translation_cpy(kdop, dv)
+    }
+
+    #[inline]
+    fn set_translation(&mut self, v: V) {
+        let center = self.translation();
+
+        self.append_translation(&(v - center))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::rand::{IsaacRng, Rng};
+    use na::{Vec3, Pnt3, Iso3};
+    use na;
+    use shape::{Plane, BezierCurve};
+    use bounding_volume::{KDOP, KDOP14, HasKDOP, DOP14, DOP26, BoundingVolume};
+    use bounding_volume;
+    use point::LocalPointQuery;
+    use partitioning::{BVT, DBVT, BoundingVolumeInterferencesCollector};
+
+    #[test]
+    fn test_kdop_cuts_corners() {
+        let pts = [ Vec3::new(0.0f64, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0),
+                    Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 0.0, 1.0) ];
+
+        let mut a = KDOP::new_invalid(DOP14);
+        let mut b = KDOP::new_invalid(DOP14);
+
+        for pt in pts.iter() {
+            a.take_point(pt);
+            b.take_point(&(*pt + Vec3::new(0.9, 0.9, 0.9)));
+        }
+
+        // The AABBs of those tetrahedra intersect, but not the 14-DOPs.
+        assert!(!a.intersects(&b));
+        assert!(a.merged(&b).contains(&b));
+    }
+
+    #[test]
+    fn test_kdop_plane_and_bezier_curve() {
+        let m: Iso3<f64> = na::one();
+        let curve = BezierCurve::new(vec!(Pnt3::new(0.0f64, 0.0, 0.0), Pnt3::new(1.0, 2.0, 0.0),
+                                          Pnt3::new(2.0, 0.0, 1.0)));
+        let plane = Plane::new(Vec3::y());
+
+        let curve_kdop: KDOP<f64, Vec3<f64>, DOP26> = curve.kdop(&m, &DOP26);
+        let plane_kdop: KDOP<f64, Vec3<f64>, DOP26> = plane.kdop(&m, &DOP26);
+
+        assert!(curve_kdop.mins().len() == 13);
+        assert!(curve_kdop.clone() == curve_kdop);
+
+        for pt in curve.control_points().iter() {
+            let mut enlarged = curve_kdop.clone();
+
+            enlarged.take_point(pt.as_vec());
+            assert!(enlarged == curve_kdop);
+        }
+
+        assert!(plane_kdop.contains(&curve_kdop));
+        assert!(plane_kdop.loosened(1.0).contains(&plane_kdop));
+    }
+
+    // The 14-DOPs of small random point clouds.
+    fn random_kdops(rng: &mut IsaacRng, n: uint) -> Vec<(uint, KDOP14<f64>)> {
+        let m: Iso3<f64> = na::one();
+
+        range(0u, n).map(|i| {
+            let center = Pnt3::new(rng.gen::<f64>() * 20.0, rng.gen::<f64>() * 20.0, rng.gen::<f64>() * 20.0);
+            let pts: Vec<Pnt3<f64>> = range(0u, 5).map(|_| {
+                center + Vec3::new(rng.gen::<f64>() * 2.0 - 1.0, rng.gen::<f64>() * 2.0 - 1.0, rng.gen::<f64>() * 2.0 - 1.0)
+            }).collect();
+
+            (i, bounding_volume::point_cloud_kdop(&m, pts.as_slice(), &DOP14))
+        }).collect()
+    }
+
+    #[test]
+    fn test_kdop_bvt_and_dbvt() {
+        let mut rng = IsaacRng::new_unseeded();
+        let leaves  = random_kdops(&mut rng, 200);
+        let queries = random_kdops(&mut rng, 20);
+
+        let balanced = BVT::new_balanced(leaves.clone());
+        let sah      = BVT::new_sah(leaves.clone());
+        let mut dbvt: DBVT<Pnt3<f64>, uint, KDOP14<f64>> = DBVT::new();
+
+        for &(i, ref kdop) in leaves.iter() {
+            let _ = dbvt.insert_new(i, kdop.clone());
+        }
+
+        dbvt.optimize(10);
+
+        for &(_, ref query) in queries.iter() {
+            let mut expected: Vec<uint> = leaves.iter().filter(|l| l.ref1().intersects(query)).map(|l| *l.ref0()).collect();
+            expected.sort();
+
+            for bvt in [ &balanced, &sah ].iter() {
+                let mut found = Vec::new();
+
+                bvt.visit(&mut BoundingVolumeInterferencesCollector::new(query, &mut found));
+                found.sort();
+                assert!(found == expected);
+            }
+
+            let mut found = Vec::new();
+
+            dbvt.interferences_with_bounding_volume(query, &mut found);
+            found.sort();
+            assert!(found == expected);
+        }
+
+        // The pruning of the nearest neighbors search relies on exact k-DOP distances.
+        for _ in range(0u, 20) {
+            let pt = Pnt3::new(rng.gen::<f64>() * 30.0 - 5.0, rng.gen::<f64>() * 30.0 - 5.0, rng.gen::<f64>() * 30.0 - 5.0);
+            let mut expected: Vec<f64> = leaves.iter().map(|l| l.ref1().distance_to_point(&pt)).collect();
+
+            expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+            let from_bvt  = sah.k_nearest(&pt, 5, &mut |b, pt| leaves[*b].ref1().distance_to_point(pt));
+            let from_dbvt = dbvt.k_nearest(&pt, 5, &mut |b, pt| leaves[*b].ref1().distance_to_point(pt));
+
+            assert!(from_bvt.len() == 5 && from_dbvt.len() == 5);
+
+            for i in range(0u, 5) {
+                assert!(*from_bvt[i].ref0() == expected[i] && *from_dbvt[i].ref0() == expected[i]);
+            }
+        }
+    }
+}
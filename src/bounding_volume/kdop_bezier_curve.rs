@@ -0,0 +1,17 @@
+use na::Transform;
+use bounding_volume::{HasKDOP, KDOP, KDOPDirections};
+use bounding_volume;
+use shape::BezierCurve;
+use math::{Scalar, Point, Vect};
+
+impl<N, P, V, M, D> HasKDOP<N, V, D, M> for BezierCurve<P>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Transform<P>,
+          D: KDOPDirections<V> {
+    #[inline]
+    fn kdop(&self, m: &M, directions: &D) -> KDOP<N, V, D> {
+        bounding_volume::point_cloud_kdop(m, self.control_points(), directions)
+    }
+}
@@ -0,0 +1,52 @@
+use na::Transform;
+use na;
+use bounding_volume::{HasKDOP, KDOP, KDOPDirections, BoundingVolume, AABB};
+use shape::Compound;
+use math::{Scalar, Point, Vect};
+
+impl<N, P, V, M, M2, D> HasKDOP<N, V, D, M2> for Compound<N, P, V, M>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M2: Transform<P>,
+          D: KDOPDirections<V> {
+    /// Merges the k-DOPs of the parts.
+    ///
+    /// The parts are trait-objects that do not provide their own k-DOP. Thus, each part is
+    /// bounded by the k-DOP of its AABB instead. The k-DOP of an empty compound is invalid.
+    #[inline]
+    fn kdop(&self, m: &M2, directions: &D) -> KDOP<N, V, D> {
+        let mut res = KDOP::new_invalid(directions.clone());
+
+        for bv in self.bounding_volumes().iter() {
+            res.merge(&aabb_kdop(m, bv, directions));
+        }
+
+        res
+    }
+}
+
+// The k-DOP of the corners of an AABB.
+fn aabb_kdop<N, P, V, M, D>(m: &M, bv: &AABB<P>, directions: &D) -> KDOP<N, V, D>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Transform<P>,
+          D: KDOPDirections<V> {
+    let dim     = na::dim::<V>();
+    let mut res = KDOP::new_invalid(directions.clone());
+
+    for c in range(0u, 1 << dim) {
+        let mut corner = bv.mins().clone();
+
+        for i in range(0, dim) {
+            if c & (1 << i) != 0 {
+                corner[i] = bv.maxs()[i];
+            }
+        }
+
+        res.take_point(m.transform(&corner).as_vec());
+    }
+
+    res
+}
@@ -0,0 +1,19 @@
+use na::Transform;
+use bounding_volume::{HasKDOP, KDOP, KDOPDirections};
+use bounding_volume;
+use shape::{Mesh, MeshElement};
+use math::{Scalar, Point, Vect};
+
+
+impl<N, P, V, M, D, E> HasKDOP<N, V, D, M> for Mesh<N, P, V, E>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Transform<P>,
+          D: KDOPDirections<V>,
+          E: MeshElement<P> {
+    #[inline]
+    fn kdop(&self, m: &M, directions: &D) -> KDOP<N, V, D> {
+        bounding_volume::point_cloud_kdop(m, self.vertices().as_slice(), directions)
+    }
+}
@@ -0,0 +1,23 @@
+use na::Bounded;
+use na;
+use bounding_volume::{HasKDOP, KDOP, KDOPDirections};
+use shape::Plane;
+use math::{Scalar, Vect};
+
+impl<N, V, M, D> HasKDOP<N, V, D, M> for Plane<V>
+    where N: Scalar,
+          V: Vect<N>,
+          D: KDOPDirections<V> {
+    #[inline]
+    fn kdop(&self, _: &M, directions: &D) -> KDOP<N, V, D> {
+        // Like for the AABB, we divide by 2.0 so that the k-DOP can still be loosened.
+        let max: N  = Bounded::max_value();
+        let half: N = na::cast(0.5f64);
+        let half_max = max * half;
+        let n        = directions.ndirections();
+
+        KDOP::new(directions.clone(),
+                  Vec::from_elem(n, -half_max).as_slice(),
+                  Vec::from_elem(n, half_max).as_slice())
+    }
+}
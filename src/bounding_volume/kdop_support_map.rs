@@ -0,0 +1,102 @@
+use na::{Transform, Rotate, Translate};
+use bounding_volume::{HasKDOP, KDOP, KDOPDirections};
+use bounding_volume;
+use shape::{Ball, Cuboid, Cone, Cylinder, Capsule, Convex};
+use shape::{Triangle, Segment};
+use math::{Scalar, Point, Vect};
+
+impl<N, P, V, M, D> HasKDOP<N, V, D, M> for Ball<N>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Translate<P>,
+          D: KDOPDirections<V> {
+    #[inline]
+    fn kdop(&self, m: &M, directions: &D) -> KDOP<N, V, D> {
+        bounding_volume::implicit_shape_kdop(m, self, directions)
+    }
+}
+
+impl<N, P, V, M, D> HasKDOP<N, V, D, M> for Cuboid<V>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Transform<P> + Rotate<V>,
+          D: KDOPDirections<V> {
+    #[inline]
+    fn kdop(&self, m: &M, directions: &D) -> KDOP<N, V, D> {
+        bounding_volume::implicit_shape_kdop(m, self, directions)
+    }
+}
+
+impl<N, P, V, M, D> HasKDOP<N, V, D, M> for Cone<N>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Transform<P> + Rotate<V>,
+          D: KDOPDirections<V> {
+    #[inline]
+    fn kdop(&self, m: &M, directions: &D) -> KDOP<N, V, D> {
+        bounding_volume::implicit_shape_kdop(m, self, directions)
+    }
+}
+
+impl<N, P, V, M, D> HasKDOP<N, V, D, M> for Cylinder<N>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Transform<P> + Rotate<V>,
+          D: KDOPDirections<V> {
+    #[inline]
+    fn kdop(&self, m: &M, directions: &D) -> KDOP<N, V, D> {
+        bounding_volume::implicit_shape_kdop(m, self, directions)
+    }
+}
+
+impl<N, P, V, M, D> HasKDOP<N, V, D, M> for Capsule<N>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Transform<P> + Rotate<V>,
+          D: KDOPDirections<V> {
+    #[inline]
+    fn kdop(&self, m: &M, directions: &D) -> KDOP<N, V, D> {
+        bounding_volume::implicit_shape_kdop(m, self, directions)
+    }
+}
+
+impl<N, P, V, M, D> HasKDOP<N, V, D, M> for Triangle<P>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Transform<P> + Rotate<V>,
+          D: KDOPDirections<V> {
+    #[inline]
+    fn kdop(&self, m: &M, directions: &D) -> KDOP<N, V, D> {
+        bounding_volume::implicit_shape_kdop(m, self, directions)
+    }
+}
+
+impl<N, P, V, M, D> HasKDOP<N, V, D, M> for Segment<P>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Transform<P> + Rotate<V>,
+          D: KDOPDirections<V> {
+    #[inline]
+    fn kdop(&self, m: &M, directions: &D) -> KDOP<N, V, D> {
+        bounding_volume::implicit_shape_kdop(m, self, directions)
+    }
+}
+
+impl<N, P, V, M, D> HasKDOP<N, V, D, M> for Convex<P>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Transform<P> + Rotate<V>,
+          D: KDOPDirections<V> {
+    #[inline]
+    fn kdop(&self, m: &M, directions: &D) -> KDOP<N, V, D> {
+        bounding_volume::implicit_shape_kdop(m, self, directions)
+    }
+}
@@ -1,17 +1,27 @@
 //! Bounding volumes.
 
 #[doc(inline)]
-pub use bounding_volume::bounding_volume::{HasBoundingVolume, BoundingVolume};
+pub use bounding_volume::bounding_volume::{HasBoundingVolume, BoundingVolume, HasSurfaceArea,
+                                           SweptBoundingVolume};
 #[doc(inline)]
 pub use bounding_volume::aabb::{HasAABB, AABB};
 #[doc(inline)]
 pub use bounding_volume::bounding_sphere::{HasBoundingSphere, BoundingSphere};
+#[doc(inline)]
+pub use bounding_volume::obb::{HasOBB, OBB, PrincipalFrame};
+#[doc(inline)]
+pub use bounding_volume::frustum::{Frustum, FrustumClassifiable, Containment};
+#[doc(inline)]
+pub use bounding_volume::kdop::{HasKDOP, KDOP, KDOPDirections, DOP8, DOP14, DOP18, DOP26};
 
-pub use bounding_volume::aabb_utils::{implicit_shape_aabb, point_cloud_aabb};
+pub use bounding_volume::aabb_utils::{implicit_shape_aabb, point_cloud_aabb, swept_aabb};
 pub use bounding_volume::aabb_ball::ball_aabb;
-pub use bounding_volume::bounding_sphere_utils::{point_cloud_bounding_sphere_with_center, point_cloud_bounding_sphere};
+pub use bounding_volume::bounding_sphere_utils::{point_cloud_bounding_sphere_with_center, point_cloud_bounding_sphere,
+                                                 point_cloud_minimal_bounding_sphere};
+pub use bounding_volume::obb::{point_cloud_obb, point_cloud_obb_with_frame};
+pub use bounding_volume::kdop::{implicit_shape_kdop, point_cloud_kdop};
 
-use na::{Pnt2, Pnt3};
+use na::{Pnt2, Pnt3, Vec2, Vec3, Iso2, Iso3};
 
 #[doc(hidden)]
 pub mod bounding_volume;
@@ -24,6 +34,7 @@ mod aabb_plane;
 mod aabb_convex;
 mod aabb_compound;
 mod aabb_mesh;
+mod aabb_bezier_curve;
 mod aabb_bezier_surface;
 mod aabb_utils;
 
@@ -43,6 +54,30 @@ mod bounding_sphere_mesh;
 mod bounding_sphere_bezier_surface;
 mod bounding_sphere_utils;
 
+#[doc(hidden)]
+pub mod obb;
+mod obb_cuboid;
+mod obb_support_map;
+mod obb_ball;
+mod obb_plane;
+mod obb_convex;
+mod obb_compound;
+mod obb_mesh;
+mod obb_bezier_curve;
+mod obb_bezier_surface;
+
+#[doc(hidden)]
+pub mod frustum;
+
+#[doc(hidden)]
+pub mod kdop;
+mod kdop_support_map;
+mod kdop_compound;
+mod kdop_mesh;
+mod kdop_plane;
+mod kdop_bezier_curve;
+mod kdop_bezier_surface;
+
 /*
  *
  * Aliases.
@@ -52,8 +87,20 @@ mod bounding_sphere_utils;
 pub type BoundingSphere2<N> = BoundingSphere<N, Pnt2<N>>;
 /// A 2D AABB.
 pub type AABB2<N> = AABB<Pnt2<N>>;
+/// A 2D OBB.
+pub type OBB2<N> = OBB<Vec2<N>, Iso2<N>>;
+/// A 2D 8-DOP.
+pub type KDOP8<N> = KDOP<N, Vec2<N>, DOP8>;
 
 /// A 3D bounding sphere:
 pub type BoundingSphere3<N> = BoundingSphere<N, Pnt3<N>>;
 /// A 3D AABB.
 pub type AABB3<N> = AABB<Pnt3<N>>;
+/// A 3D OBB.
+pub type OBB3<N> = OBB<Vec3<N>, Iso3<N>>;
+/// A 3D 14-DOP.
+pub type KDOP14<N> = KDOP<N, Vec3<N>, DOP14>;
+/// A 3D 18-DOP.
+pub type KDOP18<N> = KDOP<N, Vec3<N>, DOP18>;
+/// A 3D 26-DOP.
+pub type KDOP26<N> = KDOP<N, Vec3<N>, DOP26>;
@@ -0,0 +1,543 @@
+//! Oriented Bounding Box.
+
+use std::num::Float;
+use na::{Translation, Pnt2, Pnt3, Vec1, Vec3, Mat2, Mat3, Iso2, Iso3, Rot3, Col};
+use na;
+use bounding_volume::{BoundingVolume, HasSurfaceArea, SweptBoundingVolume};
+use utils;
+use math::{Scalar, Point, Vect, Isometry};
+
+/// Trait of objects that can be bounded by an OBB.
+pub trait HasOBB<V, M> {
+    /// The object’s OBB.
+    fn obb(&self, &M) -> OBB<V, M>;
+}
+
+/// An Oriented Bounding Box.
+///
+/// It is a box with half extents `half_extents`, centered at the origin of the local frame given
+/// by `transform`.
+#[deriving(Show, PartialEq, Clone, Encodable, Decodable)]
+pub struct OBB<V, M> {
+    transform:    M,
+    half_extents: V
+}
+
+impl<V, M> OBB<V, M> {
+    /// Creates a new OBB.
+    ///
+    /// # Arguments:
+    ///   * `transform` - the local frame of the box. Its origin is the center of the box.
+    ///   * `half_extents` - the half extents of the box along each axis of its local frame. Each
+    ///   component must be positive.
+    pub fn new(transform: M, half_extents: V) -> OBB<V, M> {
+        OBB {
+            transform:    transform,
+            half_extents: half_extents
+        }
+    }
+
+    /// The local frame of this OBB.
+    #[inline]
+    pub fn transform(&self) -> &M {
+        &self.transform
+    }
+
+    /// The half extents of this OBB along each axis of its local frame.
+    #[inline]
+    pub fn half_extents(&self) -> &V {
+        &self.half_extents
+    }
+}
+
+impl<N, P, V, M> OBB<V, M>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Isometry<N, P, V> {
+    /// Creates the OBB of an AABB expressed in the local frame `m`.
+    pub fn new_from_local_aabb(m: &M, mins: &P, maxs: &P) -> OBB<V, M> {
+        let _0_5: N   = na::cast(0.5f64);
+        let ls_center = na::center(mins, maxs);
+        let mut frame = m.clone();
+
+        frame.set_translation(m.transform(&ls_center).to_vec());
+
+        OBB::new(frame, (*maxs - *mins) * _0_5)
+    }
+
+    /// The center of this OBB.
+    #[inline]
+    pub fn center(&self) -> P {
+        self.transform.transform(&na::orig())
+    }
+
+    /// The i-th axis of the local frame of this OBB, expressed in world-space.
+    #[inline]
+    pub fn axis(&self, i: uint) -> V {
+        let mut basis = na::zero::<V>();
+        basis[i] = na::one();
+
+        self.transform.rotate(&basis)
+    }
+
+    /// The `2^dim` corners of this OBB.
+    pub fn corners(&self) -> Vec<P> {
+        let dim = na::dim::<V>();
+
+        range(0u, 1 << dim).map(|c| {
+            let mut local = self.half_extents.clone();
+
+            for i in range(0, dim) {
+                if c & (1 << i) != 0 {
+                    local[i] = -local[i];
+                }
+            }
+
+            self.transform.transform(&(na::orig::<P>() + local))
+        }).collect()
+    }
+
+    // The half-length of the projection of this OBB on the given axis.
+    #[inline]
+    fn projection_radius(&self, axis: &V) -> N {
+        na::dot(&na::abs(&self.transform.inv_rotate(axis)), &self.half_extents)
+    }
+
+    // Computes the AABB of the given points, and of this box, in the local frame of this box.
+    fn local_aabb_with_points(&self, pts: &[P]) -> (P, P) {
+        let mut mins = na::orig::<P>() + (-self.half_extents);
+        let mut maxs = na::orig::<P>() + self.half_extents.clone();
+
+        for pt in pts.iter() {
+            let local = self.transform.inv_transform(pt);
+
+            mins = na::inf(&mins, &local);
+            maxs = na::sup(&maxs, &local);
+        }
+
+        (mins, maxs)
+    }
+}
+
+impl<N, P, V, M> BoundingVolume<N> for OBB<V, M>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Isometry<N, P, V> {
+    /// Tests the intersection of two OBBs with the separating axis theorem.
+    fn intersects(&self, other: &OBB<V, M>) -> bool {
+        let dim   = na::dim::<V>();
+        let delta = other.center() - self.center();
+
+        let mut axes = Vec::with_capacity(dim * 2 + dim * dim);
+
+        for i in range(0, dim) {
+            axes.push(self.axis(i));
+            axes.push(other.axis(i));
+        }
+
+        if dim == 3 {
+            // The edges cross products are required in 3d.
+            for i in range(0, dim) {
+                for j in range(0, dim) {
+                    axes.push(utils::cross3(&self.axis(i), &other.axis(j)));
+                }
+            }
+        }
+
+        for axis in axes.iter() {
+            if na::is_zero(&na::sqnorm(axis)) {
+                continue;
+            }
+
+            let dist = na::dot(axis, &delta).abs();
+
+            if dist > self.projection_radius(axis) + other.projection_radius(axis) {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn contains(&self, other: &OBB<V, M>) -> bool {
+        other.corners().iter().all(|corner| {
+            let local = self.transform.inv_transform(corner);
+
+            range(0, na::dim::<V>()).all(|i| local[i].abs() <= self.half_extents[i])
+        })
+    }
+
+    /// Merges this OBB with another one. The orientation of this OBB is kept.
+    #[inline]
+    fn merge(&mut self, other: &OBB<V, M>) {
+        *self = self.merged(other)
+    }
+
+    /// Computes an OBB enclosing this OBB and another one. The orientation of this OBB is kept.
+    fn merged(&self, other: &OBB<V, M>) -> OBB<V, M> {
+        let (mins, maxs) = self.local_aabb_with_points(other.corners().as_slice());
+
+        OBB::new_from_local_aabb(&self.transform, &mins, &maxs)
+    }
+
+    #[inline]
+    fn loosen(&mut self, amount: N) {
+        assert!(amount >= na::zero(), "The loosening margin must be positive.");
+
+        for i in range(0, na::dim::<V>()) {
+            self.half_extents[i] = self.half_extents[i] + amount;
+        }
+    }
+
+    #[inline]
+    fn loosened(&self, amount: N) -> OBB<V, M> {
+        let mut res = self.clone();
+        res.loosen(amount);
+
+        res
+    }
+
+    #[inline]
+    fn tighten(&mut self, amount: N) {
+        assert!(amount >= na::zero(), "The tightening margin must be positive.");
+
+        for i in range(0, na::dim::<V>()) {
+            self.half_extents[i] = self.half_extents[i] - amount;
+            assert!(self.half_extents[i] >= na::zero(), "The tightening margin is to large.");
+        }
+    }
+
+    #[inline]
+    fn tightened(&self, amount: N) -> OBB<V, M> {
+        let mut res = self.clone();
+        res.tighten(amount);
+
+        res
+    }
+}
+
+impl<N, P, V, M> HasSurfaceArea<N> for OBB<V, M>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Isometry<N, P, V> {
+    #[inline]
+    fn surface_area(&self) -> N {
+        let _2: N   = na::cast(2.0f64);
+        let extents = self.half_extents.clone() * _2;
+        let dim     = na::dim::<V>();
+        let mut res = na::zero::<N>();
+
+        for i in range(0, dim) {
+            let mut face: N = na::one();
+
+            for j in range(0, dim) {
+                if i != j {
+                    face = face * extents[j];
+                }
+            }
+
+            res = res + face;
+        }
+
+        res + res
+    }
+}
+
+impl<N, P, V, M> SweptBoundingVolume<V> for OBB<V, M>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Isometry<N, P, V> {
+    /// The orientation of this OBB is kept.
+    #[inline]
+    fn swept(&self, dv: &V) -> OBB<V, M> {
+        self.merged(&Translation::append_translation_cpy(self, dv))
+    }
+}
+
+impl<N, P, V, M> Translation<V> for OBB<V, M>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Isometry<N, P, V> {
+    #[inline]
+    fn translation(&self) -> V {
+        self.transform.translation()
+    }
+
+    #[inline]
+    fn inv_translation(&self) -> V {
+        -self.translation()
+    }
+
+    #[inline]
+    fn append_translation(&mut self, dv: &V) {
+        self.transform.append_translation(dv)
+    }
+
+    #[inline]
+    fn append_translation_cpy(obb: &OBB<V, M>, dv: &V) -> OBB<V, M> {
+        OBB::new(Translation::append_translation_cpy(&obb.transform, dv), obb.half_extents.clone())
+    }
+
+    #[inline]
+    fn prepend_translation(&mut self, dv: &V) {
+        self.append_translation(dv)
+    }
+
+    #[inline]
+    fn prepend_translation_cpy(obb: &OBB<V, M>, dv: &V) -> OBB<V, M> {
+        Translation::append_translation_cpy(obb, dv)
+    }
+
+    #[inline]
+    fn set_translation(&mut self, v: V) {
+        self.transform.set_translation(v)
+    }
+}
+
+/// Trait of isometries which can be built from the principal axes of a point cloud.
+pub trait PrincipalFrame<P> {
+    /// Computes the frame centered at the center of `pts`, with axes aligned with the
+    /// eigenvectors of the covariance matrix of `pts`.
+    fn principal_frame(pts: &[P]) -> Self;
+
+    /// Computes the frame centered at `o`, with its first axis directed toward `a` and its second
+    /// axis lying on the plane containing `o`, `a` and `b`.
+    ///
+    /// The axes which are not determined by the three points (e.g. when they are aligned) are
+    /// chosen arbitrarily.
+    fn frame_with_points(o: &P, a: &P, b: &P) -> Self;
+}
+
+impl<N: Scalar> PrincipalFrame<Pnt2<N>> for Iso2<N> {
+    fn principal_frame(pts: &[Pnt2<N>]) -> Iso2<N> {
+        let (cov, center): (Mat2<N>, Pnt2<N>) = utils::cov_and_center(pts);
+        let _2: N = na::cast(2.0f64);
+
+        // Angle of the eigenvector with the largest eigenvalue.
+        let angle = (cov.m12 * _2).atan2(cov.m11 - cov.m22) / _2;
+
+        Iso2::new(center.to_vec(), Vec1::new(angle))
+    }
+
+    fn frame_with_points(o: &Pnt2<N>, a: &Pnt2<N>, _: &Pnt2<N>) -> Iso2<N> {
+        let x = *a - *o;
+
+        if na::is_zero(&na::sqnorm(&x)) {
+            Iso2::new(o.to_vec(), na::zero())
+        }
+        else {
+            Iso2::new(o.to_vec(), Vec1::new(x.y.atan2(x.x)))
+        }
+    }
+}
+
+impl<N: Scalar> PrincipalFrame<Pnt3<N>> for Iso3<N> {
+    fn principal_frame(pts: &[Pnt3<N>]) -> Iso3<N> {
+        let (cov, center): (Mat3<N>, Pnt3<N>) = utils::cov_and_center(pts);
+        let (eigvec, _) = na::eigen_qr(&cov, &Float::epsilon(), 1000);
+
+        let mut x = na::normalize(&eigvec.col(0));
+        let y     = na::normalize(&eigvec.col(1));
+        let z     = na::normalize(&eigvec.col(2));
+
+        // Ensure the frame is right-handed.
+        if na::dot(&na::cross(&x, &y), &z) < na::zero() {
+            x = -x;
+        }
+
+        let basis = Mat3::new(
+            x.x, y.x, z.x,
+            x.y, y.y, z.y,
+            x.z, y.z, z.z
+        );
+
+        Iso3::new_with_rotmat(center.to_vec(), unsafe { Rot3::new_with_mat(basis) })
+    }
+
+    fn frame_with_points(o: &Pnt3<N>, a: &Pnt3<N>, b: &Pnt3<N>) -> Iso3<N> {
+        let x  = *a - *o;
+        let lx = na::norm(&x);
+
+        if na::is_zero(&lx) {
+            return Iso3::new(o.to_vec(), na::zero());
+        }
+
+        let _eps: N = Float::epsilon();
+        let x       = x / lx;
+        let ob      = *b - *o;
+        let mut y   = ob - x * na::dot(&ob, &x);
+        let ly      = na::norm(&y);
+
+        if ly <= lx * _eps.sqrt() {
+            // `b` is on the line (o, a): pick the canonical axis least aligned with `x`.
+            let mut e: Vec3<N> = na::zero();
+
+            if x.x.abs() <= x.y.abs() && x.x.abs() <= x.z.abs() {
+                e.x = na::one();
+            }
+            else if x.y.abs() <= x.z.abs() {
+                e.y = na::one();
+            }
+            else {
+                e.z = na::one();
+            }
+
+            y = na::normalize(&na::cross(&x, &e));
+        }
+        else {
+            y = y / ly;
+        }
+
+        let z = na::cross(&x, &y);
+
+        let basis = Mat3::new(
+            x.x, y.x, z.x,
+            x.y, y.y, z.y,
+            x.z, y.z, z.z
+        );
+
+        Iso3::new_with_rotmat(o.to_vec(), unsafe { Rot3::new_with_mat(basis) })
+    }
+}
+
+/// Computes an OBB of a point cloud, aligned with its principal axes.
+///
+/// The orientation of the OBB is given by the eigenvectors of the covariance matrix of the
+/// points, computed with `utils::cov_and_center`.
+pub fn point_cloud_obb<N, P, V, M>(m: &M, pts: &[P]) -> OBB<V, M>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Isometry<N, P, V> + PrincipalFrame<P> {
+    assert!(!pts.is_empty(), "Cannot compute the OBB of an empty point cloud.");
+
+    let frame: M = PrincipalFrame::principal_frame(pts);
+
+    point_cloud_obb_with_frame(m, frame, pts)
+}
+
+/// Computes the OBB of a point cloud, with the orientation of the given `frame`.
+///
+/// The `frame` is expressed in the local space of the points, and is then transformed by `m`.
+pub fn point_cloud_obb_with_frame<N, P, V, M>(m: &M, frame: M, pts: &[P]) -> OBB<V, M>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Isometry<N, P, V> {
+    assert!(!pts.is_empty(), "Cannot compute the OBB of an empty point cloud.");
+
+    let mut mins  = frame.inv_transform(&pts[0]);
+    let mut maxs  = mins.clone();
+
+    for pt in pts.slice_from(1).iter() {
+        let local = frame.inv_transform(pt);
+
+        mins = na::inf(&mins, &local);
+        maxs = na::sup(&maxs, &local);
+    }
+
+    OBB::new_from_local_aabb(&(m.clone() * frame), &mins, &maxs)
+}
+
+#[cfg(test)]
+mod test {
+    use std::rand::{IsaacRng, Rng};
+    use na::{Iso3, Vec3, Pnt3, Mat3, Transform, Rotate};
+    use na;
+    use bounding_volume::{OBB, AABB, HasOBB, BoundingVolume};
+    use point::LocalPointQuery;
+    use ray::{Ray, LocalRayCast};
+    use shape::{BezierCurve, Compound, CompoundData};
+
+    fn random_vec(rng: &mut IsaacRng, min: f64, max: f64) -> Vec3<f64> {
+        let d = max - min;
+
+        Vec3::new(rng.gen::<f64>() * d + min, rng.gen::<f64>() * d + min, rng.gen::<f64>() * d + min)
+    }
+
+    #[test]
+    fn test_obb_intersection() {
+        let he = Vec3::new(2.0f64, 0.1, 0.1);
+
+        // Two thin rods, rotated by 90 degrees around the `z` axis.
+        let a = OBB::new(Iso3::new(na::zero(), na::zero()), he);
+        let b = OBB::new(Iso3::new(Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 0.0, 1.57079632679)), he);
+        let c = OBB::new(Iso3::new(Vec3::new(0.0, 2.5, 0.0), Vec3::new(0.0, 0.0, 1.57079632679)), he);
+        let d = OBB::new(Iso3::new(Vec3::new(3.0, 3.0, 0.0), Vec3::new(0.0, 0.0, 0.78539816339)), he);
+
+        assert!(a.intersects(&b));
+        assert!(!a.intersects(&c));
+        assert!(!a.intersects(&d));
+        assert!(a.merged(&b).contains(&b));
+    }
+
+    // The queries on an OBB match those on its AABB in its local frame.
+    #[test]
+    fn test_obb_ray_and_point_queries() {
+        let mut rng = IsaacRng::new_unseeded();
+
+        for _ in range(0u, 20) {
+            let m    = Iso3::new(random_vec(&mut rng, -5.0, 5.0), random_vec(&mut rng, -3.0, 3.0));
+            let he   = random_vec(&mut rng, 0.1, 2.0);
+            let obb  = OBB::new(m, he);
+            let aabb = AABB::new(na::orig::<Pnt3<f64>>() + (-he), na::orig::<Pnt3<f64>>() + he);
+
+            for _ in range(0u, 20) {
+                let pt    = na::orig::<Pnt3<f64>>() + random_vec(&mut rng, -10.0, 10.0);
+                let ls_pt = m.inv_transform(&pt);
+
+                assert!(obb.contains_point(&pt) == aabb.contains_point(&ls_pt));
+                assert!(na::approx_eq(&obb.distance_to_point(&pt), &aabb.distance_to_point(&ls_pt)));
+
+                for solid in [ true, false ].iter() {
+                    let proj = obb.project_point(&pt, *solid);
+
+                    assert!(na::approx_eq(&proj, &m.transform(&aabb.project_point(&ls_pt, *solid))));
+                }
+
+                let dir    = na::normalize(&(na::translation(&m) - pt.to_vec() + random_vec(&mut rng, -1.0, 1.0)));
+                let ray    = Ray::new(pt, dir);
+                let ls_ray = Ray::new(ls_pt, m.inv_rotate(&dir));
+
+                for solid in [ true, false ].iter() {
+                    let inter    = obb.toi_and_normal_with_ray(&ray, *solid);
+                    let expected = aabb.toi_and_normal_with_ray(&ls_ray, *solid);
+
+                    match (inter, expected) {
+                        (Some(inter), Some(expected)) => {
+                            assert!(na::approx_eq(&inter.toi, &expected.toi));
+                            assert!(na::approx_eq(&inter.normal, &m.rotate(&expected.normal)));
+                            assert!(obb.toi_with_ray(&ray, *solid).is_some());
+                        },
+                        (None, None) => assert!(obb.toi_with_ray(&ray, *solid).is_none()),
+                        _            => panic!("The OBB and AABB ray casts disagree.")
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_obb_bezier_curve_and_empty_compound() {
+        let m: Iso3<f64> = na::one();
+        let curve = BezierCurve::new(vec!(Pnt3::new(0.0f64, 0.0, 0.0), Pnt3::new(1.0, 2.0, 0.0),
+                                          Pnt3::new(2.0, 0.0, 1.0), Pnt3::new(3.0, 1.0, 1.0)));
+        let obb   = curve.obb(&m);
+
+        for pt in curve.control_points().iter() {
+            assert!(obb.loosened(1.0e-7).contains_point(pt));
+        }
+
+        let empty: Compound<f64, Pnt3<f64>, Vec3<f64>, Iso3<f64>> =
+            Compound::new(CompoundData::<f64, Pnt3<f64>, Vec3<f64>, Iso3<f64>, Mat3<f64>>::new());
+        let obb = empty.obb(&Iso3::new(Vec3::new(1.0, 2.0, 3.0), na::zero()));
+
+        assert!(*obb.half_extents() == na::zero::<Vec3<f64>>());
+        assert!(obb.center() == Pnt3::new(1.0, 2.0, 3.0));
+    }
+}
@@ -0,0 +1,21 @@
+use na;
+use bounding_volume::{HasOBB, OBB};
+use shape::Ball;
+use math::{Scalar, Point, Vect, Isometry};
+
+impl<N, P, V, M> HasOBB<V, M> for Ball<N>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Isometry<N, P, V> {
+    #[inline]
+    fn obb(&self, m: &M) -> OBB<V, M> {
+        let mut half_extents = na::zero::<V>();
+
+        for i in range(0, na::dim::<V>()) {
+            half_extents[i] = self.radius();
+        }
+
+        OBB::new(m.clone(), half_extents)
+    }
+}
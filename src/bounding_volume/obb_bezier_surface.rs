@@ -0,0 +1,15 @@
+use bounding_volume::{HasOBB, OBB, PrincipalFrame};
+use bounding_volume;
+use shape::BezierSurface;
+use math::{Scalar, Point, Vect, Isometry};
+
+impl<N, P, V, M> HasOBB<V, M> for BezierSurface<P>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Isometry<N, P, V> + PrincipalFrame<P> {
+    #[inline]
+    fn obb(&self, m: &M) -> OBB<V, M> {
+        bounding_volume::point_cloud_obb(m, self.control_points())
+    }
+}
@@ -0,0 +1,21 @@
+use na;
+use bounding_volume::{HasOBB, OBB};
+use shape::Compound;
+use math::{Scalar, Point, Vect, Isometry};
+
+impl<N, P, V, M, M2> HasOBB<V, M2> for Compound<N, P, V, M>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M2: Isometry<N, P, V> {
+    /// The OBB of the root AABB of the compound, oriented like `m`.
+    ///
+    /// The OBB of an empty compound is the point at the origin of its local frame.
+    #[inline]
+    fn obb(&self, m: &M2) -> OBB<V, M2> {
+        match self.bvt().root_bounding_volume() {
+            Some(bv) => OBB::new_from_local_aabb(m, bv.mins(), bv.maxs()),
+            None     => OBB::new_from_local_aabb(m, &na::orig(), &na::orig())
+        }
+    }
+}
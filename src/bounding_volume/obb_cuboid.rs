@@ -0,0 +1,14 @@
+use bounding_volume::{HasOBB, OBB};
+use shape::Cuboid;
+use math::{Scalar, Point, Vect, Isometry};
+
+impl<N, P, V, M> HasOBB<V, M> for Cuboid<V>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Isometry<N, P, V> {
+    #[inline]
+    fn obb(&self, m: &M) -> OBB<V, M> {
+        OBB::new(m.clone(), self.half_extents().clone())
+    }
+}
@@ -0,0 +1,17 @@
+use bounding_volume::{HasOBB, OBB, PrincipalFrame};
+use bounding_volume;
+use shape::{Mesh, MeshElement};
+use math::{Scalar, Point, Vect, Isometry};
+
+
+impl<N, P, V, M, E> HasOBB<V, M> for Mesh<N, P, V, E>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Isometry<N, P, V> + PrincipalFrame<P>,
+          E: MeshElement<P> {
+    #[inline]
+    fn obb(&self, m: &M) -> OBB<V, M> {
+        bounding_volume::point_cloud_obb(m, self.vertices().as_slice())
+    }
+}
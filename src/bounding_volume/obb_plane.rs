@@ -0,0 +1,22 @@
+use na::Bounded;
+use na;
+use bounding_volume::{HasOBB, OBB};
+use shape::Plane;
+use math::{Scalar, Point, Vect, Isometry};
+
+
+impl<N, P, V, M> HasOBB<V, M> for Plane<V>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> + Bounded,
+          M: Isometry<N, P, V> {
+    #[inline]
+    fn obb(&self, _: &M) -> OBB<V, M> {
+        // we divide by 2.0  so that we can still make some operations with it (like loosening)
+        // without breaking the box.
+        let max: V  = Bounded::max_value();
+        let half: N = na::cast(0.5f64);
+
+        OBB::new(na::one(), max * half)
+    }
+}
@@ -0,0 +1,123 @@
+use na::Identity;
+use na;
+use bounding_volume::{HasOBB, OBB, PrincipalFrame};
+use bounding_volume;
+use shape::{Cone, Cylinder, Capsule};
+use shape::{Triangle, Segment};
+use math::{Scalar, Point, Vect, Isometry};
+
+impl<N, P, V, M> HasOBB<V, M> for Cone<N>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Isometry<N, P, V> {
+    #[inline]
+    fn obb(&self, m: &M) -> OBB<V, M> {
+        let local = bounding_volume::implicit_shape_aabb(&Identity::new(), self);
+
+        OBB::new_from_local_aabb(m, local.mins(), local.maxs())
+    }
+}
+
+impl<N, P, V, M> HasOBB<V, M> for Cylinder<N>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Isometry<N, P, V> {
+    #[inline]
+    fn obb(&self, m: &M) -> OBB<V, M> {
+        let local = bounding_volume::implicit_shape_aabb(&Identity::new(), self);
+
+        OBB::new_from_local_aabb(m, local.mins(), local.maxs())
+    }
+}
+
+impl<N, P, V, M> HasOBB<V, M> for Capsule<N>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Isometry<N, P, V> {
+    #[inline]
+    fn obb(&self, m: &M) -> OBB<V, M> {
+        let local = bounding_volume::implicit_shape_aabb(&Identity::new(), self);
+
+        OBB::new_from_local_aabb(m, local.mins(), local.maxs())
+    }
+}
+
+impl<N, P, V, M> HasOBB<V, M> for Triangle<P>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Isometry<N, P, V> + PrincipalFrame<P> {
+    #[inline]
+    fn obb(&self, m: &M) -> OBB<V, M> {
+        let pts = [ self.a().clone(), self.b().clone(), self.c().clone() ];
+
+        // The frame is aligned with the longest edge and, in 3D, with the triangle normal.
+        let mut best   = 0u;
+        let mut best_l = na::sqdist(&pts[0], &pts[1]);
+
+        for i in range(1u, 3) {
+            let l = na::sqdist(&pts[i], &pts[(i + 1) % 3]);
+
+            if l > best_l {
+                best   = i;
+                best_l = l;
+            }
+        }
+
+        let frame: M = PrincipalFrame::frame_with_points(&pts[best], &pts[(best + 1) % 3], &pts[(best + 2) % 3]);
+
+        bounding_volume::point_cloud_obb_with_frame(m, frame, pts.as_slice())
+    }
+}
+
+impl<N, P, V, M> HasOBB<V, M> for Segment<P>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Isometry<N, P, V> + PrincipalFrame<P> {
+    #[inline]
+    fn obb(&self, m: &M) -> OBB<V, M> {
+        let pts      = [ self.a().clone(), self.b().clone() ];
+        let frame: M = PrincipalFrame::frame_with_points(&pts[0], &pts[1], &pts[1]);
+
+        bounding_volume::point_cloud_obb_with_frame(m, frame, pts.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use na::{Iso3, Pnt3, Vec3};
+    use na;
+    use bounding_volume::{HasOBB, OBB3};
+    use shape::{Triangle, Segment};
+
+    fn obb_contains(obb: &OBB3<f64>, m: &Iso3<f64>, pt: &Pnt3<f64>) -> bool {
+        let local = na::inv_transform(obb.transform(), &na::transform(m, pt));
+        let he    = obb.half_extents();
+
+        local.x.abs() <= he.x + 1.0e-7 && local.y.abs() <= he.y + 1.0e-7 && local.z.abs() <= he.z + 1.0e-7
+    }
+
+    #[test]
+    fn test_triangle_segment_obb() {
+        let m   = Iso3::new(Vec3::new(1.0f64, -2.0, 3.0), Vec3::new(0.3, -0.2, 0.5));
+        let tri = Triangle::new(Pnt3::new(0.0f64, 0.0, 0.0), Pnt3::new(1.0, 1.0, 1.0), Pnt3::new(2.0, -1.0, 0.5));
+        let obb: OBB3<f64> = tri.obb(&m);
+
+        // The triangle is flat in its OBB frame.
+        assert!(na::approx_eq(&obb.half_extents().z, &0.0));
+        assert!(obb_contains(&obb, &m, tri.a()));
+        assert!(obb_contains(&obb, &m, tri.b()));
+        assert!(obb_contains(&obb, &m, tri.c()));
+
+        let seg = Segment::new(Pnt3::new(0.0f64, 0.0, 0.0), Pnt3::new(1.0, 2.0, 2.0));
+        let obb: OBB3<f64> = seg.obb(&m);
+
+        assert!(na::approx_eq(obb.half_extents(), &Vec3::new(1.5, 0.0, 0.0)));
+        assert!(obb_contains(&obb, &m, seg.a()));
+        assert!(obb_contains(&obb, &m, seg.b()));
+    }
+}
@@ -1,4 +1,5 @@
 use ray::Ray;
+use bounding_volume::{Frustum, FrustumClassifiable};
 
 /// Trait all broad phase must implement.
 pub trait BroadPhase<P, V, B, BV, DV> {
@@ -38,4 +39,8 @@ pub trait BroadPhase<P, V, B, BV, DV> {
 
     /// Collects every object which might contain a given point.
     fn interferences_with_point(&mut self, &P, &mut Vec<B>);
+
+    /// Collects every object which might be inside of a given frustum.
+    fn interferences_with_frustum<N>(&mut self, &Frustum<N>, &mut Vec<B>)
+        where BV: FrustumClassifiable<N>;
 }
@@ -1,5 +1,3 @@
-use std::cell::RefCell;
-use std::rc::Rc;
 use na::Translation;
 use na;
 use broad_phase::BroadPhase;
@@ -8,13 +6,15 @@ use utils::data::hash_map::HashMap;
 use utils::data::pair::{Pair, PairTWHash};
 use utils::data::has_uid::HasUid;
 use broad_phase::Dispatcher;
-use bounding_volume::{HasBoundingVolume, BoundingVolume};
+use bounding_volume::{HasBoundingVolume, BoundingVolume, HasSurfaceArea, SweptBoundingVolume};
+use bounding_volume::{Frustum, FrustumClassifiable};
 use ray::{Ray, LocalRayCast};
 use point::LocalPointQuery;
-use partitioning::{DBVT, DBVTLeaf,
+use partitioning::{DBVT, LeafId,
                    BoundingVolumeInterferencesCollector,
                    RayInterferencesCollector,
-                   PointInterferencesCollector};
+                   PointInterferencesCollector,
+                   FrustumInterferencesCollector};
 use math::{Scalar, Point, Vect};
 
 
@@ -22,41 +22,44 @@ use math::{Scalar, Point, Vect};
 ///
 /// It uses two separate trees: one for static objects and which is never updated, and one for
 /// moving objects.
-pub struct DBVTBroadPhase<N, P, B, BV, D, DV> {
-    tree:        DBVT<P, B, BV>,
-    stree:       DBVT<P, B, BV>,
-    active2bv:   HashMap<uint, Rc<RefCell<DBVTLeaf<P, B, BV>>>, UintTWHash>,
-    inactive2bv: HashMap<uint, Rc<RefCell<DBVTLeaf<P, B, BV>>>, UintTWHash>,
-    pairs:       HashMap<Pair<Rc<RefCell<DBVTLeaf<P, B, BV>>>>, DV, PairTWHash>, // pair manager
-    spairs:      HashMap<Pair<Rc<RefCell<DBVTLeaf<P, B, BV>>>>, DV, PairTWHash>,
-    dispatcher:  D,
-    margin:      N,
-    collector:   Vec<Rc<RefCell<DBVTLeaf<P, B, BV>>>>,
-    to_update:   Vec<Rc<RefCell<DBVTLeaf<P, B, BV>>>>,
-    update_off:  uint // incremental pairs removal index
+pub struct DBVTBroadPhase<N, P, V, B, BV, D, DV> {
+    tree:          DBVT<P, B, BV>,
+    stree:         DBVT<P, B, BV>,
+    active2bv:     HashMap<uint, LeafId, UintTWHash>,
+    inactive2bv:   HashMap<uint, LeafId, UintTWHash>,
+    pairs:         HashMap<Pair<B>, DV, PairTWHash>, // pair manager
+    spairs:        HashMap<Pair<B>, DV, PairTWHash>,
+    displacements: HashMap<uint, V, UintTWHash>, // predicted displacements
+    dispatcher:    D,
+    margin:        N,
+    collector:     Vec<B>,
+    to_update:     Vec<LeafId>,
+    update_off:    uint // incremental pairs removal index
 }
 
-impl<N, P, V, B, BV, D, DV> DBVTBroadPhase<N, P, B, BV, D, DV>
+impl<N, P, V, B, BV, D, DV> DBVTBroadPhase<N, P, V, B, BV, D, DV>
     where N:  Scalar,
           P:  Point<N, V>,
           V:  Vect<N>,
-          B:  'static + HasBoundingVolume<BV> + Clone,
-          BV: 'static + BoundingVolume<N> + Translation<V> + Clone,
+          B:  'static + HasBoundingVolume<BV> + HasUid + Clone,
+          BV: 'static + BoundingVolume<N> + HasSurfaceArea<N> + SweptBoundingVolume<V> + Translation<V> +
+              Clone,
           D:  Dispatcher<B, B, DV> {
     /// Creates a new broad phase based on a Dynamic Bounding Volume Tree.
-    pub fn new(dispatcher: D, margin: N) -> DBVTBroadPhase<N, P, B, BV, D, DV> {
+    pub fn new(dispatcher: D, margin: N) -> DBVTBroadPhase<N, P, V, B, BV, D, DV> {
         DBVTBroadPhase {
-            tree:        DBVT::new(),
-            stree:       DBVT::new(),
-            active2bv:   HashMap::new(UintTWHash::new()),
-            inactive2bv: HashMap::new(UintTWHash::new()),
-            pairs:       HashMap::new(PairTWHash::new()),
-            spairs:      HashMap::new(PairTWHash::new()),
-            dispatcher:  dispatcher,
-            update_off:  0,
-            collector:   Vec::new(),
-            to_update:   Vec::new(),
-            margin:      margin
+            tree:          DBVT::new(),
+            stree:         DBVT::new(),
+            active2bv:     HashMap::new(UintTWHash::new()),
+            inactive2bv:   HashMap::new(UintTWHash::new()),
+            pairs:         HashMap::new(PairTWHash::new()),
+            spairs:        HashMap::new(PairTWHash::new()),
+            displacements: HashMap::new(UintTWHash::new()),
+            dispatcher:    dispatcher,
+            update_off:    0,
+            collector:     Vec::new(),
+            to_update:     Vec::new(),
+            margin:        margin
         }
     }
 
@@ -66,26 +69,68 @@ impl<N, P, V, B, BV, D, DV> DBVTBroadPhase<N, P, B, BV, D, DV>
         self.pairs.len()
     }
 
+    /// Sets the displacement an object is predicted to undergo.
+    ///
+    /// The bounding volume of the object is then swept along this displacement so that the
+    /// interferences it may encounter during its motion are reported as well. This is useful for
+    /// continuous collision detection. The displacement is kept by every subsequent update until
+    /// it is replaced by another call to this method or removed by
+    /// `clear_predicted_displacement`.
+    pub fn set_predicted_displacement(&mut self, b: &B, displacement: V) {
+        let _ = self.displacements.insert_or_replace(b.uid(), displacement, true);
+    }
+
+    /// Removes the predicted displacement of an object.
+    ///
+    /// The swept bounding volume already stored is kept until the object leaves it, like any
+    /// other enlarged bounding volume.
+    pub fn clear_predicted_displacement(&mut self, b: &B) {
+        self.displacements.remove(&b.uid());
+    }
+
+    // The bounding volume of an object, swept along its predicted displacement.
+    fn predicted_bounding_volume(&self, b: &B) -> BV {
+        let bv = b.bounding_volume();
+
+        match self.displacements.find(&b.uid()) {
+            Some(dv) => bv.swept(dv),
+            None     => bv
+        }
+    }
+
+    // The bounding volume of the object with the given uid, from the active or the inactive tree.
+    fn bounding_volume_of(&self, uid: uint) -> Option<&BV> {
+        match self.active2bv.find(&uid) {
+            Some(id) => self.tree.get(*id).map(|l| &l.bounding_volume),
+            None     => {
+                match self.inactive2bv.find(&uid) {
+                    Some(id) => self.stree.get(*id).map(|l| &l.bounding_volume),
+                    None     => None
+                }
+            }
+        }
+    }
+
     fn update_updatable(&mut self) {
         /*
-         * Re-insert outdated nodes one by one and collect interferences at the same time.
+         * Collect interferences of the updated leaves.
          */
         let mut new_colls = 0u;
 
         for u in self.to_update.iter() {
-            { // scope to avoid dynamic borrow failure.
-                let bu = u.borrow();
-                self.tree.interferences_with_leaf(bu.deref(), &mut self.collector);
-                self.stree.interferences_with_leaf(bu.deref(), &mut self.collector);
+            {
+                let leaf = self.tree.get(*u).expect("Internal error: updated leaf not found.");
+
+                self.tree.interferences_with_leaf(*u, &mut self.collector);
+                self.stree.interferences_with_bounding_volume(&leaf.bounding_volume, &mut self.collector);
 
                 // dispatch
                 for i in self.collector.iter() {
-                    let bi = i.borrow();
-                    if self.dispatcher.is_valid(&bu.object, &bi.object) {
+                    if self.dispatcher.is_valid(&leaf.object, i) {
                         let dispatcher = &mut self.dispatcher;
                         let _ = self.pairs.find_or_insert_lazy(
-                            Pair::new(u.clone(), i.clone()),
-                            || dispatcher.dispatch(&bu.object, &bi.object)
+                            Pair::new(leaf.object.clone(), i.clone()),
+                            || dispatcher.dispatch(&leaf.object, i)
                             );
 
                         new_colls = new_colls + 1;
@@ -94,7 +139,6 @@ impl<N, P, V, B, BV, D, DV> DBVTBroadPhase<N, P, B, BV, D, DV>
             }
 
             self.collector.clear();
-            self.tree.insert(u.clone());
         }
 
         /*
@@ -113,13 +157,10 @@ impl<N, P, V, B, BV, D, DV> DBVTBroadPhase<N, P, B, BV, D, DV>
                     let elts  = self.pairs.elements();
                     let entry = &elts[id];
 
-                    let bf = entry.key.first.borrow();
-                    let bs = entry.key.second.borrow();
-                    if !bf.bounding_volume.intersects(&bs.bounding_volume) {
-                        true
-                    }
-                    else {
-                        false
+                    match (self.bounding_volume_of(entry.key.first.uid()),
+                           self.bounding_volume_of(entry.key.second.uid())) {
+                        (Some(bf), Some(bs)) => !bf.intersects(bs),
+                        _                    => true
                     }
                 };
 
@@ -135,19 +176,21 @@ impl<N, P, V, B, BV, D, DV> DBVTBroadPhase<N, P, B, BV, D, DV>
     }
 }
 
-impl<N, P, V, B, BV, D, DV> BroadPhase<P, V, B, BV, DV> for DBVTBroadPhase<N, P, B, BV, D, DV>
+impl<N, P, V, B, BV, D, DV> BroadPhase<P, V, B, BV, DV> for DBVTBroadPhase<N, P, V, B, BV, D, DV>
     where N:  Scalar,
           P:  Point<N, V>,
           V:  Vect<N>,
           B:  'static + HasBoundingVolume<BV> + HasUid + Clone,
-          BV: 'static + BoundingVolume<N> + Translation<V> + LocalRayCast<N, P, V> + LocalPointQuery<N, P> + Clone,
+          BV: 'static + BoundingVolume<N> + HasSurfaceArea<N> + SweptBoundingVolume<V> + Translation<V> +
+              LocalRayCast<N, P, V> + LocalPointQuery<N, P> + Clone,
           D:  Dispatcher<B, B, DV> {
     #[inline]
     fn add(&mut self, b: B) {
         let id   = b.uid();
-        let leaf = Rc::new(RefCell::new(DBVTLeaf::new(b.bounding_volume().loosened(self.margin.clone()), b)));
+        let bv   = self.predicted_bounding_volume(&b).loosened(self.margin.clone());
+        let leaf = self.tree.insert_new(b, bv);
 
-        self.to_update.push(leaf.clone());
+        self.to_update.push(leaf);
         self.update_updatable();
 
         self.active2bv.insert(id, leaf);
@@ -155,23 +198,16 @@ impl<N, P, V, B, BV, D, DV> BroadPhase<P, V, B, BV, DV> for DBVTBroadPhase<N, P,
 
     fn remove(&mut self, b: &B) {
         // remove b from the dbvts
-        let key      = b.uid();
-        let leaf_opt = self.active2bv.get_and_remove(&key);
-        let mut leaf;
-
-        match leaf_opt {
-            Some(l) => {
-                leaf = l.value;
-                self.tree.remove(&mut leaf);
-            },
-            None => {
-                let leaf_opt = self.inactive2bv.get_and_remove(&key);
-                match leaf_opt {
-                    Some(l) => {
-                        leaf = l.value;
-                        self.stree.remove(&mut leaf);
-                    },
-                    None => return
+        let key = b.uid();
+
+        self.displacements.remove(&key);
+
+        match self.active2bv.get_and_remove(&key) {
+            Some(l) => { let _ = self.tree.remove(l.value); },
+            None    => {
+                match self.inactive2bv.get_and_remove(&key) {
+                    Some(l) => { let _ = self.stree.remove(l.value); },
+                    None    => return
                 }
             }
         }
@@ -180,7 +216,7 @@ impl<N, P, V, B, BV, D, DV> BroadPhase<P, V, B, BV, DV> for DBVTBroadPhase<N, P,
 
         // remove every pair involving b
         for elt in self.pairs.elements().iter() {
-            if elt.key.first.uid() == leaf.uid() || elt.key.second.uid() == leaf.uid() {
+            if elt.key.first.uid() == key || elt.key.second.uid() == key {
                 keys_to_remove.push(elt.key.clone());
             }
         }
@@ -193,7 +229,7 @@ impl<N, P, V, B, BV, D, DV> BroadPhase<P, V, B, BV, DV> for DBVTBroadPhase<N, P,
 
         // remove every "sleeping" pair involving b
         for elt in self.spairs.elements().iter() {
-            if elt.key.first.uid() == leaf.uid() || elt.key.second.uid() == leaf.uid() {
+            if elt.key.first.uid() == key || elt.key.second.uid() == key {
                 keys_to_remove.push(elt.key.clone());
             }
         }
@@ -204,44 +240,42 @@ impl<N, P, V, B, BV, D, DV> BroadPhase<P, V, B, BV, DV> for DBVTBroadPhase<N, P,
     }
 
     fn update(&mut self) {
-        // NOTE: be careful not to add the same object twice!
         /*
-         * Remove all outdated nodes
+         * Move all outdated leaves.
          */
-        for a in self.active2bv.elements_mut().iter_mut() {
-            let mut new_bv = a.value.borrow().object.bounding_volume();
-
-            if !a.value.borrow().bounding_volume.contains(&new_bv) {
-                // need an update!
-                new_bv.loosen(self.margin.clone());
+        for a in self.active2bv.elements().iter() {
+            let mut new_bv = {
+                let leaf = self.tree.get(a.value).expect("Internal error: active leaf not found.");
+                let new_bv = self.predicted_bounding_volume(&leaf.object);
 
-                {
-                    let mut bv = a.value.borrow_mut();
-                    bv.bounding_volume = new_bv;
+                if leaf.bounding_volume.contains(&new_bv) {
+                    continue;
                 }
 
-                self.tree.remove(&mut a.value);
-                self.to_update.push(a.value.clone());
-            }
+                new_bv
+            };
+
+            // need an update!
+            new_bv.loosen(self.margin.clone());
+            self.tree.update_bounding_volume(a.value, new_bv);
+            self.to_update.push(a.value);
         }
 
         self.update_updatable();
     }
 
     fn update_object(&mut self, object: &B) {
-        match self.active2bv.find_mut(&object.uid()) {
-            None       => { },
-            Some(leaf) => {
-                let mut new_bv = leaf.borrow().object.bounding_volume();
-                if !leaf.borrow().bounding_volume.contains(&new_bv) {
+        match self.active2bv.find(&object.uid()) {
+            None     => { },
+            Some(id) => {
+                let mut new_bv = self.predicted_bounding_volume(object);
+
+                if !self.tree.get(*id).expect("Internal error: active leaf not found.")
+                                      .bounding_volume.contains(&new_bv) {
                     // update for real
                     new_bv.loosen(self.margin.clone());
-                    {
-                        let mut bl = leaf.borrow_mut();
-                        bl.bounding_volume = new_bv;
-                    }
-                    self.tree.remove(leaf);
-                    self.to_update.push(leaf.clone());
+                    self.tree.update_bounding_volume(*id, new_bv);
+                    self.to_update.push(*id);
                 }
             }
         }
@@ -252,149 +286,200 @@ impl<N, P, V, B, BV, D, DV> BroadPhase<P, V, B, BV, DV> for DBVTBroadPhase<N, P,
     #[inline(always)]
     fn for_each_pair(&self, f: |&B, &B, &DV| -> ()) {
         for p in self.pairs.elements().iter() {
-            let bf = p.key.first.borrow_mut();
-            let bs = p.key.second.borrow_mut();
-            f(&bf.object, &bs.object, &p.value)
+            f(&p.key.first, &p.key.second, &p.value)
         }
     }
 
     #[inline(always)]
     fn for_each_pair_mut(&mut self, f: |&B, &B, &mut DV| -> ()) {
         for p in self.pairs.elements_mut().iter_mut() {
-            let bf = p.key.first.borrow();
-            let bs = p.key.second.borrow();
-            f(&bf.object, &bs.object, &mut p.value)
+            f(&p.key.first, &p.key.second, &mut p.value)
         }
     }
 
     #[inline(always)]
     fn activate(&mut self, body: &B, f: |&B, &B, &mut DV| -> ()) {
-        // verify that it is not already active and add it to the active map.
-        let mut leaf =
+        // verify that it is not already active and remove it from the inactive map.
+        let id =
             match self.inactive2bv.get_and_remove(&body.uid()) {
                 None    => return, // not found: the object is already active
                 Some(l) => l.value
             };
 
-        self.active2bv.insert(body.uid(), leaf.clone());
-
         // remove from the inactive tree
-        self.stree.remove(&mut leaf);
+        let leaf = self.stree.remove(id).expect("Internal error: inactive leaf not found.");
 
         // Now we find interferences with inactive objects.
-        { // scope to avoid dynamic borrow failure
-            let leaf = leaf.clone();
-            let bleaf = leaf.borrow();
-            self.stree.interferences_with_leaf(bleaf.deref(), &mut self.collector);
-
-            for i in self.collector.iter() {
-                let bi = i.borrow();
-                if self.dispatcher.is_valid(&bleaf.object, &bi.object) {
-                    // the intereference should be registered on the spairs already
-                    match self.spairs.get_and_remove(&Pair::new(leaf.clone(), i.clone())) {
-                        Some(dv) => {
-                            let key   = dv.key.clone();
-                            let value = dv.value;
-                            let bdvf  = key.first.borrow();
-                            let bdvs  = key.second.borrow();
-                            let key   = dv.key;
-                            let obj1  = &bdvf.object;
-                            let obj2  = &bdvs.object;
-                            let p     = self.pairs.insert_or_replace(key, value, true);
-
-                            f(obj1, obj2, p)
-                        },
-                        None => panic!("Internal error: found a new collision during the activation.")
-                    }
+        self.stree.interferences_with_bounding_volume(&leaf.bounding_volume, &mut self.collector);
+
+        for i in self.collector.iter() {
+            if self.dispatcher.is_valid(&leaf.object, i) {
+                // the intereference should be registered on the spairs already
+                match self.spairs.get_and_remove(&Pair::new(leaf.object.clone(), i.clone())) {
+                    Some(dv) => {
+                        let obj1 = dv.key.first.clone();
+                        let obj2 = dv.key.second.clone();
+                        let p    = self.pairs.insert_or_replace(dv.key, dv.value, true);
+
+                        f(&obj1, &obj2, p)
+                    },
+                    None => panic!("Internal error: found a new collision during the activation.")
                 }
             }
         }
 
         // add to the active tree
-        self.tree.insert(leaf);
+        let new_id = self.tree.insert_new(leaf.object, leaf.bounding_volume);
+        self.active2bv.insert(body.uid(), new_id);
         self.collector.clear();
     }
 
     fn deactivate(&mut self, body: &B) {
-        // verify that it is not already inactive and add it to the inactive map.
-        let mut leaf =
+        // verify that it is not already inactive and remove it from the active map.
+        let id =
             match self.active2bv.get_and_remove(&body.uid()) {
                 None    => return, // not found: the object is already inactive
                 Some(l) => l.value
             };
 
-        self.inactive2bv.insert(body.uid(), leaf.clone());
+        // remove from the active tree
+        let leaf = self.tree.remove(id).expect("Internal error: active leaf not found.");
 
         // Now transfer all collisions involving `leaf` and deactivated objects from `pairs` to
         // `spairs`.
-
-        // remove from the active tree
-        self.tree.remove(&mut leaf);
-
-        { // scope to avoid dynamic borrow failure of leaf
-            let cleaf = leaf.clone();
-            let bleaf = cleaf.borrow();
-            self.stree.interferences_with_leaf(bleaf.deref(), &mut self.collector);
-
-            for i in self.collector.iter() {
-                let fi = i.borrow();
-                if self.dispatcher.is_valid(&bleaf.object, &fi.object) {
-                    // the intereference should be registered on the pairs already
-                    match self.pairs.get_and_remove(&Pair::new(leaf.clone(), i.clone())) {
-                        Some(dv) => { self.spairs.insert(dv.key, dv.value); },
-                        None     => panic!("Internal error: found a new collision during the deactivation.")
-                    }
+        self.stree.interferences_with_bounding_volume(&leaf.bounding_volume, &mut self.collector);
+
+        for i in self.collector.iter() {
+            if self.dispatcher.is_valid(&leaf.object, i) {
+                // the intereference should be registered on the pairs already
+                match self.pairs.get_and_remove(&Pair::new(leaf.object.clone(), i.clone())) {
+                    Some(dv) => { self.spairs.insert(dv.key, dv.value); },
+                    None     => panic!("Internal error: found a new collision during the deactivation.")
                 }
             }
         }
 
         // add to the inactive tree
-        self.stree.insert(leaf);
+        let new_id = self.stree.insert_new(leaf.object, leaf.bounding_volume);
+        self.inactive2bv.insert(body.uid(), new_id);
         self.collector.clear();
     }
 
     fn interferences_with_bounding_volume(&mut self, bv: &BV, out: &mut Vec<B>) {
-        {
-            let mut visitor = BoundingVolumeInterferencesCollector::new(bv, &mut self.collector);
+        let mut visitor = BoundingVolumeInterferencesCollector::new(bv, out);
 
-            self.tree.visit(&mut visitor);
-            self.stree.visit(&mut visitor);
-        }
+        self.tree.visit(&mut visitor);
+        self.stree.visit(&mut visitor);
+    }
 
-        for l in self.collector.iter() {
-            out.push(l.borrow().object.clone())
-        }
+    fn interferences_with_ray(&mut self, ray: &Ray<P, V>, out: &mut Vec<B>) {
+        let mut visitor = RayInterferencesCollector::new(ray, out);
 
-        self.collector.clear()
+        self.tree.visit(&mut visitor);
+        self.stree.visit(&mut visitor);
     }
 
-    fn interferences_with_ray(&mut self, ray: &Ray<P, V>, out: &mut Vec<B>) {
-        {
-            let mut visitor = RayInterferencesCollector::new(ray, &mut self.collector);
+    fn interferences_with_point(&mut self, point: &P, out: &mut Vec<B>) {
+        let mut visitor = PointInterferencesCollector::new(point, out);
 
-            self.tree.visit(&mut visitor);
-            self.stree.visit(&mut visitor);
-        }
+        self.tree.visit(&mut visitor);
+        self.stree.visit(&mut visitor);
+    }
 
-        for l in self.collector.iter() {
-            out.push(l.borrow().object.clone())
-        }
+    fn interferences_with_frustum<N2>(&mut self, frustum: &Frustum<N2>, out: &mut Vec<B>)
+        where BV: FrustumClassifiable<N2> {
+        let mut visitor = FrustumInterferencesCollector::new(frustum, out);
 
-        self.collector.clear()
+        self.tree.visit(&mut visitor);
+        self.stree.visit(&mut visitor);
     }
+}
 
-    fn interferences_with_point(&mut self, point: &P, out: &mut Vec<B>) {
-        {
-            let mut visitor = PointInterferencesCollector::new(point, &mut self.collector);
+#[cfg(test)]
+mod test {
+    use std::rc::Rc;
+    use na::{Pnt3, Vec3, Mat4};
+    use na;
+    use bounding_volume::{AABB, HasBoundingVolume, Frustum};
+    use broad_phase::{BroadPhase, NoIdDispatcher, DBVTBroadPhase};
 
-            self.tree.visit(&mut visitor);
-            self.stree.visit(&mut visitor);
-        }
+    struct Body {
+        aabb: AABB<Pnt3<f64>>
+    }
 
-        for l in self.collector.iter() {
-            out.push(l.borrow().object.clone())
+    impl HasBoundingVolume<AABB<Pnt3<f64>>> for Rc<Body> {
+        fn bounding_volume(&self) -> AABB<Pnt3<f64>> {
+            self.aabb.clone()
         }
+    }
+
+    type TestBroadPhase = DBVTBroadPhase<f64, Pnt3<f64>, Vec3<f64>, Rc<Body>, AABB<Pnt3<f64>>,
+                                         NoIdDispatcher<Rc<Body>>, ()>;
+
+    fn body(mins: Pnt3<f64>, maxs: Pnt3<f64>) -> Rc<Body> {
+        Rc::new(Body { aabb: AABB::new(mins, maxs) })
+    }
+
+    #[test]
+    fn test_dbvt_broad_phase_no_self_pair() {
+        let mut bf: TestBroadPhase = DBVTBroadPhase::new(NoIdDispatcher::new(), 0.1);
+
+        bf.add(body(Pnt3::new(0.0, 0.0, 0.0), Pnt3::new(1.0, 1.0, 1.0)));
+        bf.update();
+
+        assert!(bf.num_interferences() == 0);
+
+        bf.add(body(Pnt3::new(0.5, 0.5, 0.5), Pnt3::new(2.0, 2.0, 2.0)));
+        bf.update();
+
+        assert!(bf.num_interferences() == 1);
+    }
+
+    #[test]
+    fn test_dbvt_broad_phase_predicted_displacement() {
+        let mut bf: TestBroadPhase = DBVTBroadPhase::new(NoIdDispatcher::new(), 0.1);
+
+        let fast = body(Pnt3::new(0.0, 0.0, 0.0), Pnt3::new(1.0, 1.0, 1.0));
+        let wall = body(Pnt3::new(5.0, -1.0, -1.0), Pnt3::new(5.5, 2.0, 2.0));
+
+        bf.add(fast.clone());
+        bf.add(wall.clone());
+        bf.update();
+
+        assert!(bf.num_interferences() == 0);
+
+        // The fast body will cross the wall before the next update.
+        bf.set_predicted_displacement(&fast, Vec3::new(10.0, 0.0, 0.0));
+        bf.update();
+
+        assert!(bf.num_interferences() == 1);
+
+        // A displacement away from the wall does not report anything.
+        let other = body(Pnt3::new(0.0, 5.0, 0.0), Pnt3::new(1.0, 6.0, 1.0));
+
+        bf.add(other.clone());
+        bf.set_predicted_displacement(&other, Vec3::new(-10.0, 0.0, 0.0));
+        bf.update_object(&other);
+
+        assert!(bf.num_interferences() == 1);
+    }
+
+    #[test]
+    fn test_dbvt_broad_phase_frustum() {
+        // The orthographic projection of the cube `[-1, 1]^3`.
+        let frustum: Frustum<f64> = Frustum::new_with_matrix(&na::one::<Mat4<f64>>());
+        let mut bf: TestBroadPhase = DBVTBroadPhase::new(NoIdDispatcher::new(), 0.0);
+
+        let inside  = body(Pnt3::new(-0.5, -0.5, -0.5), Pnt3::new(0.5, 0.5, 0.5));
+        let outside = body(Pnt3::new(2.0, 2.0, 2.0), Pnt3::new(3.0, 3.0, 3.0));
+
+        bf.add(inside.clone());
+        bf.add(outside.clone());
+        bf.deactivate(&outside);
+
+        let mut out = Vec::new();
+        bf.interferences_with_frustum(&frustum, &mut out);
 
-        self.collector.clear()
+        assert!(out.len() == 1 && &*out[0] as *const Body == &*inside as *const Body);
     }
 }
@@ -0,0 +1,416 @@
+use na;
+use broad_phase::BroadPhase;
+use utils::data::hash::UintTWHash;
+use utils::data::hash_map::HashMap;
+use utils::data::pair::{Pair, PairTWHash};
+use utils::data::has_uid::HasUid;
+use broad_phase::Dispatcher;
+use bounding_volume::{HasBoundingVolume, BoundingVolume, AABB};
+use bounding_volume::{Frustum, FrustumClassifiable};
+use ray::Ray;
+use partitioning::{LooseTree, LooseTreeId, FrustumInterferencesCollector};
+use math::{Scalar, Point, Vect};
+
+
+/// Broad phase based on a loose octree (in 3D) or quadtree (in 2D).
+///
+/// Like the `DBVTBroadPhase`, it uses two separate trees: one for static objects and which is
+/// never updated, and one for moving objects. Both trees cover the same fixed region of space.
+/// Objects outside of this region are still handled, but less efficiently.
+pub struct LooseTreeBroadPhase<N, P, B, D, DV> {
+    tree:        LooseTree<N, P, B>,
+    stree:       LooseTree<N, P, B>,
+    active2bv:   HashMap<uint, LooseTreeId, UintTWHash>,
+    inactive2bv: HashMap<uint, LooseTreeId, UintTWHash>,
+    pairs:       HashMap<Pair<B>, DV, PairTWHash>, // pair manager
+    spairs:      HashMap<Pair<B>, DV, PairTWHash>,
+    dispatcher:  D,
+    margin:      N,
+    collector:   Vec<B>,
+    to_update:   Vec<LooseTreeId>,
+    update_off:  uint // incremental pairs removal index
+}
+
+impl<N, P, V, B, D, DV> LooseTreeBroadPhase<N, P, B, D, DV>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          B: 'static + HasBoundingVolume<AABB<P>> + HasUid + Clone,
+          D: Dispatcher<B, B, DV> {
+    /// Creates a new broad phase based on a loose tree.
+    ///
+    /// # Arguments:
+    /// * `dispatcher` - the collision dispatcher.
+    /// * `margin` - the loosening margin of the objects bounding volumes.
+    /// * `bounds` - the region covered by the trees.
+    /// * `looseness` - the looseness factor of the tree cells, see `LooseTree::new`.
+    /// * `max_depth` - the maximum depth of the trees.
+    pub fn new(dispatcher: D, margin: N, bounds: AABB<P>, looseness: N, max_depth: uint)
+               -> LooseTreeBroadPhase<N, P, B, D, DV> {
+        LooseTreeBroadPhase {
+            tree:        LooseTree::new(bounds.clone(), looseness, max_depth),
+            stree:       LooseTree::new(bounds, looseness, max_depth),
+            active2bv:   HashMap::new(UintTWHash::new()),
+            inactive2bv: HashMap::new(UintTWHash::new()),
+            pairs:       HashMap::new(PairTWHash::new()),
+            spairs:      HashMap::new(PairTWHash::new()),
+            dispatcher:  dispatcher,
+            update_off:  0,
+            collector:   Vec::new(),
+            to_update:   Vec::new(),
+            margin:      margin
+        }
+    }
+
+    /// Number of interferences detected by this broad phase.
+    #[inline]
+    pub fn num_interferences(&self) -> uint {
+        self.pairs.len()
+    }
+
+    // The bounding volume of the object with the given uid, from the active or the inactive tree.
+    fn bounding_volume_of(&self, uid: uint) -> Option<&AABB<P>> {
+        match self.active2bv.find(&uid) {
+            Some(id) => self.tree.get(*id).map(|p| &p.bounding_volume),
+            None     => {
+                match self.inactive2bv.find(&uid) {
+                    Some(id) => self.stree.get(*id).map(|p| &p.bounding_volume),
+                    None     => None
+                }
+            }
+        }
+    }
+
+    fn update_updatable(&mut self) {
+        /*
+         * Collect interferences of the updated objects.
+         */
+        let mut new_colls = 0u;
+
+        for u in self.to_update.iter() {
+            {
+                let proxy = self.tree.get(*u).expect("Internal error: updated object not found.");
+
+                self.tree.interferences_with_object(*u, &mut self.collector);
+                self.stree.interferences_with_bounding_volume(&proxy.bounding_volume, &mut self.collector);
+
+                // dispatch
+                for i in self.collector.iter() {
+                    if self.dispatcher.is_valid(&proxy.object, i) {
+                        let dispatcher = &mut self.dispatcher;
+                        let _ = self.pairs.find_or_insert_lazy(
+                            Pair::new(proxy.object.clone(), i.clone()),
+                            || dispatcher.dispatch(&proxy.object, i)
+                            );
+
+                        new_colls = new_colls + 1;
+                    }
+                }
+            }
+
+            self.collector.clear();
+        }
+
+        /*
+         * Remove some of the outdated collisions.
+         */
+        // NOTE: the exact same code is used on `dbvt_broad_phase.rs`.
+        if new_colls != 0 && self.pairs.len() != 0 {
+            let len          = self.pairs.len();
+            let num_removals = na::clamp(new_colls, len / 10, len);
+
+            for i in range(self.update_off, self.update_off + num_removals) {
+                let id = i % self.pairs.len();
+
+                let remove = {
+                    let elts  = self.pairs.elements();
+                    let entry = &elts[id];
+
+                    match (self.bounding_volume_of(entry.key.first.uid()),
+                           self.bounding_volume_of(entry.key.second.uid())) {
+                        (Some(bf), Some(bs)) => !bf.intersects(bs),
+                        _                    => true
+                    }
+                };
+
+                if remove {
+                    self.pairs.remove_elem_at(id);
+                }
+            }
+
+            self.update_off = (self.update_off + num_removals) % self.pairs.len();
+        }
+
+        self.to_update.clear();
+    }
+}
+
+impl<N, P, V, B, D, DV> BroadPhase<P, V, B, AABB<P>, DV> for LooseTreeBroadPhase<N, P, B, D, DV>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          B: 'static + HasBoundingVolume<AABB<P>> + HasUid + Clone,
+          D: Dispatcher<B, B, DV> {
+    #[inline]
+    fn add(&mut self, b: B) {
+        let id    = b.uid();
+        let bv    = b.bounding_volume().loosened(self.margin.clone());
+        let proxy = self.tree.insert(b, bv);
+
+        self.to_update.push(proxy);
+        self.update_updatable();
+
+        self.active2bv.insert(id, proxy);
+    }
+
+    fn remove(&mut self, b: &B) {
+        let key = b.uid();
+
+        match self.active2bv.get_and_remove(&key) {
+            Some(l) => { let _ = self.tree.remove(l.value); },
+            None    => {
+                match self.inactive2bv.get_and_remove(&key) {
+                    Some(l) => { let _ = self.stree.remove(l.value); },
+                    None    => return
+                }
+            }
+        }
+
+        let mut keys_to_remove = Vec::new();
+
+        // remove every pair involving b
+        for elt in self.pairs.elements().iter() {
+            if elt.key.first.uid() == key || elt.key.second.uid() == key {
+                keys_to_remove.push(elt.key.clone());
+            }
+        }
+
+        for k in keys_to_remove.iter() {
+            self.pairs.remove(k);
+        }
+
+        keys_to_remove.clear();
+
+        // remove every "sleeping" pair involving b
+        for elt in self.spairs.elements().iter() {
+            if elt.key.first.uid() == key || elt.key.second.uid() == key {
+                keys_to_remove.push(elt.key.clone());
+            }
+        }
+
+        for k in keys_to_remove.iter() {
+            self.spairs.remove(k);
+        }
+    }
+
+    fn update(&mut self) {
+        /*
+         * Move all outdated objects.
+         */
+        for a in self.active2bv.elements().iter() {
+            let mut new_bv = {
+                let proxy  = self.tree.get(a.value).expect("Internal error: active object not found.");
+                let new_bv = proxy.object.bounding_volume();
+
+                if proxy.bounding_volume.contains(&new_bv) {
+                    continue;
+                }
+
+                new_bv
+            };
+
+            // need an update!
+            new_bv.loosen(self.margin.clone());
+            self.tree.update_bounding_volume(a.value, new_bv);
+            self.to_update.push(a.value);
+        }
+
+        self.update_updatable();
+    }
+
+    fn update_object(&mut self, object: &B) {
+        match self.active2bv.find(&object.uid()) {
+            None     => { },
+            Some(id) => {
+                let mut new_bv = object.bounding_volume();
+
+                if !self.tree.get(*id).expect("Internal error: active object not found.")
+                                      .bounding_volume.contains(&new_bv) {
+                    // update for real
+                    new_bv.loosen(self.margin.clone());
+                    self.tree.update_bounding_volume(*id, new_bv);
+                    self.to_update.push(*id);
+                }
+            }
+        }
+
+        self.update_updatable();
+    }
+
+    #[inline(always)]
+    fn for_each_pair(&self, f: |&B, &B, &DV| -> ()) {
+        for p in self.pairs.elements().iter() {
+            f(&p.key.first, &p.key.second, &p.value)
+        }
+    }
+
+    #[inline(always)]
+    fn for_each_pair_mut(&mut self, f: |&B, &B, &mut DV| -> ()) {
+        for p in self.pairs.elements_mut().iter_mut() {
+            f(&p.key.first, &p.key.second, &mut p.value)
+        }
+    }
+
+    #[inline(always)]
+    fn activate(&mut self, body: &B, f: |&B, &B, &mut DV| -> ()) {
+        // verify that it is not already active and remove it from the inactive map.
+        let id =
+            match self.inactive2bv.get_and_remove(&body.uid()) {
+                None    => return, // not found: the object is already active
+                Some(l) => l.value
+            };
+
+        // remove from the inactive tree
+        let proxy = self.stree.remove(id);
+
+        // Now we find interferences with inactive objects.
+        self.stree.interferences_with_bounding_volume(&proxy.bounding_volume, &mut self.collector);
+
+        for i in self.collector.iter() {
+            if self.dispatcher.is_valid(&proxy.object, i) {
+                // the intereference should be registered on the spairs already
+                match self.spairs.get_and_remove(&Pair::new(proxy.object.clone(), i.clone())) {
+                    Some(dv) => {
+                        let obj1 = dv.key.first.clone();
+                        let obj2 = dv.key.second.clone();
+                        let p    = self.pairs.insert_or_replace(dv.key, dv.value, true);
+
+                        f(&obj1, &obj2, p)
+                    },
+                    None => panic!("Internal error: found a new collision during the activation.")
+                }
+            }
+        }
+
+        // add to the active tree
+        let new_id = self.tree.insert(proxy.object, proxy.bounding_volume);
+        self.active2bv.insert(body.uid(), new_id);
+        self.collector.clear();
+    }
+
+    fn deactivate(&mut self, body: &B) {
+        // verify that it is not already inactive and remove it from the active map.
+        let id =
+            match self.active2bv.get_and_remove(&body.uid()) {
+                None    => return, // not found: the object is already inactive
+                Some(l) => l.value
+            };
+
+        // remove from the active tree
+        let proxy = self.tree.remove(id);
+
+        // Now transfer all collisions involving `proxy` and deactivated objects from `pairs` to
+        // `spairs`.
+        self.stree.interferences_with_bounding_volume(&proxy.bounding_volume, &mut self.collector);
+
+        for i in self.collector.iter() {
+            if self.dispatcher.is_valid(&proxy.object, i) {
+                // the intereference should be registered on the pairs already
+                match self.pairs.get_and_remove(&Pair::new(proxy.object.clone(), i.clone())) {
+                    Some(dv) => { self.spairs.insert(dv.key, dv.value); },
+                    None     => panic!("Internal error: found a new collision during the deactivation.")
+                }
+            }
+        }
+
+        // add to the inactive tree
+        let new_id = self.stree.insert(proxy.object, proxy.bounding_volume);
+        self.inactive2bv.insert(body.uid(), new_id);
+        self.collector.clear();
+    }
+
+    fn interferences_with_bounding_volume(&mut self, bv: &AABB<P>, out: &mut Vec<B>) {
+        self.tree.interferences_with_bounding_volume(bv, out);
+        self.stree.interferences_with_bounding_volume(bv, out);
+    }
+
+    fn interferences_with_ray(&mut self, ray: &Ray<P, V>, out: &mut Vec<B>) {
+        self.tree.interferences_with_ray(ray, out);
+        self.stree.interferences_with_ray(ray, out);
+    }
+
+    fn interferences_with_point(&mut self, point: &P, out: &mut Vec<B>) {
+        self.tree.interferences_with_point(point, out);
+        self.stree.interferences_with_point(point, out);
+    }
+
+    fn interferences_with_frustum<N2>(&mut self, frustum: &Frustum<N2>, out: &mut Vec<B>)
+        where AABB<P>: FrustumClassifiable<N2> {
+        let mut visitor = FrustumInterferencesCollector::new(frustum, out);
+
+        self.tree.visit(&mut visitor);
+        self.stree.visit(&mut visitor);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::rc::Rc;
+    use na::Pnt3;
+    use bounding_volume::{AABB, HasBoundingVolume};
+    use broad_phase::{BroadPhase, NoIdDispatcher, LooseTreeBroadPhase};
+
+    struct Body {
+        aabb: AABB<Pnt3<f64>>
+    }
+
+    impl HasBoundingVolume<AABB<Pnt3<f64>>> for Rc<Body> {
+        fn bounding_volume(&self) -> AABB<Pnt3<f64>> {
+            self.aabb.clone()
+        }
+    }
+
+    type TestBroadPhase = LooseTreeBroadPhase<f64, Pnt3<f64>, Rc<Body>, NoIdDispatcher<Rc<Body>>, ()>;
+
+    fn body(mins: Pnt3<f64>, maxs: Pnt3<f64>) -> Rc<Body> {
+        Rc::new(Body { aabb: AABB::new(mins, maxs) })
+    }
+
+    #[test]
+    fn test_loose_tree_broad_phase() {
+        let bounds = AABB::new(Pnt3::new(-10.0, -10.0, -10.0), Pnt3::new(10.0, 10.0, 10.0));
+        let mut bf: TestBroadPhase = LooseTreeBroadPhase::new(NoIdDispatcher::new(), 0.1, bounds, 2.0, 6);
+
+        let a = body(Pnt3::new(0.0, 0.0, 0.0), Pnt3::new(1.0, 1.0, 1.0));
+        let b = body(Pnt3::new(0.5, 0.5, 0.5), Pnt3::new(2.0, 2.0, 2.0));
+        // Outside of the tree bounds.
+        let c = body(Pnt3::new(20.0, 20.0, 20.0), Pnt3::new(21.0, 21.0, 21.0));
+
+        bf.add(a.clone());
+        bf.update();
+        assert!(bf.num_interferences() == 0);
+
+        bf.add(b.clone());
+        bf.add(c.clone());
+        bf.update();
+        assert!(bf.num_interferences() == 1);
+
+        // The pair is put to sleep once both its objects are inactive, and woken up when one of
+        // them is activated.
+        bf.deactivate(&a);
+        assert!(bf.num_interferences() == 1);
+        bf.deactivate(&b);
+        assert!(bf.num_interferences() == 0);
+
+        let mut reactivated = 0u;
+        bf.activate(&b, |_, _, _| reactivated = reactivated + 1);
+        assert!(reactivated == 1 && bf.num_interferences() == 1);
+
+        let mut out = Vec::new();
+        bf.interferences_with_point(&Pnt3::new(20.5, 20.5, 20.5), &mut out);
+        assert!(out.len() == 1 && &*out[0] as *const Body == &*c as *const Body);
+
+        bf.remove(&a);
+        assert!(bf.num_interferences() == 0);
+    }
+}
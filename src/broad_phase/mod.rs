@@ -6,6 +6,7 @@ pub use self::dispatcher::{Dispatcher, NoIdDispatcher};
 pub use self::brute_force_broad_phase::BruteForceBroadPhase;
 pub use self::brute_force_bounding_volume_broad_phase::BruteForceBoundingVolumeBroadPhase;
 pub use self::dbvt_broad_phase::DBVTBroadPhase;
+pub use self::loose_tree_broad_phase::LooseTreeBroadPhase;
 
 #[doc(hidden)]
 pub mod broad_phase;
@@ -13,3 +14,4 @@ mod dispatcher;
 mod brute_force_broad_phase;
 mod brute_force_bounding_volume_broad_phase;
 mod dbvt_broad_phase;
+mod loose_tree_broad_phase;
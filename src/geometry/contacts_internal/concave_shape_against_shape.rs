@@ -29,7 +29,7 @@ pub fn manifold_concave_shape_against_shape<N, P, V, AV, M, G1, G2>(
 
     {
         let mut visitor = BoundingVolumeInterferencesCollector::new(&ls_aabb2, &mut interferences);
-        g1.bvt().visit(&mut visitor);
+        g1.visit_bvt(&mut visitor);
     }
 
     for i in interferences.into_iter() {
@@ -83,7 +83,7 @@ pub fn concave_shape_against_shape<N, P, V, AV, M, G1, G2>(
 
     {
         let mut visitor = BoundingVolumeInterferencesCollector::new(&ls_aabb2, &mut interferences);
-        g1.bvt().visit(&mut visitor);
+        g1.visit_bvt(&mut visitor);
     }
 
     let mut res = None::<Contact<N, P, V>>;
@@ -17,7 +17,7 @@ pub fn concave_shape_against_shape<N, P, V, M, G1, G2>(m1: &M, g1: &G1, m2: &M,
           G2: Shape<N, P, V, M> {
     let mut cost_fn = ConcaveShapeAgainstShapeDistCostFn::new(m1, g1, m2, g2);
 
-    g1.bvt().best_first_search(&mut cost_fn).map(|(_, res)| res).expect("The concave shape must not be empty.")
+    g1.best_first_search_bvt(&mut cost_fn).map(|(_, res)| res).expect("The concave shape must not be empty.")
 }
 
 /// Smallest distance between a shape and a concave shape.
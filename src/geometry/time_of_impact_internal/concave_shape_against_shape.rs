@@ -19,7 +19,7 @@ pub fn concave_shape_against_shape<N, P, V, M, G1, G2>(m1: &M, vel1: &V, g1: &G1
           G2: Shape<N, P, V, M> {
     let mut cost_fn = ConcaveShapeAgainstShapeTOICostFn::new(m1, vel1, g1, m2, vel2, g2);
 
-    g1.bvt().best_first_search(&mut cost_fn).map(|(_, res)| res)
+    g1.best_first_search_bvt(&mut cost_fn).map(|(_, res)| res)
 }
 
 /// Time Of Impact of any shape with a composite shape, under translational movement.
@@ -53,7 +53,7 @@ impl<N, P, V, M, G1, G2> ConcaveShapeShape<N, P, V, M, G1, G2>
 
         {
             let mut visitor = BoundingVolumeInterferencesCollector::new(&ls_aabb2, &mut self.interferences);
-            g1.bvt().visit(&mut visitor);
+            g1.visit_bvt(&mut visitor);
         }
 
         for i in self.interferences.iter() {
@@ -1,14 +1,18 @@
 //! A read-only Bounding Volume Tree.
 
 use test::stats::Stats;
+use std::num::{Float, ToPrimitive};
 use std::collections::BinaryHeap;
+use std::sync::Future;
+use std::mem;
 use na::{Translation, Bounded};
 use na;
-use ray::{Ray, LocalRayCast};
+use ray::{Ray, LocalRayCast, RayPacket};
+use point::LocalPointQuery;
 use partitioning::{BVTVisitor, BVTTVisitor, BVTCostFn};
-use bounding_volume::BoundingVolume;
+use bounding_volume::{BoundingVolume, HasSurfaceArea, AABB};
 use utils::data::ref_with_cost::RefWithCost;
-use math::{Scalar, Vect};
+use math::{Scalar, Point, Vect};
 
 
 /// A Boundig Volume Tree.
@@ -69,16 +73,39 @@ impl<B, BV> BVT<B, BV> {
         }
     }
 
+    /// Visits the bounding volume traversal tree implicitely formed with this tree itself.
+    ///
+    /// Each pair of distinct leaves is visited at most once: a leaf is never paired with itself,
+    /// and the mirror `(b, a)` of a pair `(a, b)` is never visited.
+    pub fn visit_self_bvtt<Vis: BVTTVisitor<B, BV>>(&self, visitor: &mut Vis) {
+        match self.tree {
+            Some(ref t) => t.visit_self_bvtt(visitor),
+            None        => { }
+        }
+    }
+
     // FIXME: internalize the type parameter R using associated types.
     // FIXME: really return a ref to B ?
     /// Performs a best-fist-search on the tree.
     ///
     /// Returns the content of the best leaf nound, and a result of user-defined type.
     pub fn best_first_search<'a, N, BFS, R>(&'a self, algorithm: &mut BFS) -> Option<(&'a B, R)>
+        where N:   Scalar,
+              BFS: BVTCostFn<N, B, BV, R> {
+        self.best_first_search_with_max_cost(algorithm, Bounded::max_value())
+    }
+
+    /// Performs a best-fist-search on the tree, ignoring everything with a cost greater than or
+    /// equal to `max_cost`.
+    ///
+    /// Subtrees with a bounding volume cost greater than or equal to `max_cost` are pruned without
+    /// being visited.
+    pub fn best_first_search_with_max_cost<'a, N, BFS, R>(&'a self, algorithm: &mut BFS, max_cost: N)
+                                                          -> Option<(&'a B, R)>
         where N:   Scalar,
               BFS: BVTCostFn<N, B, BV, R> {
         match self.tree {
-            Some(ref t) => t.best_first_search(algorithm),
+            Some(ref t) => t.best_first_search(algorithm, max_cost),
             None        => None
         }
     }
@@ -103,6 +130,12 @@ impl<B, BV> BVT<B, BV> {
             None        => 0
         }
     }
+
+    /// Consumes this tree and returns its root node, if any.
+    #[inline]
+    pub fn unwrap_root(self) -> Option<BVTNode<B, BV>> {
+        self.tree
+    }
 }
 
 impl<N, V, B, BV> BVT<B, BV>
@@ -115,6 +148,143 @@ impl<N, V, B, BV> BVT<B, BV>
     }
 }
 
+impl<N, V, B, BV> BVT<B, BV>
+    where N:  Scalar,
+          V:  Vect<N>,
+          BV: Translation<V> + BoundingVolume<N> + HasSurfaceArea<N> + Clone {
+    /// Creates a `BVT` using the binned Surface Area Heuristic.
+    ///
+    /// This is slower to build than `new_balanced` but usually gives better trees for elongated
+    /// or clustered objects.
+    pub fn new_sah(leaves: Vec<(B, BV)>) -> BVT<B, BV> {
+        BVT::new_with_partitioner(leaves, sah_partitioner)
+    }
+}
+
+impl<B: Send, BV: Send> BVT<B, BV> {
+    /// Builds a bounding volume tree using an user-defined construction function, on several
+    /// threads.
+    ///
+    /// The top levels of the tree are partitioned on the current thread. Then, the independent
+    /// subtrees are built in parallel using at most `nthreads` threads.
+    pub fn new_with_partitioner_parallel(
+        leaves:      Vec<(B, BV)>,
+        partitioner: fn(uint, Vec<(B, BV)>) -> (BV, BinaryPartition<B, BV>),
+        nthreads:    uint)
+        -> BVT<B, BV> {
+        if leaves.len() == 0 {
+            BVT {
+                tree: None
+            }
+        }
+        else {
+            // Each level of the tree doubles the number of threads.
+            let mut parallel_depth = 0;
+
+            while (1u << parallel_depth) < nthreads {
+                parallel_depth = parallel_depth + 1;
+            }
+
+            BVT {
+                tree: Some(_new_with_partitioner_parallel(0, leaves, partitioner, parallel_depth))
+            }
+        }
+    }
+}
+
+impl<N, V, B, BV> BVT<B, BV>
+    where N:  Scalar,
+          V:  Vect<N>,
+          B:  Send,
+          BV: Send + Translation<V> + BoundingVolume<N> + Clone {
+    /// Creates a balanced `BVT` using at most `nthreads` threads.
+    pub fn new_balanced_parallel(leaves: Vec<(B, BV)>, nthreads: uint) -> BVT<B, BV> {
+        BVT::new_with_partitioner_parallel(leaves, median_partitioner::<N, V, B, BV>, nthreads)
+    }
+}
+
+impl<N, V, B, BV> BVT<B, BV>
+    where N:  Scalar,
+          V:  Vect<N>,
+          B:  Send,
+          BV: Send + Translation<V> + BoundingVolume<N> + HasSurfaceArea<N> + Clone {
+    /// Creates a `BVT` using the binned Surface Area Heuristic and at most `nthreads` threads.
+    pub fn new_sah_parallel(leaves: Vec<(B, BV)>, nthreads: uint) -> BVT<B, BV> {
+        BVT::new_with_partitioner_parallel(leaves, sah_partitioner::<N, V, B, BV>, nthreads)
+    }
+}
+
+impl<N, B, BV> BVT<B, BV>
+    where N:  Scalar,
+          BV: BoundingVolume<N> + Clone {
+    /// Updates the bounding volumes of this tree without modifying its structure.
+    ///
+    /// The bounding volume of each leaf is replaced by `leaf_bv(leaf_content)`. Then, the
+    /// bounding volumes of the internal nodes are recomputed bottom-up. The tree quality might
+    /// degrade if the leaves move a lot: check `surface_area_cost` to decide when to rebuild it.
+    pub fn refit(&mut self, leaf_bv: &mut |&B| -> BV) {
+        match self.tree {
+            Some(ref mut n) => n.refit(leaf_bv),
+            None            => { }
+        }
+    }
+}
+
+impl<N, B, BV> BVT<B, BV>
+    where N:  Scalar,
+          BV: HasSurfaceArea<N> {
+    /// The sum of the surface areas of the internal nodes, divided by the root surface area.
+    ///
+    /// This approximates the average number of internal nodes visited by a random ray, i.e., the
+    /// lower, the better.
+    pub fn surface_area_cost(&self) -> N {
+        match self.tree {
+            Some(ref n) => {
+                let root_area = n.bounding_volume().surface_area();
+
+                if na::is_zero(&root_area) {
+                    na::zero()
+                }
+                else {
+                    n.internal_surface_area() / root_area
+                }
+            },
+            None => na::zero()
+        }
+    }
+}
+
+impl<N, B, BV> BVTNode<B, BV>
+    where N:  Scalar,
+          BV: BoundingVolume<N> + Clone {
+    fn refit(&mut self, leaf_bv: &mut |&B| -> BV) {
+        match *self {
+            BVTNode::Internal(ref mut bv, ref mut left, ref mut right) => {
+                left.refit(leaf_bv);
+                right.refit(leaf_bv);
+
+                *bv = left.bounding_volume().merged(right.bounding_volume());
+            },
+            BVTNode::Leaf(ref mut bv, ref b) => {
+                *bv = (*leaf_bv)(b);
+            }
+        }
+    }
+}
+
+impl<N, B, BV> BVTNode<B, BV>
+    where N:  Scalar,
+          BV: HasSurfaceArea<N> {
+    fn internal_surface_area(&self) -> N {
+        match *self {
+            BVTNode::Internal(ref bv, ref left, ref right) => {
+                bv.surface_area() + left.internal_surface_area() + right.internal_surface_area()
+            },
+            BVTNode::Leaf(_, _) => na::zero()
+        }
+    }
+}
+
 impl<B, BV> BVTNode<B, BV> {
     /// The bounding volume of this node.
     #[inline]
@@ -167,16 +337,27 @@ impl<B, BV> BVTNode<B, BV> {
         }
     }
 
-    fn best_first_search<'a, N, BFS, R>(&'a self, algorithm: &mut BFS) -> Option<(&'a B, R)>
+    fn visit_self_bvtt<Vis: BVTTVisitor<B, BV>>(&self, visitor: &mut Vis) {
+        match *self {
+            BVTNode::Internal(_, ref left, ref right) => {
+                left.visit_self_bvtt(visitor);
+                right.visit_self_bvtt(visitor);
+                left.visit_bvtt(&**right, visitor);
+            },
+            BVTNode::Leaf(_, _) => { }
+        }
+    }
+
+    fn best_first_search<'a, N, BFS, R>(&'a self, algorithm: &mut BFS, max_cost: N) -> Option<(&'a B, R)>
         where N:   Scalar,
               BFS: BVTCostFn<N, B, BV, R> {
         let mut queue: BinaryHeap<RefWithCost<'a, N, BVTNode<B, BV>>> = BinaryHeap::new();
-        let mut best_cost = Bounded::max_value();
+        let mut best_cost = max_cost;
         let mut result    = None;
 
         match algorithm.compute_bv_cost(self.bounding_volume()) {
-            Some(cost) => queue.push(RefWithCost::new(self, cost)),
-            None       => return None
+            Some(cost) if cost < best_cost => queue.push(RefWithCost::new(self, -cost)),
+            _                              => return None
         }
 
         loop {
@@ -241,20 +422,141 @@ impl<N, P, V, B, BV> BVT<B, BV>
     pub fn cast_ray<'a, T>(&'a self,
                            ray:     &Ray<P, V>,
                            cast_fn: &mut |&B, &Ray<P, V>| -> Option<(N, T)>) -> Option<(N, T, &'a B)> {
+        self.cast_ray_with_max_toi(ray, Bounded::max_value(), cast_fn)
+    }
+
+    /// Computes the closest intersection between the objects stored on this tree and a given ray,
+    /// ignoring intersections with a time of impact greater than or equal to `max_toi`.
+    pub fn cast_ray_with_max_toi<'a, T>(&'a self,
+                                        ray:     &Ray<P, V>,
+                                        max_toi: N,
+                                        cast_fn: &mut |&B, &Ray<P, V>| -> Option<(N, T)>)
+                                        -> Option<(N, T, &'a B)> {
         match self.tree {
             None        => None,
             Some(ref n) => {
-                if n.bounding_volume().toi_with_ray(ray, true).is_some() {
-                    n.cast_ray(ray, Bounded::max_value(), cast_fn)
-                }
-                else {
-                    None
+                match n.bounding_volume().toi_with_ray(ray, true) {
+                    Some(toi) if toi < max_toi => n.cast_ray(ray, max_toi, cast_fn),
+                    _                          => None
                 }
             }
         }
     }
 }
 
+impl<N, P, V, B> BVT<B, AABB<P>>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> {
+    /// Computes the closest intersection between the objects stored on this tree and each ray of
+    /// a packet.
+    ///
+    /// The whole packet is tested against each bounding volume at once. Each ray has its own
+    /// active flag so that it stops traversing the subtrees it misses, or that are farther than
+    /// its current best intersection. The children of each node are traversed front-to-back along
+    /// the mean direction of the rays. The i-th result corresponds to the i-th ray of the packet.
+    pub fn cast_ray_packet<'a, T>(&'a self,
+                                  packet:  &RayPacket<P, V>,
+                                  cast_fn: &mut |&B, &Ray<P, V>| -> Option<(N, T)>)
+                                  -> Vec<Option<(N, T, &'a B)>> {
+        let nrays         = packet.len();
+        let mut res       = Vec::from_fn(nrays, |_| None);
+        let mut best_tois = Vec::from_elem(nrays, Bounded::max_value());
+
+        let root = match self.tree {
+            Some(ref t) => t,
+            None        => return res
+        };
+
+        let mut dir: V = na::zero();
+
+        for ray in packet.rays().iter() {
+            dir = dir + ray.dir;
+        }
+
+        // `masks[i]` contains the active rays of the i-th node of the stack. Those buffers are
+        // reused as the stack grows and shrinks so that no allocation is done per node.
+        let mut masks   = vec!(Vec::from_elem(nrays, false));
+        let mut current = Vec::from_elem(nrays, true);
+        let mut stack   = Vec::new();
+        // Incremented each time a ray finds a closer intersection.
+        let mut nhits   = 0u;
+
+        if packet.intersects_aabb(root.bounding_volume(), current.as_slice(), best_tois.as_slice(),
+                                  masks[0].as_mut_slice()) {
+            stack.push((root, nhits));
+        }
+
+        loop {
+            match stack.pop() {
+                Some((node, nhits_at_push)) => {
+                    let depth = stack.len();
+
+                    mem::swap(&mut current, &mut masks[depth]);
+
+                    if nhits_at_push != nhits {
+                        // Some rays found closer intersections since this node was pushed.
+                        if !packet.intersects_aabb(node.bounding_volume(), current.as_slice(), best_tois.as_slice(),
+                                                   masks[depth].as_mut_slice()) {
+                            continue;
+                        }
+
+                        mem::swap(&mut current, &mut masks[depth]);
+                    }
+
+                    match *node {
+                        BVTNode::Internal(_, ref left, ref right) => {
+                            let delta = left.bounding_volume().center() - right.bounding_volume().center();
+
+                            // Push the farthest child first so that the nearest one is popped first.
+                            let children = if na::dot(&delta, &dir) <= na::zero() {
+                                [ &**right, &**left ]
+                            }
+                            else {
+                                [ &**left, &**right ]
+                            };
+
+                            for child in children.iter() {
+                                let slot = stack.len();
+
+                                if masks.len() == slot {
+                                    masks.push(Vec::from_elem(nrays, false));
+                                }
+
+                                if packet.intersects_aabb(child.bounding_volume(), current.as_slice(),
+                                                          best_tois.as_slice(), masks[slot].as_mut_slice()) {
+                                    stack.push((*child, nhits));
+                                }
+                            }
+                        },
+                        BVTNode::Leaf(_, ref b) => {
+                            for (i, ray) in packet.rays().iter().enumerate() {
+                                if !current[i] {
+                                    continue;
+                                }
+
+                                match (*cast_fn)(b, ray) {
+                                    Some((toi, data)) => {
+                                        if toi < best_tois[i] {
+                                            best_tois[i] = toi;
+                                            res[i]       = Some((toi, data, b));
+                                            nhits        = nhits + 1;
+                                        }
+                                    },
+                                    None => { }
+                                }
+                            }
+                        }
+                    }
+                },
+                None => break
+            }
+        }
+
+        res
+    }
+}
+
 impl<N, P, V, B, BV> BVTNode<B, BV>
     where N: Scalar,
           BV: LocalRayCast<N, P, V> {
@@ -337,6 +639,129 @@ impl<N, P, V, B, BV> BVTNode<B, BV>
     }
 }
 
+impl<N, P, B, BV> BVT<B, BV>
+    where N:  Scalar,
+          BV: LocalPointQuery<N, P> {
+    /// Finds the `k` objects closest to a given point.
+    ///
+    /// The bounding volumes are pruned using their distance to the point, and `dist_fn` computes
+    /// the exact distance between a leaf object and the point, typically with
+    /// `LocalPointQuery::distance_to_point`. The result is sorted by increasing distance.
+    pub fn k_nearest<'a>(&'a self, pt: &P, k: uint, dist_fn: &mut |&B, &P| -> N) -> Vec<(N, &'a B)> {
+        let mut res: BinaryHeap<RefWithCost<'a, N, B>> = BinaryHeap::with_capacity(k);
+
+        let root = match self.tree {
+            Some(ref t) if k != 0 => t,
+            _                     => return Vec::new()
+        };
+
+        let mut queue: BinaryHeap<RefWithCost<'a, N, BVTNode<B, BV>>> = BinaryHeap::new();
+
+        queue.push(RefWithCost::new(root, -root.bounding_volume().distance_to_point(pt)));
+
+        loop {
+            match queue.pop() {
+                Some(node) => {
+                    match nearest_bound(&res, k) {
+                        Some(bound) if -node.cost >= bound => break, // no better object can be found.
+                        _                                  => { }
+                    }
+
+                    match *node.object {
+                        BVTNode::Internal(_, ref left, ref right) => {
+                            for child in [ &**left, &**right ].iter() {
+                                let dist = child.bounding_volume().distance_to_point(pt);
+
+                                match nearest_bound(&res, k) {
+                                    Some(bound) if dist >= bound => { },
+                                    _                            => queue.push(RefWithCost::new(*child, -dist))
+                                }
+                            }
+                        },
+                        BVTNode::Leaf(_, ref b) => {
+                            let dist = (*dist_fn)(b, pt);
+                            push_nearest(&mut res, k, dist, b)
+                        }
+                    }
+                },
+                None => break
+            }
+        }
+
+        res.into_sorted_vec().into_iter().map(|e| (e.cost, e.object)).collect()
+    }
+
+    /// Finds all the objects at a distance smaller than or equal to `radius` from a given point.
+    ///
+    /// The bounding volumes are pruned using their distance to the point, and `dist_fn` computes
+    /// the exact distance between a leaf object and the point. The result is sorted by increasing
+    /// distance.
+    pub fn within_radius<'a>(&'a self, pt: &P, radius: N, dist_fn: &mut |&B, &P| -> N)
+                             -> Vec<(N, &'a B)> {
+        let mut res = Vec::new();
+
+        match self.tree {
+            Some(ref t) => t.within_radius(pt, radius, dist_fn, &mut res),
+            None        => { }
+        }
+
+        res.sort_by(|a, b| a.ref0().partial_cmp(b.ref0()).unwrap_or(Equal));
+
+        res
+    }
+}
+
+impl<N, P, B, BV> BVTNode<B, BV>
+    where N:  Scalar,
+          BV: LocalPointQuery<N, P> {
+    fn within_radius<'a>(&'a self,
+                         pt:      &P,
+                         radius:  N,
+                         dist_fn: &mut |&B, &P| -> N,
+                         out:     &mut Vec<(N, &'a B)>) {
+        if self.bounding_volume().distance_to_point(pt) > radius {
+            return;
+        }
+
+        match *self {
+            BVTNode::Internal(_, ref left, ref right) => {
+                left.within_radius(pt, radius, dist_fn, out);
+                right.within_radius(pt, radius, dist_fn, out);
+            },
+            BVTNode::Leaf(_, ref b) => {
+                let dist = (*dist_fn)(b, pt);
+
+                if dist <= radius {
+                    out.push((dist, b))
+                }
+            }
+        }
+    }
+}
+
+/// Inserts a candidate on a bounded max-heap of the `k` objects closest to some query.
+///
+/// The top of the heap is the farthest of the objects found so far. The candidate is discarded if
+/// the heap is full and all its elements are closer.
+pub fn push_nearest<'a, N: Scalar, T>(nearest: &mut BinaryHeap<RefWithCost<'a, N, T>>, k: uint, dist: N, b: &'a T) {
+    if nearest.len() < k {
+        nearest.push(RefWithCost::new(b, dist))
+    }
+    else if dist < nearest.top().unwrap().cost {
+        let _ = nearest.replace(RefWithCost::new(b, dist));
+    }
+}
+
+/// The distance of the farthest of the `k` nearest objects, if `k` objects were found already.
+pub fn nearest_bound<'a, N: Scalar, T>(nearest: &BinaryHeap<RefWithCost<'a, N, T>>, k: uint) -> Option<N> {
+    if nearest.len() == k {
+        nearest.top().map(|e| e.cost)
+    }
+    else {
+        None
+    }
+}
+
 /// Construction function for a kdree to be used with `BVT::new_with_partitioner`.
 pub fn median_partitioner_with_centers<N, V, B, BV>(depth:  uint,
                                                     leaves: Vec<(B, BV)>,
@@ -409,6 +834,165 @@ pub fn median_partitioner<N, V, B, BV>(depth:  uint,
     median_partitioner_with_centers(depth, leaves, &mut |_, bv| bv.translation())
 }
 
+/// Binned Surface Area Heuristic construction function to be used with
+/// `BVT::new_with_partitioner`.
+///
+/// The leaves are projected on `nbins` bins along each axis. The split minimizing the sum of the
+/// surface areas of the two parts weighted by their number of leaves is selected.
+pub fn sah_partitioner_with_centers<N, V, B, BV>(depth:  uint,
+                                                 leaves: Vec<(B, BV)>,
+                                                 nbins:  uint,
+                                                 center: &mut |&B, &BV| -> V)
+                                                 -> (BV, BinaryPartition<B, BV>)
+    where N:  Scalar,
+          V:  Vect<N>,
+          BV: BoundingVolume<N> + HasSurfaceArea<N> + Clone {
+    assert!(nbins >= 2, "The SAH partitioner needs at least two bins.");
+
+    if leaves.len() <= 2 {
+        return median_partitioner_with_centers(depth, leaves, center);
+    }
+
+    let centers: Vec<V> = leaves.iter().map(|&(ref b, ref bv)| (*center)(b, bv)).collect();
+
+    let mut cmins = centers[0].clone();
+    let mut cmaxs = centers[0].clone();
+
+    for c in centers.iter() {
+        cmins = na::inf(&cmins, c);
+        cmaxs = na::sup(&cmaxs, c);
+    }
+
+    // (axis, bin index of the first right bin, cost)
+    let mut best: Option<(uint, uint, N)> = None;
+    let _nbins: N = na::cast(nbins as f64);
+
+    for axis in range(0, na::dim::<V>()) {
+        let extent = cmaxs[axis] - cmins[axis];
+
+        if extent <= na::zero() {
+            continue;
+        }
+
+        let mut counts: Vec<uint>       = Vec::from_elem(nbins, 0u);
+        let mut bins:   Vec<Option<BV>> = Vec::from_fn(nbins, |_| None);
+
+        for (c, &(_, ref bv)) in centers.iter().zip(leaves.iter()) {
+            let ibin = sah_bin(c[axis], cmins[axis], extent, _nbins, nbins);
+
+            counts[ibin] = counts[ibin] + 1;
+
+            let merged = match bins[ibin] {
+                Some(ref bbv) => bbv.merged(bv),
+                None          => bv.clone()
+            };
+
+            bins[ibin] = Some(merged);
+        }
+
+        // Sweep from the right to get the areas of the right parts.
+        let mut right_areas: Vec<N> = Vec::from_elem(nbins, na::zero());
+        let mut right_bv: Option<BV> = None;
+
+        for i in range(1, nbins).rev() {
+            right_bv = sah_merge(right_bv, &bins[i]);
+
+            right_areas[i] = match right_bv {
+                Some(ref bv) => bv.surface_area(),
+                None         => na::zero()
+            };
+        }
+
+        // Sweep from the left and evaluate the cost of each split.
+        let mut left_bv: Option<BV> = None;
+        let mut nleft = 0u;
+
+        for i in range(1, nbins) {
+            left_bv = sah_merge(left_bv, &bins[i - 1]);
+            nleft   = nleft + counts[i - 1];
+
+            let nright = leaves.len() - nleft;
+
+            if nleft == 0 || nright == 0 {
+                continue;
+            }
+
+            let left_area = match left_bv {
+                Some(ref bv) => bv.surface_area(),
+                None         => na::zero()
+            };
+
+            let _nleft:  N = na::cast(nleft as f64);
+            let _nright: N = na::cast(nright as f64);
+            let cost       = left_area * _nleft + right_areas[i] * _nright;
+
+            let better = match best {
+                Some((_, _, ref best_cost)) => cost < *best_cost,
+                None                        => true
+            };
+
+            if better {
+                best = Some((axis, i, cost));
+            }
+        }
+    }
+
+    match best {
+        None => median_partitioner_with_centers(depth, leaves, center),
+        Some((axis, split, _)) => {
+            let extent = cmaxs[axis] - cmins[axis];
+
+            let mut left  = Vec::new();
+            let mut right = Vec::new();
+            let mut bounding_bounding_volume = leaves[0].ref1().clone();
+
+            for (c, (b, bv)) in centers.iter().zip(leaves.into_iter()) {
+                bounding_bounding_volume.merge(&bv);
+
+                if sah_bin(c[axis], cmins[axis], extent, _nbins, nbins) < split {
+                    left.push((b, bv));
+                }
+                else {
+                    right.push((b, bv));
+                }
+            }
+
+            (bounding_bounding_volume, BinaryPartition::Parts(left, right))
+        }
+    }
+}
+
+/// Binned Surface Area Heuristic construction function to be used with
+/// `BVT::new_with_partitioner`.
+///
+/// This uses 16 bins, and the bounding volumes centers as the leaves centers.
+pub fn sah_partitioner<N, V, B, BV>(depth:  uint,
+                                    leaves: Vec<(B, BV)>)
+                                    -> (BV, BinaryPartition<B, BV>)
+    where N:  Scalar,
+          V:  Vect<N>,
+          BV: Translation<V> + BoundingVolume<N> + HasSurfaceArea<N> + Clone {
+    sah_partitioner_with_centers(depth, leaves, 16, &mut |_, bv| bv.translation())
+}
+
+#[inline]
+fn sah_bin<N: Scalar>(coord: N, min: N, extent: N, _nbins: N, nbins: uint) -> uint {
+    let ibin = ((coord - min) / extent * _nbins).floor();
+
+    // NOTE: `to_uint` returns `None` for negative values.
+    na::min(ibin.to_uint().unwrap_or(0), nbins - 1)
+}
+
+#[inline]
+fn sah_merge<N, BV>(acc: Option<BV>, bv: &Option<BV>) -> Option<BV>
+    where BV: BoundingVolume<N> + Clone {
+    match (acc, bv) {
+        (Some(acc), &Some(ref bv)) => Some(acc.merged(bv)),
+        (None, &Some(ref bv))      => Some(bv.clone()),
+        (acc, &None)               => acc
+    }
+}
+
 fn _new_with_partitioner<B, BV>(depth:       uint,
                                 leaves:      Vec<(B, BV)>,
                                 partitioner: |uint, Vec<(B, BV)>| -> (BV, BinaryPartition<B, BV>))
@@ -431,3 +1015,216 @@ fn __new_with_partitioner<B, BV>(depth:       uint,
         }
     }
 }
+
+fn _new_with_partitioner_parallel<B: Send, BV: Send>(
+    depth:          uint,
+    leaves:         Vec<(B, BV)>,
+    partitioner:    fn(uint, Vec<(B, BV)>) -> (BV, BinaryPartition<B, BV>),
+    parallel_depth: uint)
+    -> BVTNode<B, BV> {
+    if depth >= parallel_depth {
+        return _new_with_partitioner(depth, leaves, |i, p| partitioner(i, p));
+    }
+
+    let (bv, partitions) = partitioner(depth, leaves);
+
+    match partitions {
+        BinaryPartition::Part(b)            => BVTNode::Leaf(bv, b),
+        BinaryPartition::Parts(left, right) => {
+            // The left subtree is built on another thread while this one builds the right one.
+            let left = Future::spawn(proc() {
+                _new_with_partitioner_parallel(depth + 1, left, partitioner, parallel_depth)
+            });
+            let right = _new_with_partitioner_parallel(depth + 1, right, partitioner, parallel_depth);
+
+            BVTNode::Internal(bv, box left.unwrap(), box right)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cmp;
+    use std::rand::{IsaacRng, Rng};
+    use na::{Pnt3, Vec3};
+    use na;
+    use bounding_volume::{AABB, BoundingVolume};
+    use point::LocalPointQuery;
+    use ray::{LocalRayCast, RayPacket};
+    use partitioning::{BVT, BVTNode, BoundingVolumeInterferencesCollector, median_partitioner,
+                       sah_partitioner};
+
+    type TestBVT = BVT<uint, AABB<Pnt3<f64>>>;
+
+    // Elongated boxes, clustered along the `x` axis.
+    fn random_leaves(rng: &mut IsaacRng, n: uint) -> Vec<(uint, AABB<Pnt3<f64>>)> {
+        range(0u, n).map(|i| {
+            let cluster = (i % 4) as f64 * 25.0;
+            let center  = Pnt3::new(cluster + rng.gen::<f64>() * 5.0, rng.gen::<f64>() * 5.0, rng.gen::<f64>() * 5.0);
+            let half    = Vec3::new(rng.gen::<f64>() * 3.0, rng.gen::<f64>() * 0.5, rng.gen::<f64>() * 0.5);
+
+            (i, AABB::new(center - half, center + half))
+        }).collect()
+    }
+
+    // Checks the bounding volumes of the subtree and collects its leaves.
+    fn check_node(node: &BVTNode<uint, AABB<Pnt3<f64>>>, leaves: &[(uint, AABB<Pnt3<f64>>)], out: &mut Vec<uint>) {
+        match *node {
+            BVTNode::Internal(ref bv, ref left, ref right) => {
+                assert!(bv.contains(left.bounding_volume()) && bv.contains(right.bounding_volume()));
+
+                check_node(&**left, leaves, out);
+                check_node(&**right, leaves, out);
+            },
+            BVTNode::Leaf(ref bv, ref b) => {
+                assert!(bv.contains(leaves[*b].ref1()) && leaves[*b].ref1().contains(bv));
+
+                out.push(*b)
+            }
+        }
+    }
+
+    fn check_bvt(bvt: &TestBVT, leaves: &[(uint, AABB<Pnt3<f64>>)], rng: &mut IsaacRng) {
+        let mut found = Vec::new();
+
+        check_node(bvt.tree.as_ref().unwrap(), leaves, &mut found);
+        found.sort();
+
+        assert!(found == range(0u, leaves.len()).collect());
+
+        for _ in range(0u, 20) {
+            let bv = random_leaves(rng, 1).pop().unwrap().val1();
+            let mut expected: Vec<uint> = leaves.iter().filter(|l| l.ref1().intersects(&bv)).map(|l| *l.ref0()).collect();
+            let mut found = Vec::new();
+
+            bvt.visit(&mut BoundingVolumeInterferencesCollector::new(&bv, &mut found));
+
+            expected.sort();
+            found.sort();
+            assert!(expected == found);
+        }
+    }
+
+    #[test]
+    fn test_bvt_sah() {
+        let mut rng = IsaacRng::new_unseeded();
+        let leaves  = random_leaves(&mut rng, 500);
+
+        let sah: TestBVT          = BVT::new_sah(leaves.clone());
+        let partitioned: TestBVT  = BVT::new_with_partitioner(leaves.clone(), sah_partitioner);
+        let parallel: TestBVT     = BVT::new_sah_parallel(leaves.clone(), 4);
+
+        check_bvt(&sah, leaves.as_slice(), &mut rng);
+        check_bvt(&partitioned, leaves.as_slice(), &mut rng);
+        check_bvt(&parallel, leaves.as_slice(), &mut rng);
+
+        let empty: TestBVT = BVT::new_sah(Vec::new());
+
+        assert!(empty.root_bounding_volume().is_none());
+    }
+
+    // Checks that two subtrees have the same structure, bounding volumes, and leaves.
+    fn same_node(a: &BVTNode<uint, AABB<Pnt3<f64>>>, b: &BVTNode<uint, AABB<Pnt3<f64>>>) -> bool {
+        match (a, b) {
+            (&BVTNode::Internal(ref bva, ref la, ref ra), &BVTNode::Internal(ref bvb, ref lb, ref rb)) => {
+                bva == bvb && same_node(&**la, &**lb) && same_node(&**ra, &**rb)
+            },
+            (&BVTNode::Leaf(ref bva, ref a), &BVTNode::Leaf(ref bvb, ref b)) => bva == bvb && a == b,
+            _ => false
+        }
+    }
+
+    fn same_bvt(a: &TestBVT, b: &TestBVT) -> bool {
+        match (&a.tree, &b.tree) {
+            (&Some(ref a), &Some(ref b)) => same_node(a, b),
+            (&None, &None)               => true,
+            _                            => false
+        }
+    }
+
+    #[test]
+    fn test_bvt_parallel_matches_sequential() {
+        let mut rng = IsaacRng::new_unseeded();
+        let leaves  = random_leaves(&mut rng, 300);
+
+        let median = median_partitioner::<f64, Vec3<f64>, uint, AABB<Pnt3<f64>>>;
+        let sah    = sah_partitioner::<f64, Vec3<f64>, uint, AABB<Pnt3<f64>>>;
+
+        let balanced: TestBVT = BVT::new_balanced(leaves.clone());
+        let median_seq: TestBVT = BVT::new_with_partitioner(leaves.clone(), median);
+        let sah_seq: TestBVT  = BVT::new_sah(leaves.clone());
+
+        // More threads than leaves, and a number of threads that is not a power of two.
+        for nthreads in [ 0u, 1, 2, 3, 4, 8, 1000 ].iter() {
+            let median_par: TestBVT = BVT::new_with_partitioner_parallel(leaves.clone(), median, *nthreads);
+            let sah_par: TestBVT    = BVT::new_with_partitioner_parallel(leaves.clone(), sah, *nthreads);
+
+            assert!(same_bvt(&median_seq, &median_par));
+            assert!(same_bvt(&sah_seq, &sah_par));
+            assert!(same_bvt(&balanced, &BVT::new_balanced_parallel(leaves.clone(), *nthreads)));
+            assert!(same_bvt(&sah_seq, &BVT::new_sah_parallel(leaves.clone(), *nthreads)));
+        }
+
+        let empty: TestBVT = BVT::new_with_partitioner_parallel(Vec::new(), sah, 4);
+
+        assert!(empty.root_bounding_volume().is_none());
+    }
+
+    #[test]
+    fn test_bvt_k_nearest_within_radius() {
+        let mut rng = IsaacRng::new_unseeded();
+        let leaves  = random_leaves(&mut rng, 200);
+        let bvt: TestBVT = BVT::new_sah(leaves.clone());
+
+        for _ in range(0u, 20) {
+            let pt = Pnt3::new(rng.gen::<f64>() * 90.0 - 5.0, rng.gen::<f64>() * 10.0 - 2.5, rng.gen::<f64>() * 10.0 - 2.5);
+            let mut expected: Vec<f64> = leaves.iter().map(|l| l.ref1().distance_to_point(&pt)).collect();
+
+            expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+            // `k` larger than the number of leaves returns all the leaves.
+            for k in [ 0u, 1, 10, 300 ].iter() {
+                let found = bvt.k_nearest(&pt, *k, &mut |b, pt| leaves[*b].ref1().distance_to_point(pt));
+
+                assert!(found.len() == cmp::min(*k, leaves.len()));
+
+                for (i, &(dist, b)) in found.iter().enumerate() {
+                    assert!(dist == expected[i] && dist == leaves[*b].ref1().distance_to_point(&pt));
+                }
+            }
+
+            let found = bvt.within_radius(&pt, 4.0, &mut |b, pt| leaves[*b].ref1().distance_to_point(pt));
+
+            assert!(found.len() == expected.iter().filter(|d| **d <= 4.0).count());
+
+            for (i, &(dist, b)) in found.iter().enumerate() {
+                assert!(dist == expected[i] && dist == leaves[*b].ref1().distance_to_point(&pt));
+            }
+        }
+
+        let empty: TestBVT = BVT::new_sah(Vec::new());
+        let origin = Pnt3::new(0.0f64, 0.0, 0.0);
+
+        assert!(empty.k_nearest(&origin, 3, &mut |_, _| 0.0).is_empty());
+        assert!(empty.within_radius(&origin, 3.0, &mut |_, _| 0.0).is_empty());
+    }
+
+    #[test]
+    fn test_bvt_cast_ray_packet() {
+        let mut rng = IsaacRng::new_unseeded();
+        let leaves  = random_leaves(&mut rng, 300);
+        let bvt: TestBVT = BVT::new_sah(leaves.clone());
+
+        let dirs: Vec<Vec3<f64>> = range(0u, 64).map(|_| {
+            na::normalize(&Vec3::new(1.0, rng.gen::<f64>() * 0.2 - 0.1, rng.gen::<f64>() * 0.2 - 0.1))
+        }).collect();
+        let packet = RayPacket::new_with_origin(Pnt3::new(-10.0f64, 2.5, 2.5), dirs.as_slice());
+        let res    = bvt.cast_ray_packet(&packet, &mut |b, ray| leaves[*b].ref1().toi_with_ray(ray, true).map(|t| (t, ())));
+
+        for (ray, inter) in packet.rays().iter().zip(res.iter()) {
+            let expected = bvt.cast_ray(ray, &mut |b, ray| leaves[*b].ref1().toi_with_ray(ray, true).map(|t| (t, ())));
+
+            assert!(expected.map(|e| *e.ref0()) == inter.as_ref().map(|e| *e.ref0()));
+        }
+    }
+}
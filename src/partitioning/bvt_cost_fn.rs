@@ -1,4 +1,4 @@
-use ray::{Ray, LocalRayCast, RayIntersection};
+use ray::{Ray, LocalRayCast, RayIntersection, RayCastOptions, InsideMode};
 use math::Scalar;
 
 
@@ -12,18 +12,33 @@ pub trait BVTCostFn<N, B, BV, R> {
 
 /// A search thet selects the objects that has the smallest time of impact with a given ray.
 pub struct RayIntersectionCostFn<'a, N, P: 'a, V: 'a> {
-    ray:   &'a Ray<P, V>,
-    solid: bool,
-    uvs:   bool
+    ray:     &'a Ray<P, V>,
+    options: RayCastOptions<N>,
+    uvs:     bool
 }
 
-impl<'a, N, P, V> RayIntersectionCostFn<'a, N, P, V> {
+impl<'a, N: Scalar, P, V> RayIntersectionCostFn<'a, N, P, V> {
     /// Creates a new `BestRayInterferenceSearch`.
     pub fn new(ray: &'a Ray<P, V>, solid: bool, uvs: bool) -> RayIntersectionCostFn<'a, N, P, V> {
+        let mut options = RayCastOptions::new();
+
+        if !solid {
+            options.inside = InsideMode::Hollow;
+        }
+
+        RayIntersectionCostFn::new_with_options(ray, options, uvs)
+    }
+
+    /// Creates a new `BestRayInterferenceSearch` using the given ray casting options.
+    ///
+    /// Use `BVT::best_first_search_with_max_cost` with `options.max_toi` to prune the subtrees
+    /// too far away from the ray origin.
+    pub fn new_with_options(ray: &'a Ray<P, V>, options: RayCastOptions<N>, uvs: bool)
+                            -> RayIntersectionCostFn<'a, N, P, V> {
         RayIntersectionCostFn {
-            ray:   ray,
-            solid: solid,
-            uvs:   uvs
+            ray:     ray,
+            options: options,
+            uvs:     uvs
         }
     }
 }
@@ -40,10 +55,10 @@ impl<'a, N, P, V, B, BV> BVTCostFn<N, B, BV, RayIntersection<N, V>> for RayInter
     #[inline]
     fn compute_b_cost(&mut self, b: &B) -> Option<(N, RayIntersection<N, V>)> {
         if self.uvs {
-            b.toi_and_normal_and_uv_with_ray(self.ray, self.solid).map(|i| (i.toi, i))
+            b.toi_and_normal_and_uv_with_ray_and_options(self.ray, &self.options).map(|i| (i.toi, i))
         }
         else {
-            b.toi_and_normal_with_ray(self.ray, self.solid).map(|i| (i.toi, i))
+            b.toi_and_normal_with_ray_and_options(self.ray, &self.options).map(|i| (i.toi, i))
         }
     }
 }
@@ -1,4 +1,4 @@
-use bounding_volume::BoundingVolume;
+use bounding_volume::{BoundingVolume, Frustum, FrustumClassifiable, Containment};
 use ray::{Ray, LocalRayCast};
 use point::LocalPointQuery;
 
@@ -106,3 +106,157 @@ impl<'a, N, P, B, BV> BVTVisitor<B, BV> for PointInterferencesCollector<'a, P, B
         }
     }
 }
+
+/// Bounding Volume Tree visitor collecting nodes that may be inside of a given frustum.
+///
+/// The frustum tests are skipped for the nodes enclosed by a node found completely inside of the
+/// frustum.
+pub struct FrustumInterferencesCollector<'a, N: 'a, B: 'a, BV> {
+    frustum:   &'a Frustum<N>,
+    inside:    Option<BV>,
+    collector: &'a mut Vec<B>
+}
+
+impl<'a, N, B, BV> FrustumInterferencesCollector<'a, N, B, BV> {
+    /// Creates a new `FrustumInterferencesCollector`.
+    #[inline]
+    pub fn new(frustum: &'a Frustum<N>, buffer: &'a mut Vec<B>) -> FrustumInterferencesCollector<'a, N, B, BV> {
+        FrustumInterferencesCollector {
+            frustum:   frustum,
+            inside:    None,
+            collector: buffer
+        }
+    }
+
+    // Tests if `bv` is enclosed by the last node found inside of the frustum. Because the frustum
+    // is convex, such a bounding volume is inside of the frustum too.
+    #[inline]
+    fn is_known_inside<N2>(&self, bv: &BV) -> bool
+        where BV: BoundingVolume<N2> {
+        match self.inside {
+            Some(ref inside) => inside.contains(bv),
+            None             => false
+        }
+    }
+}
+
+impl<'a, N, N2, B, BV> BVTVisitor<B, BV> for FrustumInterferencesCollector<'a, N, B, BV>
+    where B:  Clone,
+          BV: BoundingVolume<N2> + FrustumClassifiable<N> + Clone {
+    #[inline]
+    fn visit_internal(&mut self, bv: &BV) -> bool {
+        if self.is_known_inside(bv) {
+            return true;
+        }
+
+        match bv.classify_with_frustum(self.frustum) {
+            Containment::Outside      => false,
+            Containment::Intersecting => true,
+            Containment::Inside       => {
+                self.inside = Some(bv.clone());
+                true
+            }
+        }
+    }
+
+    #[inline]
+    fn visit_leaf(&mut self, b: &B, bv: &BV) {
+        if self.is_known_inside(bv) || bv.classify_with_frustum(self.frustum) != Containment::Outside {
+            self.collector.push(b.clone())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::Cell;
+    use na::{Pnt3, Mat4};
+    use na;
+    use bounding_volume::{AABB, BoundingVolume, Frustum, FrustumClassifiable, Containment};
+    use partitioning::{BVTVisitor, FrustumInterferencesCollector};
+
+    // An AABB counting its classifications against a frustum.
+    #[deriving(Clone)]
+    struct CountingAABB<'a> {
+        aabb:  AABB<Pnt3<f64>>,
+        count: &'a Cell<uint>
+    }
+
+    impl<'a> BoundingVolume<f64> for CountingAABB<'a> {
+        fn intersects(&self, other: &CountingAABB<'a>) -> bool {
+            self.aabb.intersects(&other.aabb)
+        }
+
+        fn contains(&self, other: &CountingAABB<'a>) -> bool {
+            self.aabb.contains(&other.aabb)
+        }
+
+        fn merge(&mut self, other: &CountingAABB<'a>) {
+            self.aabb.merge(&other.aabb)
+        }
+
+        fn merged(&self, other: &CountingAABB<'a>) -> CountingAABB<'a> {
+            CountingAABB { aabb: self.aabb.merged(&other.aabb), count: self.count }
+        }
+
+        fn loosen(&mut self, margin: f64) {
+            self.aabb.loosen(margin)
+        }
+
+        fn loosened(&self, margin: f64) -> CountingAABB<'a> {
+            CountingAABB { aabb: self.aabb.loosened(margin), count: self.count }
+        }
+
+        fn tighten(&mut self, margin: f64) {
+            self.aabb.tighten(margin)
+        }
+
+        fn tightened(&self, margin: f64) -> CountingAABB<'a> {
+            CountingAABB { aabb: self.aabb.tightened(margin), count: self.count }
+        }
+    }
+
+    fn counting_aabb<'a>(mins: Pnt3<f64>, maxs: Pnt3<f64>, count: &'a Cell<uint>) -> CountingAABB<'a> {
+        CountingAABB { aabb: AABB::new(mins, maxs), count: count }
+    }
+
+    impl<'a> FrustumClassifiable<f64> for CountingAABB<'a> {
+        fn classify_with_frustum(&self, frustum: &Frustum<f64>) -> Containment {
+            self.count.set(self.count.get() + 1);
+            self.aabb.classify_with_frustum(frustum)
+        }
+    }
+
+    #[test]
+    fn test_frustum_collector_skips_inside_subtrees() {
+        // The orthographic projection of the cube `[-1, 1]^3`.
+        let frustum: Frustum<f64> = Frustum::new_with_matrix(&na::one::<Mat4<f64>>());
+        let count   = Cell::new(0u);
+        let root    = counting_aabb(Pnt3::new(-0.5, -0.5, -0.5), Pnt3::new(0.5, 0.5, 0.5), &count);
+        let child   = counting_aabb(Pnt3::new(-0.5, -0.5, -0.5), Pnt3::new(0.0, 0.0, 0.0), &count);
+        let leaf1   = counting_aabb(Pnt3::new(-0.4, -0.4, -0.4), Pnt3::new(-0.1, -0.1, -0.1), &count);
+        let leaf2   = counting_aabb(Pnt3::new(0.1, 0.1, 0.1), Pnt3::new(0.4, 0.4, 0.4), &count);
+        let outside = counting_aabb(Pnt3::new(1.5, 1.5, 1.5), Pnt3::new(2.0, 2.0, 2.0), &count);
+
+        let mut out = Vec::new();
+
+        {
+            let mut collector = FrustumInterferencesCollector::new(&frustum, &mut out);
+
+            // The root is inside of the frustum: its subtree is never classified again.
+            assert!(collector.visit_internal(&root));
+            assert!(count.get() == 1);
+
+            assert!(collector.visit_internal(&child));
+            collector.visit_leaf(&0u, &leaf1);
+            collector.visit_leaf(&1u, &leaf2);
+            assert!(count.get() == 1);
+
+            // Nodes outside of the inside subtree are classified.
+            collector.visit_leaf(&2u, &outside);
+            assert!(count.get() == 2);
+        }
+
+        assert!(out == vec!(0u, 1));
+    }
+}
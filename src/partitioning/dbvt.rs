@@ -1,512 +1,1129 @@
 //! A Dynamic Bounding Volume Tree.
 
-use std::cell::RefCell;
-use std::rc::Rc;
-use std::ptr;
-use std::mem;
-use utils::data::owned_allocation_cache::OwnedAllocationCache;
+use std::uint;
+use std::collections::BinaryHeap;
 use na::{FloatVec, Translation};
 use na;
-use bounding_volume::BoundingVolume;
+use bounding_volume::{BoundingVolume, HasSurfaceArea};
+use point::LocalPointQuery;
 use partitioning::bvt_visitor::{BVTVisitor, BoundingVolumeInterferencesCollector};
+use partitioning::bvt;
+use utils::data::ref_with_cost::RefWithCost;
 use math::{Scalar, Point};
 
 
-#[deriving(Encodable, Decodable)]
-enum UpdateState {
-    NeedsShrink,
-    UpToDate
+// Index used for non-existing parents.
+static NONE: uint = uint::MAX;
+
+/// Stable identifier of a leaf of a `DBVT`.
+///
+/// It remains valid until the leaf is removed from the tree. The storage of a removed leaf may
+/// be reused by another leaf, but its identifier will differ by its generation so that a stale
+/// identifier is never mistaken for the new leaf.
+#[deriving(Clone, PartialEq, Eq, Hash, Show, Encodable, Decodable)]
+pub struct LeafId {
+    index:      uint,
+    generation: uint
 }
 
-type Cache<P, B, BV> = OwnedAllocationCache<DBVTInternal<P, B, BV>>;
+#[deriving(Clone, PartialEq, Eq, Encodable, Decodable)]
+enum DBVTNodeId {
+    Internal(uint),
+    Leaf(uint)
+}
 
 /// A Dynamic Bounding Volume Tree.
+///
+/// The nodes and leaves are stored on contiguous buffers, and leaves are addressed by a stable
+/// `LeafId`. Thus, this tree is `Send` and `Sync` whenever its content is.
+#[deriving(Clone, Encodable, Decodable)]
 pub struct DBVT<P, B, BV> {
-    cache: Cache<P, B, BV>,
-    tree:  Option<DBVTNode<P, B, BV>>,
-    len:   uint
+    root:           Option<DBVTNodeId>,
+    leaves:         Vec<Option<DBVTLeaf<P, B, BV>>>,
+    generations:    Vec<uint>,
+    free_leaves:    Vec<uint>,
+    internals:      Vec<Option<DBVTInternal<P, BV>>>,
+    free_internals: Vec<uint>,
+    len:            uint
+}
+
+/// Leaf of a Dynamic Bounding Volume Tree.
+#[deriving(Clone, Encodable, Decodable)]
+pub struct DBVTLeaf<P, B, BV> {
+    /// The bounding volume of this node.
+    pub bounding_volume: BV,
+    /// The center of this node bounding volume.
+    pub center:          P,
+    /// An user-defined object.
+    pub object:          B,
+    /// Index of this node parent.
+    parent:              uint,
+    /// Whether this leaf is part of the tree.
+    attached:            bool
+}
+
+/// Internal node of a DBVT. An internal node always has two children.
+#[deriving(Clone, Encodable, Decodable)]
+struct DBVTInternal<P, BV> {
+    /// The bounding volume of this node. It always encloses both its children bounding volumes.
+    bounding_volume: BV,
+    /// The center of this node bounding volume.
+    center:          P,
+    /// This node left child.
+    left:            DBVTNodeId,
+    /// This node right child.
+    right:           DBVTNodeId,
+    /// Index of this node parent.
+    parent:          uint
+}
+
+impl<N, P: Point<N, V>, V, B, BV: Translation<V>> DBVTLeaf<P, B, BV> {
+    /// Creates a new leaf.
+    pub fn new(bounding_volume: BV, object: B) -> DBVTLeaf<P, B, BV> {
+        DBVTLeaf {
+            center:          na::orig::<P>() + bounding_volume.translation(),
+            bounding_volume: bounding_volume,
+            object:          object,
+            parent:          NONE,
+            attached:        false
+        }
+    }
+}
+
+impl<N, P: Point<N, V>, V, BV: Translation<V>> DBVTInternal<P, BV> {
+    /// Creates a new internal node.
+    fn new(bounding_volume: BV, parent: uint, left: DBVTNodeId, right: DBVTNodeId)
+           -> DBVTInternal<P, BV> {
+        DBVTInternal {
+            center:          na::orig::<P>() + bounding_volume.translation(),
+            bounding_volume: bounding_volume,
+            left:            left,
+            right:           right,
+            parent:          parent
+        }
+    }
 }
 
 impl<P, B, BV> DBVT<P, B, BV> {
     /// Creates a new Dynamic Bounding Volume Tree.
     pub fn new() -> DBVT<P, B, BV> {
         DBVT {
-            cache: OwnedAllocationCache::new(),
-            tree:  None,
-            len:   0
+            root:           None,
+            leaves:         Vec::new(),
+            generations:    Vec::new(),
+            free_leaves:    Vec::new(),
+            internals:      Vec::new(),
+            free_internals: Vec::new(),
+            len:            0
         }
     }
-}
 
-impl<N, P, V, B, BV> DBVT<P, B, BV>
-    where N: Scalar,
-          P:  Point<N, V>,
-          V:  FloatVec<N>,
-          BV: 'static + BoundingVolume<N> + Translation<V> + Clone,
-          B:  'static + Clone {
-    /// Removes a leaf from the tree. Fails if the tree is empty.
-    pub fn remove(&mut self, leaf: &mut Rc<RefCell<DBVTLeaf<P, B, BV>>>) {
-        let self_tree = self.tree.take().unwrap();
+    /// The number of leaves of this tree.
+    #[inline]
+    pub fn len(&self) -> uint {
+        self.len
+    }
 
-        let mut bleaf = (*leaf).borrow_mut();
-        self.tree = bleaf.unlink(&mut self.cache, self_tree);
-        self.len  = self.len - 1;
+    /// Whether this tree has no leaf.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
     }
 
-    // FIXME: it feels strange that this method takes (B, BV) in this order while the leaves
-    // constructor takes (BV, B)…
-    /// Creates, inserts, and returns a new leaf with the given content.
-    pub fn insert_new(&mut self, b: B, bv: BV) -> Rc<RefCell<DBVTLeaf<P, B, BV>>> {
-        let leaf = Rc::new(RefCell::new(DBVTLeaf::new(bv, b)));
+    /// Whether the leaf with the given identifier is on this tree.
+    ///
+    /// This is `false` for the identifier of a removed leaf.
+    #[inline]
+    pub fn contains(&self, id: LeafId) -> bool {
+        id.index < self.leaves.len()                   &&
+        self.generations[id.index] == id.generation &&
+        self.leaves[id.index].is_some()
+    }
 
-        self.insert(leaf.clone());
+    /// The leaf with the given identifier.
+    ///
+    /// Returns `None` if the leaf is not on this tree.
+    #[inline]
+    pub fn get(&self, id: LeafId) -> Option<&DBVTLeaf<P, B, BV>> {
+        if self.contains(id) {
+            self.leaves[id.index].as_ref()
+        }
+        else {
+            None
+        }
+    }
 
-        leaf
+    #[inline]
+    fn leaf(&self, i: uint) -> &DBVTLeaf<P, B, BV> {
+        self.leaves[i].as_ref().expect("DBVT internal error: invalid leaf.")
     }
 
-    /// Inserts a leaf to the tree.
-    pub fn insert(&mut self, leaf: Rc<RefCell<DBVTLeaf<P, B, BV>>>) {
-        let mut self_tree = None;
-        mem::swap(&mut self_tree, &mut self.tree);
+    #[inline]
+    fn leaf_mut(&mut self, i: uint) -> &mut DBVTLeaf<P, B, BV> {
+        self.leaves[i].as_mut().expect("DBVT internal error: invalid leaf.")
+    }
 
-        self.tree = match self_tree {
-            None    => Some(DBVTNode::Leaf(leaf)),
-            Some(t) => Some(DBVTNode::Internal(t.insert(&mut self.cache, leaf)))
-        };
+    #[inline]
+    fn internal(&self, i: uint) -> &DBVTInternal<P, BV> {
+        self.internals[i].as_ref().expect("DBVT internal error: invalid internal node.")
+    }
 
-        self.len = self.len + 1;
+    #[inline]
+    fn internal_mut(&mut self, i: uint) -> &mut DBVTInternal<P, BV> {
+        self.internals[i].as_mut().expect("DBVT internal error: invalid internal node.")
     }
 
-    /// Visit this tree using… a visitor!
-    pub fn visit<Vis: BVTVisitor<Rc<RefCell<DBVTLeaf<P, B, BV>>>, BV>>(&self, visitor: &mut Vis) {
-        match self.tree {
-            Some(ref t) => t.visit(visitor),
-            None        => { }
+    #[inline]
+    fn node_bounding_volume(&self, node: DBVTNodeId) -> &BV {
+        match node {
+            DBVTNodeId::Internal(i) => &self.internal(i).bounding_volume,
+            DBVTNodeId::Leaf(i)     => &self.leaf(i).bounding_volume
         }
     }
 
-    /// Finds all leaves which have their bounding boxes intersecting a specific leave's bounding
-    /// volume.
-    ///
-    /// # Arguments:
-    /// * `to_test` - the leaf to check interferences with.
-    /// * `out` - will be filled with all leaves intersecting `to_test`. Note that `to_test`
-    ///           is not considered intersecting itself.
-    pub fn interferences_with_leaf(&self,
-                                   leaf: &DBVTLeaf<P, B, BV>,
-                                   out:  &mut Vec<Rc<RefCell<DBVTLeaf<P, B, BV>>>>) {
-        match self.tree {
-            Some(ref tree) => tree.interferences_with_leaf(leaf, out),
-            None           => { }
+    #[inline]
+    fn node_center(&self, node: DBVTNodeId) -> &P {
+        match node {
+            DBVTNodeId::Internal(i) => &self.internal(i).center,
+            DBVTNodeId::Leaf(i)     => &self.leaf(i).center
         }
     }
 
-    /// Finds all interferences between this tree and another one.
-    pub fn interferences_with_tree(&self,
-                                   leaf: &DBVT<P, B, BV>,
-                                   out:  &mut Vec<Rc<RefCell<DBVTLeaf<P, B, BV>>>>) {
-        match (&self.tree, &leaf.tree) {
-            (&Some(ref a), &Some(ref b)) => a.interferences_with_tree(b, out),
-            (&None, _) => { },
-            (_, &None) => { }
+    #[inline]
+    fn set_parent(&mut self, node: DBVTNodeId, parent: uint) {
+        match node {
+            DBVTNodeId::Internal(i) => self.internal_mut(i).parent = parent,
+            DBVTNodeId::Leaf(i)     => self.leaf_mut(i).parent     = parent
         }
     }
-}
 
-/// Node of the Dynamic Bounding Volume Tree.
-enum DBVTNode<P, B, BV> {
-    Internal(Box<DBVTInternal<P, B, BV>>),
-    Leaf(Rc<RefCell<DBVTLeaf<P, B, BV>>>),
-    Invalid
-}
+    // Replaces the child `old` of the internal node `parent` by `new`.
+    fn replace_child(&mut self, parent: uint, old: DBVTNodeId, new: DBVTNodeId) {
+        let p = self.internal_mut(parent);
 
-/// Internal node of a DBVT. An internal node always has two children.
-struct DBVTInternal<P, B, BV> {
-    /// The bounding volume of this node. It always encloses both its children bounding volumes.
-    bounding_volume: BV,
-    /// The center of this node bounding volume.
-    center:          P,
-    /// This node left child.
-    left:            DBVTNode<P, B, BV>,
-    /// This node right child.
-    right:           DBVTNode<P, B, BV>,
-    /// This node parent.
-    parent:          *mut DBVTInternal<P, B, BV>,
+        if p.left == old {
+            p.left = new;
+        }
+        else {
+            assert!(p.right == old, "DBVT internal error: the child does not belong to the node.");
+            p.right = new;
+        }
+    }
 
-    state:           UpdateState
-}
+    fn alloc_internal(&mut self, internal: DBVTInternal<P, BV>) -> uint {
+        match self.free_internals.pop() {
+            Some(i) => {
+                self.internals[i] = Some(internal);
+                i
+            },
+            None => {
+                self.internals.push(Some(internal));
+                self.internals.len() - 1
+            }
+        }
+    }
 
-impl<N, P: Point<N, V>, V, BV: Translation<V>, B> DBVTInternal<P, B, BV> {
-    /// Creates a new internal node.
-    fn new(bounding_volume: BV,
-           parent:          *mut DBVTInternal<P, B, BV>,
-           left:            DBVTNode<P, B, BV>,
-           right:           DBVTNode<P, B, BV>)
-           -> DBVTInternal<P, B, BV> {
-        DBVTInternal {
-            center:          na::orig::<P>() + bounding_volume.translation(),
-            bounding_volume: bounding_volume,
-            left:            left,
-            right:           right,
-            parent:          parent,
-            state:           UpdateState::UpToDate
+    fn free_internal(&mut self, i: uint) {
+        self.internals[i] = None;
+        self.free_internals.push(i);
+    }
+
+    /// Visit this tree using… a visitor!
+    pub fn visit<Vis: BVTVisitor<B, BV>>(&self, visitor: &mut Vis) {
+        match self.root {
+            Some(root) => self.visit_node(root, visitor),
+            None       => { }
         }
     }
-}
 
-#[allow(raw_pointer_deriving)]
-#[deriving(Clone)]
-/// State of a leaf.
-enum DBVTLeafState<P, B, BV> {
-    /// This leaf is the right child of another node.
-    RightChildOf(*mut DBVTInternal<P, B, BV>),
-    /// This leaf is the left child of another node.
-    LeftChildOf(*mut DBVTInternal<P, B, BV>),
-    /// This leaf is detached from any tree.
-    Detached
-}
+    fn visit_node<Vis: BVTVisitor<B, BV>>(&self, node: DBVTNodeId, visitor: &mut Vis) {
+        match node {
+            DBVTNodeId::Internal(i) => {
+                let internal = self.internal(i);
 
-impl<P, B, BV> DBVTLeafState<P, B, BV> {
-    /// Indicates whether this leaf is detached.
-    #[inline]
-    pub fn is_detached(&self) -> bool {
-        match *self {
-            DBVTLeafState::Detached => true,
-            _ => false
+                if visitor.visit_internal(&internal.bounding_volume) {
+                    self.visit_node(internal.left, visitor);
+                    self.visit_node(internal.right, visitor);
+                }
+            },
+            DBVTNodeId::Leaf(i) => {
+                let leaf = self.leaf(i);
+                visitor.visit_leaf(&leaf.object, &leaf.bounding_volume)
+            }
         }
     }
 
-    /// Returns a pointer to this leaf parent and `true` if it is the left child.
-    #[inline]
-    fn unwrap(self) -> (bool, *mut DBVTInternal<P, B, BV>) {
-        match self {
-            DBVTLeafState::RightChildOf(p) => (false, p),
-            DBVTLeafState::LeftChildOf(p)  => (true, p),
-            _               => panic!("Attempting to unwrap a detached node.")
+    /// Computes the depth of this tree.
+    pub fn depth(&self) -> uint {
+        match self.root {
+            Some(root) => self.node_depth(root),
+            None       => 0
         }
     }
-}
 
-/// Leaf of a Dynamic Bounding Volume Tree.
-#[deriving(Clone)]
-pub struct DBVTLeaf<P, B, BV> {
-    /// The bounding volume of this node.
-    pub bounding_volume: BV,
-    /// The center of this node bounding volume.
-    pub center:          P,
-    /// An user-defined object.
-    pub object:          B,
-    /// This node parent.
-    parent:              DBVTLeafState<P, B, BV>
-}
+    fn node_depth(&self, node: DBVTNodeId) -> uint {
+        match node {
+            DBVTNodeId::Internal(i) => {
+                let internal = self.internal(i);
 
-impl<P, B, BV> DBVTNode<P, B, BV> {
-    fn take_internal(self) -> Box<DBVTInternal<P, B, BV>> {
-        match self {
-            DBVTNode::Internal(i) => i,
-            _ => panic!("DBVT internal error: this is not an internal node.")
+                1 + na::max(self.node_depth(internal.left), self.node_depth(internal.right))
+            },
+            DBVTNodeId::Leaf(_) => 1
         }
     }
+}
 
-    fn invalidate(&mut self) -> DBVTNode<P, B, BV> {
-        let mut res = DBVTNode::Invalid;
+impl<N, P, V, B, BV> DBVT<P, B, BV>
+    where N:  Scalar,
+          P:  Point<N, V>,
+          V:  FloatVec<N>,
+          BV: BoundingVolume<N> + HasSurfaceArea<N> + Translation<V> + Clone,
+          B:  Clone {
+    /// Creates, inserts, and returns the identifier of a new leaf with the given content.
+    pub fn insert_new(&mut self, b: B, bv: BV) -> LeafId {
+        let leaf = DBVTLeaf::new(bv, b);
+
+        let i = match self.free_leaves.pop() {
+            Some(i) => {
+                self.leaves[i] = Some(leaf);
+                i
+            },
+            None => {
+                self.leaves.push(Some(leaf));
+                self.generations.push(0);
+                self.leaves.len() - 1
+            }
+        };
 
-        mem::swap(&mut res, self);
+        self.attach(i);
 
-        res
+        LeafId {
+            index:      i,
+            generation: self.generations[i]
+        }
     }
-}
 
-impl<P, B, BV> DBVTInternal<P, B, BV> {
-    fn is_right_internal_node(&self, r: &mut DBVTInternal<P, B, BV>) -> bool
-    {
-        match self.right {
-            DBVTNode::Internal(ref i) => &**i as *const DBVTInternal<P, B, BV> == &*r as *const DBVTInternal<P, B, BV>,
-            _ => false
+    /// Removes a leaf from the tree and returns it.
+    ///
+    /// Returns `None` if the leaf is not on this tree.
+    pub fn remove(&mut self, id: LeafId) -> Option<DBVTLeaf<P, B, BV>> {
+        if !self.contains(id) {
+            return None;
         }
+
+        let i = id.index;
+
+        self.detach(i);
+
+        let mut leaf = self.leaves[i].take().unwrap();
+
+        // Invalidate every identifier of the removed leaf.
+        self.generations[i] = self.generations[i] + 1;
+        self.free_leaves.push(i);
+
+        leaf.parent   = NONE;
+        leaf.attached = false;
+
+        Some(leaf)
     }
-}
 
-impl<N, P: Point<N, V>, V, B: 'static, BV: Translation<V> + 'static> DBVTLeaf<P, B, BV> {
-    /// Creates a new leaf.
-    pub fn new(bounding_volume: BV, object: B) -> DBVTLeaf<P, B, BV> {
-        DBVTLeaf {
-            center:          na::orig::<P>() + bounding_volume.translation(),
-            bounding_volume: bounding_volume,
-            object:          object,
-            parent:          DBVTLeafState::Detached
+    /// Replaces the bounding volume of a leaf, and moves it to its new place on the tree.
+    ///
+    /// Fails if the leaf is not on this tree.
+    pub fn update_bounding_volume(&mut self, id: LeafId, bv: BV) {
+        assert!(self.contains(id), "Attempting to update a leaf which is not on the tree.");
+
+        let i = id.index;
+
+        self.detach(i);
+
+        {
+            let leaf = self.leaf_mut(i);
+
+            leaf.center          = na::orig::<P>() + bv.translation();
+            leaf.bounding_volume = bv;
         }
+
+        self.attach(i);
     }
 
-    /// Removes this leaf from the tree.
-    ///
-    /// Returns the new root of the tree.
+    /// Finds all leaves which have their bounding boxes intersecting a specific leave's bounding
+    /// volume.
     ///
     /// # Arguments:
-    /// * `curr_root`: current root of the tree.
-    fn unlink(&mut self,
-              cache:     &mut Cache<P, B, BV>,
-              curr_root: DBVTNode<P, B, BV>) -> Option<DBVTNode<P, B, BV>> {
-        if !self.parent.is_detached() {
-            let (is_left, p) = mem::replace(&mut self.parent, DBVTLeafState::Detached).unwrap();
-
-            let pp           = unsafe { (*p).parent };
-            let parent_left  = unsafe { (*p).left.invalidate() };
-            let parent_right = unsafe { (*p).right.invalidate() };
-
-            let mut other = if is_left { parent_right } else { parent_left };
-
-            if pp.is_not_null() {
-                let is_p_right_to_pp = unsafe { (*pp).is_right_internal_node(&mut *p) };
-                // we are far away from the root
-                unsafe {
-                    match other {
-                        DBVTNode::Internal(ref mut i) => i.parent = pp,
-                        DBVTNode::Leaf(ref mut l)     => {
-                            (**l).borrow_mut().parent =
-                                if is_p_right_to_pp { DBVTLeafState::RightChildOf(pp) }
-                                else { DBVTLeafState::LeftChildOf(pp) }
-                        },
-                        DBVTNode::Invalid => unreachable!()
-                    }
+    /// * `id` - the leaf to check interferences with. Fails if it is not on this tree.
+    /// * `out` - will be filled with all leaves intersecting `id`. Note that `id` is not
+    ///           considered intersecting itself.
+    pub fn interferences_with_leaf(&self, id: LeafId, out: &mut Vec<B>) {
+        assert!(self.contains(id), "Attempting to query a leaf which is not on the tree.");
+
+        let itself    = DBVTNodeId::Leaf(id.index);
+        let bv        = &self.leaf(id.index).bounding_volume;
+        let mut stack = Vec::new();
+
+        match self.root {
+            Some(root) => stack.push(root),
+            None       => { }
+        }
 
-                    if is_p_right_to_pp {
-                        mem::swap(&mut (*pp).right, &mut other);
-                        // NOTE: the children have already been invalidated before
-                        cache.retain(other.take_internal())
+        loop {
+            match stack.pop() {
+                Some(node) => {
+                    if node == itself || !self.node_bounding_volume(node).intersects(bv) {
+                        continue;
                     }
-                    else {
-                        mem::swap(&mut (*pp).left, &mut other);
-                        // NOTE: the children have already been invalidated before
-                        cache.retain(other.take_internal())
+
+                    match node {
+                        DBVTNodeId::Internal(i) => {
+                            let internal = self.internal(i);
+
+                            stack.push(internal.right);
+                            stack.push(internal.left);
+                        },
+                        DBVTNodeId::Leaf(i) => out.push(self.leaf(i).object.clone())
                     }
+                },
+                None => break
+            }
+        }
+    }
+
+    /// Finds all leaves which have their bounding boxes intersecting a given bounding volume.
+    pub fn interferences_with_bounding_volume(&self, bv: &BV, out: &mut Vec<B>) {
+        let mut visitor = BoundingVolumeInterferencesCollector::new(bv, out);
+
+        self.visit(&mut visitor)
+    }
+
+    /// Finds all pairs of intersecting leaves between this tree and another one.
+    pub fn interferences_with_tree(&self, other: &DBVT<P, B, BV>, out: &mut Vec<(B, B)>) {
+        match (self.root, other.root) {
+            (Some(a), Some(b)) => self.node_interferences_with_tree(a, other, b, out),
+            _                  => { }
+        }
+    }
+
+    fn node_interferences_with_tree(&self,
+                                    a:     DBVTNodeId,
+                                    other: &DBVT<P, B, BV>,
+                                    b:     DBVTNodeId,
+                                    out:   &mut Vec<(B, B)>) {
+        if !self.node_bounding_volume(a).intersects(other.node_bounding_volume(b)) {
+            return;
+        }
+
+        match (a, b) {
+            (DBVTNodeId::Leaf(ia), DBVTNodeId::Leaf(ib)) => {
+                out.push((self.leaf(ia).object.clone(), other.leaf(ib).object.clone()))
+            },
+            (DBVTNodeId::Internal(ia), _) => {
+                let internal = self.internal(ia);
+
+                self.node_interferences_with_tree(internal.left, other, b, out);
+                self.node_interferences_with_tree(internal.right, other, b, out);
+            },
+            (_, DBVTNodeId::Internal(ib)) => {
+                let internal = other.internal(ib);
+
+                self.node_interferences_with_tree(a, other, internal.left, out);
+                self.node_interferences_with_tree(a, other, internal.right, out);
+            }
+        }
+    }
+
+    /// Computes the sum of the surface areas of the internal nodes of this tree.
+    ///
+    /// This is a measure of the tree quality: the lower, the better.
+    pub fn total_surface_area(&self) -> N {
+        let mut res = na::zero::<N>();
+
+        for internal in self.internals.iter() {
+            match *internal {
+                Some(ref internal) => res = res + internal.bounding_volume.surface_area(),
+                None               => { }
+            }
+        }
+
+        res
+    }
 
-                    (*pp).state = UpdateState::NeedsShrink;
+    /// Improves the quality of this tree by reinserting its worst-placed leaves.
+    ///
+    /// Each pass reinserts a tenth of the leaves, starting with those having the parent with the
+    /// largest surface area. Each leaf is reinserted where it increases the least the total surface
+    /// area of the tree, so that this never makes the tree worse.
+    pub fn optimize(&mut self, n_passes: uint) {
+        for _ in range(0, n_passes) {
+            if self.len < 3 {
+                return;
+            }
+
+            let mut leaves = Vec::new();
+
+            for (i, leaf) in self.leaves.iter().enumerate() {
+                match *leaf {
+                    Some(ref leaf) if leaf.attached && leaf.parent != NONE => {
+                        let area = self.internal(leaf.parent).bounding_volume.surface_area();
+                        leaves.push((area, i));
+                    },
+                    _ => { }
                 }
+            }
 
-                Some(curr_root)
+            leaves.sort_by(|a, b| b.ref0().partial_cmp(a.ref0()).unwrap_or(Equal));
+
+            let nreinsert = na::max(1, leaves.len() / 10);
+
+            for &(_, i) in leaves.iter().take(nreinsert) {
+                // The tree is not rotated before the reinsertion so that putting the leaf back
+                // next to its former sibling restores it exactly. Thus, the best position found
+                // cannot increase the total surface area.
+                let grand_parent = self.unlink(i);
+                self.refit_from(grand_parent);
+
+                let sibling = self.best_sibling(i);
+                self.attach_with_sibling(i, sibling);
             }
-            else {
-                // the root changes to the other child
-                match other {
-                    DBVTNode::Internal(ref mut i) => i.parent = ptr::null_mut(),
-                    DBVTNode::Leaf(ref l)         => (**l).borrow_mut().parent = DBVTLeafState::Detached,
-                    DBVTNode::Invalid             => unreachable!()
+        }
+    }
+
+    // Inserts the detached leaf `i` on the tree.
+    fn attach(&mut self, i: uint) {
+        let root = match self.root {
+            Some(root) => root,
+            None       => {
+                assert!(!self.leaf(i).attached, "DBVT internal error: the leaf is already attached.");
+
+                self.len = self.len + 1;
+
+                {
+                    let leaf = self.leaf_mut(i);
+                    leaf.attached = true;
+                    leaf.parent   = NONE;
                 }
 
-                Some(other)
+                self.root = Some(DBVTNodeId::Leaf(i));
+                return;
+            }
+        };
+
+        // Go down to the leaf closest to the new one, enlarging the bounding volumes on the way.
+        let mut curr = root;
+
+        loop {
+            match curr {
+                DBVTNodeId::Internal(ci) => {
+                    let leaf_bv = self.leaf(i).bounding_volume.clone();
+                    self.internal_mut(ci).bounding_volume.merge(&leaf_bv);
+
+                    let internal = self.internal(ci);
+                    let center   = &self.leaf(i).center;
+                    let dleft    = na::sqdist(self.node_center(internal.left), center);
+                    let dright   = na::sqdist(self.node_center(internal.right), center);
+
+                    curr = if dleft < dright { internal.left } else { internal.right };
+                },
+                DBVTNodeId::Leaf(_) => break
             }
         }
+
+        self.attach_with_sibling(i, curr);
+    }
+
+    // Inserts the detached leaf `i` on the non-empty tree, as the sibling of the node `sibling`.
+    fn attach_with_sibling(&mut self, i: uint, sibling: DBVTNodeId) {
+        assert!(!self.leaf(i).attached, "DBVT internal error: the leaf is already attached.");
+
+        self.len = self.len + 1;
+        self.leaf_mut(i).attached = true;
+
+        // Replace the sibling by a new internal node having both nodes as children.
+        let parent = match sibling {
+            DBVTNodeId::Internal(si) => self.internal(si).parent,
+            DBVTNodeId::Leaf(si)     => self.leaf(si).parent
+        };
+
+        let bv       = self.node_bounding_volume(sibling).merged(&self.leaf(i).bounding_volume);
+        let internal = DBVTInternal::new(bv, parent, sibling, DBVTNodeId::Leaf(i));
+        let new_id   = self.alloc_internal(internal);
+
+        self.set_parent(sibling, new_id);
+        self.leaf_mut(i).parent = new_id;
+
+        if parent == NONE {
+            self.root = Some(DBVTNodeId::Internal(new_id));
+        }
         else {
-            self.parent = DBVTLeafState::Detached;
+            self.replace_child(parent, sibling, DBVTNodeId::Internal(new_id));
+        }
 
-            // the tree becomes empty
-            None
+        self.refit_and_rotate_from(new_id);
+    }
+
+    // Removes the leaf `i` from the tree, without freeing it.
+    fn detach(&mut self, i: uint) {
+        let grand_parent = self.unlink(i);
+
+        self.refit_and_rotate_from(grand_parent);
+    }
+
+    // Removes the leaf `i` from the tree, without freeing it nor updating the bounding volumes.
+    //
+    // Returns the former grand-parent of the leaf, or `NONE`.
+    fn unlink(&mut self, i: uint) -> uint {
+        if !self.leaf(i).attached {
+            return NONE;
+        }
+
+        self.len = self.len - 1;
+
+        let parent = self.leaf(i).parent;
+
+        {
+            let leaf = self.leaf_mut(i);
+            leaf.attached = false;
+            leaf.parent   = NONE;
+        }
+
+        if parent == NONE {
+            // The leaf was the root.
+            self.root = None;
+            return NONE;
+        }
+
+        let (sibling, grand_parent) = {
+            let p = self.internal(parent);
+            let sibling = if p.left == DBVTNodeId::Leaf(i) { p.right } else { p.left };
+
+            (sibling, p.parent)
+        };
+
+        self.set_parent(sibling, grand_parent);
+        self.free_internal(parent);
+
+        if grand_parent == NONE {
+            self.root = Some(sibling);
         }
+        else {
+            self.replace_child(grand_parent, DBVTNodeId::Internal(parent), sibling);
+        }
+
+        grand_parent
     }
-}
 
-impl<N, P, V, BV, B> DBVTNode<P, B, BV>
-    where N: Scalar,
-          P:  Point<N, V>,
-          V:  FloatVec<N>,
-          BV: 'static + BoundingVolume<N>,
-          B: 'static {
-    fn sqdist_to(&self, to: &P) -> N {
-        match *self {
-            DBVTNode::Internal(ref i) => na::sqdist(&i.center, to),
-            DBVTNode::Leaf(ref l)     => {
-                let bl = l.borrow();
-                na::sqdist(&bl.center, to)
-            },
-            DBVTNode::Invalid => unreachable!()
+    // Finds the node which, as the sibling of the detached leaf `i`, increases the least the
+    // total surface area of the tree.
+    //
+    // This is a branch and bound search: inserting the leaf in the subtree of a node costs at
+    // least the surface area of the leaf, plus the enlargement of the node and its ancestors.
+    fn best_sibling(&self, i: uint) -> DBVTNodeId {
+        let leaf_bv   = &self.leaf(i).bounding_volume;
+        let leaf_area = leaf_bv.surface_area();
+
+        let root = match self.root {
+            Some(ref root) => root,
+            None           => panic!("DBVT internal error: cannot find a sibling on an empty tree.")
+        };
+
+        let mut best      = *root;
+        let mut best_cost = self.node_bounding_volume(*root).merged(leaf_bv).surface_area();
+
+        // The cost of a node is the opposite of the enlargement of its ancestors.
+        let mut queue = BinaryHeap::new();
+
+        queue.push(RefWithCost::new(root, na::zero::<N>()));
+
+        loop {
+            match queue.pop() {
+                Some(node) => {
+                    let inherited = -node.cost;
+
+                    if inherited + leaf_area >= best_cost {
+                        // No node left on the queue can do better.
+                        break;
+                    }
+
+                    let node_bv = self.node_bounding_volume(*node.object);
+                    let merged  = node_bv.merged(leaf_bv).surface_area();
+                    let cost    = inherited + merged;
+
+                    if cost < best_cost {
+                        best      = *node.object;
+                        best_cost = cost;
+                    }
+
+                    match *node.object {
+                        DBVTNodeId::Internal(ni) => {
+                            let inherited = inherited + merged - node_bv.surface_area();
+
+                            if inherited + leaf_area < best_cost {
+                                let internal = self.internal(ni);
+
+                                queue.push(RefWithCost::new(&internal.left, -inherited));
+                                queue.push(RefWithCost::new(&internal.right, -inherited));
+                            }
+                        },
+                        DBVTNodeId::Leaf(_) => { }
+                    }
+                },
+                None => break
+            }
         }
+
+        best
     }
-}
 
-impl<N, P, V, B, BV> DBVTInternal<P, B, BV>
-    where N: Scalar,
-          P:  Point<N, V>,
-          V:  FloatVec<N>,
-          BV: 'static + Translation<V> + BoundingVolume<N>,
-          B:  'static {
-    fn is_closest_to_left(&self, pt: &P) -> bool {
-        self.right.sqdist_to(pt) > self.left.sqdist_to(pt)
+    // Recomputes the bounding volumes of the internal node `i` and its ancestors.
+    fn refit_from(&mut self, i: uint) {
+        let mut curr = i;
+
+        while curr != NONE {
+            self.refit(curr);
+            curr = self.internal(curr).parent;
+        }
     }
-}
 
-impl<N, P, V, BV, B> DBVTNode<P, B, BV>
-    where N: Scalar,
-          P:  Point<N, V>,
-          V:  FloatVec<N>,
-          BV: 'static + Translation<V> + BoundingVolume<N>,
-          B:  'static {
-    /// Inserts a new leaf on this tree.
-    fn insert(self,
-              cache:     &mut Cache<P, B, BV>,
-              to_insert: Rc<RefCell<DBVTLeaf<P, B, BV>>>)
-              -> Box<DBVTInternal<P, B, BV>> {
-
-        let mut bto_insert = (*to_insert).borrow_mut();
-        let pto_insert     = bto_insert.deref_mut();
-
-        match self {
-            DBVTNode::Internal(i) => {
-                /*
-                 * NOTE: the insersion is done with unsafe pointers.
-                 * This is so because using &mut references dont seem to be possible since we have
-                 * to take successive references to nodes contents.
-                 */
-
-                let mut mut_internal = i;
-                let mut parent       = &mut *mut_internal as *mut DBVTInternal<P, B, BV>;
-
-                unsafe {
-                    (*parent).bounding_volume.merge(&pto_insert.bounding_volume);
-
-                    // iteratively go to the leaves
-                    let mut curr;
-                    let mut left;
-
-                    if (*parent).is_closest_to_left(&pto_insert.center) {
-                        curr = &mut (*parent).left as *mut DBVTNode<P, B, BV>;
-                        left = true;
+    // Recomputes the bounding volumes of the internal node `i` and its ancestors, and applies
+    // tree rotations where they reduce the surface area of the tree.
+    fn refit_and_rotate_from(&mut self, i: uint) {
+        let mut curr = i;
+
+        while curr != NONE {
+            self.refit(curr);
+            self.rotate(curr);
+            curr = self.internal(curr).parent;
+        }
+    }
+
+    // Recomputes the bounding volume of the internal node `i` from its children.
+    fn refit(&mut self, i: uint) {
+        let bv = {
+            let internal = self.internal(i);
+
+            self.node_bounding_volume(internal.left).merged(self.node_bounding_volume(internal.right))
+        };
+
+        let internal = self.internal_mut(i);
+
+        internal.center          = na::orig::<P>() + bv.translation();
+        internal.bounding_volume = bv;
+    }
+
+    // Swaps one child of the internal node `i` with a grand-child if this reduces the surface area
+    // of the internal child. The set of leaves of `i` is unchanged so its bounding volume does not
+    // have to be updated.
+    fn rotate(&mut self, i: uint) {
+        // (is the swapped child the left one, is the swapped grand-child the left one)
+        let mut best: Option<(bool, bool)> = None;
+        let mut best_gain = na::zero::<N>();
+
+        let (left, right) = {
+            let internal = self.internal(i);
+            (internal.left, internal.right)
+        };
+
+        for &is_left in [ true, false ].iter() {
+            let (child, other) = if is_left { (left, right) } else { (right, left) };
+
+            match other {
+                DBVTNodeId::Internal(o) => {
+                    let o        = self.internal(o);
+                    let child_bv = self.node_bounding_volume(child);
+                    let old_area = o.bounding_volume.surface_area();
+
+                    // Swapping `child` with `o.left` makes `o` contain `child` and `o.right`.
+                    let area_left  = child_bv.merged(self.node_bounding_volume(o.right)).surface_area();
+                    let area_right = child_bv.merged(self.node_bounding_volume(o.left)).surface_area();
+                    let gain_left  = old_area - area_left;
+                    let gain_right = old_area - area_right;
+
+                    if gain_left > best_gain {
+                        best_gain = gain_left;
+                        best      = Some((is_left, true));
+                    }
+
+                    if gain_right > best_gain {
+                        best_gain = gain_right;
+                        best      = Some((is_left, false));
+                    }
+                },
+                DBVTNodeId::Leaf(_) => { }
+            }
+        }
+
+        match best {
+            Some((is_left, with_left)) => {
+                let (child, other) = if is_left { (left, right) } else { (right, left) };
+                let o = match other {
+                    DBVTNodeId::Internal(o) => o,
+                    DBVTNodeId::Leaf(_)     => unreachable!()
+                };
+
+                let grand_child = {
+                    let internal = self.internal_mut(o);
+
+                    if with_left {
+                        let gc = internal.left;
+                        internal.left = child;
+                        gc
                     }
                     else {
-                        curr = &mut (*parent).right as *mut DBVTNode<P, B, BV>;
-                        left = false;
+                        let gc = internal.right;
+                        internal.right = child;
+                        gc
                     }
+                };
 
-                    loop {
-                        match *curr {
-                            DBVTNode::Internal(ref mut ci) => {
-                                // FIXME: we could avoid the systematic merge
-                                ci.bounding_volume.merge(&pto_insert.bounding_volume);
+                self.replace_child(i, child, grand_child);
+                self.set_parent(child, o);
+                self.set_parent(grand_child, i);
+                self.refit(o);
+            },
+            None => { }
+        }
+    }
+}
 
-                                if ci.is_closest_to_left(&pto_insert.center) { // FIXME
-                                    curr = &mut ci.left as *mut DBVTNode<P, B, BV>;
-                                    left = true;
-                                }
-                                else {
-                                    curr = &mut ci.right as *mut DBVTNode<P, B, BV>;
-                                    left = false;
-                                }
+impl<N, P, B, BV> DBVT<P, B, BV>
+    where N:  Scalar,
+          BV: LocalPointQuery<N, P> {
+    /// Finds the `k` objects closest to a given point.
+    ///
+    /// The bounding volumes are pruned using their distance to the point, and `dist_fn` computes
+    /// the exact distance between a leaf object and the point, typically with
+    /// `LocalPointQuery::distance_to_point`. The result is sorted by increasing distance.
+    pub fn k_nearest<'a>(&'a self, pt: &P, k: uint, dist_fn: &mut |&B, &P| -> N) -> Vec<(N, &'a B)> {
+        let mut res: BinaryHeap<RefWithCost<'a, N, B>> = BinaryHeap::with_capacity(k);
+
+        let root = match self.root {
+            Some(ref root) if k != 0 => root,
+            _                        => return Vec::new()
+        };
 
-                                parent = &mut **ci as *mut DBVTInternal<P, B, BV>;
-                            },
-                            DBVTNode::Leaf(ref l) => {
-                                let mut bl       = (**l).borrow_mut();
-                                let     pl       = bl.deref_mut();
-                                let mut internal = cache.alloc(DBVTInternal::new(
-                                    pl.bounding_volume.merged(&pto_insert.bounding_volume),
-                                    parent,
-                                    DBVTNode::Leaf(l.clone()),
-                                    DBVTNode::Leaf(to_insert.clone())));
-
-                                pl.parent = DBVTLeafState::LeftChildOf(&mut *internal as *mut DBVTInternal<P, B, BV>);
-                                pto_insert.parent =
-                                    DBVTLeafState::RightChildOf(&mut *internal as *mut DBVTInternal<P, B, BV>);
-
-                                if left {
-                                    (*parent).left = DBVTNode::Internal(internal)
-                                }
-                                else {
-                                    (*parent).right = DBVTNode::Internal(internal)
+        let mut queue: BinaryHeap<RefWithCost<'a, N, DBVTNodeId>> = BinaryHeap::new();
+
+        queue.push(RefWithCost::new(root, -self.node_bounding_volume(*root).distance_to_point(pt)));
+
+        loop {
+            match queue.pop() {
+                Some(node) => {
+                    match bvt::nearest_bound(&res, k) {
+                        Some(bound) if -node.cost >= bound => break, // no better object can be found.
+                        _                                  => { }
+                    }
+
+                    match *node.object {
+                        DBVTNodeId::Internal(i) => {
+                            let internal = self.internal(i);
+
+                            for child in [ &internal.left, &internal.right ].iter() {
+                                let dist = self.node_bounding_volume(**child).distance_to_point(pt);
+
+                                match bvt::nearest_bound(&res, k) {
+                                    Some(bound) if dist >= bound => { },
+                                    _                            => queue.push(RefWithCost::new(*child, -dist))
                                 }
+                            }
+                        },
+                        DBVTNodeId::Leaf(i) => {
+                            let leaf = self.leaf(i);
+                            let dist = (*dist_fn)(&leaf.object, pt);
 
-                                break;
-                            },
-                            DBVTNode::Invalid => unreachable!()
+                            bvt::push_nearest(&mut res, k, dist, &leaf.object)
                         }
                     }
-                }
-
-                mut_internal
-            },
-            DBVTNode::Leaf(l) => {
-                let     cl = l.clone();
-                let mut bl = (*cl).borrow_mut();
-                let     pl = bl.deref_mut();
-
-                // create the root
-                let mut root = cache.alloc(DBVTInternal::new(
-                    pl.bounding_volume.merged(&pto_insert.bounding_volume),
-                    ptr::null_mut(),
-                    DBVTNode::Leaf(l),
-                    DBVTNode::Leaf(to_insert.clone())));
-
-                pl.parent = DBVTLeafState::LeftChildOf(&mut *root as *mut DBVTInternal<P, B, BV>);
-                pto_insert.parent = DBVTLeafState::RightChildOf(&mut *root as *mut DBVTInternal<P, B, BV>);
-
-                root
-            },
-            DBVTNode::Invalid => unreachable!()
+                },
+                None => break
+            }
         }
+
+        res.into_sorted_vec().into_iter().map(|e| (e.cost, e.object)).collect()
     }
 
-    /// Finds all leaves which have their bounding boxes intersecting a specific leave's bounding
-    /// volume.
+    /// Finds all the objects at a distance smaller than or equal to `radius` from a given point.
     ///
-    /// # Arguments:
-    /// * `to_test` - the leaf to check interference with.
-    /// * `out` - will be filled with all leaves intersecting `to_test`. Note that `to_test`
-    /// is not considered intersecting itself.
-    fn interferences_with_leaf(&self,
-                               to_test: &DBVTLeaf<P, B, BV>,
-                               out:     &mut Vec<Rc<RefCell<DBVTLeaf<P, B, BV>>>>) {
-        let mut visitor = BoundingVolumeInterferencesCollector::new(&to_test.bounding_volume, out);
-        self.visit(&mut visitor)
+    /// The bounding volumes are pruned using their distance to the point, and `dist_fn` computes
+    /// the exact distance between a leaf object and the point. The result is sorted by increasing
+    /// distance.
+    pub fn within_radius<'a>(&'a self, pt: &P, radius: N, dist_fn: &mut |&B, &P| -> N)
+                             -> Vec<(N, &'a B)> {
+        let mut res   = Vec::new();
+        let mut stack = Vec::new();
+
+        match self.root {
+            Some(root) => stack.push(root),
+            None       => { }
+        }
+
+        loop {
+            match stack.pop() {
+                Some(node) => {
+                    if self.node_bounding_volume(node).distance_to_point(pt) > radius {
+                        continue;
+                    }
+
+                    match node {
+                        DBVTNodeId::Internal(i) => {
+                            let internal = self.internal(i);
+
+                            stack.push(internal.right);
+                            stack.push(internal.left);
+                        },
+                        DBVTNodeId::Leaf(i) => {
+                            let leaf = self.leaf(i);
+                            let dist = (*dist_fn)(&leaf.object, pt);
+
+                            if dist <= radius {
+                                res.push((dist, &leaf.object))
+                            }
+                        }
+                    }
+                },
+                None => break
+            }
+        }
+
+        res.sort_by(|a, b| a.ref0().partial_cmp(b.ref0()).unwrap_or(Equal));
+
+        res
     }
+}
 
-    fn visit<Vis: BVTVisitor<Rc<RefCell<DBVTLeaf<P, B, BV>>>, BV>>(&self, visitor: &mut Vis) {
-        match *self {
-            DBVTNode::Internal(ref i) => {
-                if visitor.visit_internal(&i.bounding_volume) {
-                    i.left.visit(visitor);
-                    i.right.visit(visitor);
-                }
-            },
-            DBVTNode::Leaf(ref l) => {
-                let bl = l.borrow();
-                visitor.visit_leaf(l, &bl.bounding_volume)
+#[cfg(test)]
+mod test {
+    use std::cmp;
+    use std::rand::{IsaacRng, Rng};
+    use na::{Pnt3, Vec3};
+    use bounding_volume::{AABB, BoundingVolume};
+    use point::LocalPointQuery;
+    use partitioning::dbvt::{DBVT, DBVTNodeId, LeafId, NONE};
+
+    type TestDBVT = DBVT<Pnt3<f64>, uint, AABB<Pnt3<f64>>>;
+
+    fn random_aabb(rng: &mut IsaacRng) -> AABB<Pnt3<f64>> {
+        let center = Pnt3::new(rng.gen::<f64>() * 10.0, rng.gen::<f64>() * 10.0, rng.gen::<f64>() * 10.0);
+        let half   = Vec3::new(rng.gen::<f64>(), rng.gen::<f64>(), rng.gen::<f64>());
+
+        AABB::new(center - half, center + half)
+    }
+
+    // Checks the structure of the subtree rooted at `node`, and returns its number of leaves.
+    fn check_node(tree: &TestDBVT, node: DBVTNodeId, parent: uint) -> uint {
+        match node {
+            DBVTNodeId::Internal(i) => {
+                let internal = tree.internal(i);
+
+                assert!(internal.parent == parent);
+                assert!(internal.bounding_volume.contains(tree.node_bounding_volume(internal.left)));
+                assert!(internal.bounding_volume.contains(tree.node_bounding_volume(internal.right)));
+
+                check_node(tree, internal.left, i) + check_node(tree, internal.right, i)
             },
-            DBVTNode::Invalid => unreachable!()
+            DBVTNodeId::Leaf(i) => {
+                let leaf = tree.leaf(i);
+
+                assert!(leaf.attached && leaf.parent == parent);
+
+                1
+            }
         }
     }
 
-    /// Finds all interferences between this tree and another one.
-    fn interferences_with_tree(&self,
-                               to_test: &DBVTNode<P, B, BV>,
-                               out:     &mut Vec<Rc<RefCell<DBVTLeaf<P, B, BV>>>>) {
-        match (self, to_test) {
-            (&DBVTNode::Leaf(_), &DBVTNode::Leaf(ref lb)) => {
-                let blb = lb.borrow();
-                self.interferences_with_leaf(blb.deref(), out)
-            },
-            (&DBVTNode::Leaf(ref la), &DBVTNode::Internal(_)) => {
-                let bla = la.borrow();
-                to_test.interferences_with_leaf(bla.deref(), out)
-            },
-            (&DBVTNode::Internal(_), &DBVTNode::Leaf(ref lb)) => {
-                let blb = lb.borrow();
-                self.interferences_with_leaf(blb.deref(), out)
-            },
-            (&DBVTNode::Internal(ref la), &DBVTNode::Internal(ref lb)) => {
-                // FIXME: la.partial_optimise();
-                // FIXME: lb.partial_optimise();
+    fn check_invariants(tree: &TestDBVT) {
+        let nleaves = match tree.root {
+            Some(root) => check_node(tree, root, NONE),
+            None       => 0
+        };
 
-                if (&**la as *const DBVTInternal<P, B, BV> != &**lb as *const DBVTInternal<P, B, BV>) &&
-                    la.bounding_volume.intersects(&lb.bounding_volume)
-                {
-                    la.right.interferences_with_tree(&lb.right, out);
-                    la.left.interferences_with_tree(&lb.left, out);
-                    la.right.interferences_with_tree(&lb.left, out);
-                    la.left.interferences_with_tree(&lb.right, out);
+        assert!(nleaves == tree.len());
+        assert!(tree.leaves.iter().filter(|l| l.is_some()).count() == tree.len());
+    }
+
+    // Compares the tree queries with a brute-force search on `objects`.
+    fn check_queries(tree: &TestDBVT, objects: &[(LeafId, uint, AABB<Pnt3<f64>>)], rng: &mut IsaacRng) {
+        for _ in range(0u, 20) {
+            let bv = random_aabb(rng);
+            let mut expected: Vec<uint> = objects.iter().filter(|o| o.ref2().intersects(&bv)).map(|o| *o.ref1()).collect();
+            let mut found = Vec::new();
+
+            tree.interferences_with_bounding_volume(&bv, &mut found);
+
+            expected.sort();
+            found.sort();
+            assert!(expected == found);
+        }
+
+        for &(id, object, ref bv) in objects.iter() {
+            let mut expected: Vec<uint> = objects.iter().filter(|o| *o.ref1() != object && o.ref2().intersects(bv))
+                                                        .map(|o| *o.ref1()).collect();
+            let mut found = Vec::new();
+
+            tree.interferences_with_leaf(id, &mut found);
+
+            expected.sort();
+            found.sort();
+            assert!(expected == found);
+        }
+
+        for _ in range(0u, 10) {
+            let pt = random_aabb(rng).center();
+            let mut expected: Vec<f64> = objects.iter().map(|o| o.ref2().distance_to_point(&pt)).collect();
+
+            expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+            // `k` larger than the number of leaves returns all the leaves.
+            for k in [ 0u, 1, 7, objects.len() + 10 ].iter() {
+                let found = tree.k_nearest(&pt, *k, &mut |b, pt| object_distance(objects, *b, pt));
+
+                assert!(found.len() == cmp::min(*k, objects.len()));
+
+                for (i, &(dist, b)) in found.iter().enumerate() {
+                    assert!(dist == expected[i] && dist == object_distance(objects, *b, &pt));
                 }
-            },
-            _ => unreachable!() // combinations with Invalid
+            }
+
+            let found = tree.within_radius(&pt, 3.0, &mut |b, pt| object_distance(objects, *b, pt));
+
+            assert!(found.len() == expected.iter().filter(|d| **d <= 3.0).count());
+
+            for (i, &(dist, b)) in found.iter().enumerate() {
+                assert!(dist == expected[i] && dist == object_distance(objects, *b, &pt));
+            }
         }
     }
-}
 
-// XXX: Drop should be implemented to invalidate the leaves parents when the tree is dropped.
+    fn object_distance(objects: &[(LeafId, uint, AABB<Pnt3<f64>>)], object: uint, pt: &Pnt3<f64>) -> f64 {
+        objects.iter().find(|o| *o.ref1() == object).unwrap().ref2().distance_to_point(pt)
+    }
+
+    #[test]
+    fn test_dbvt_insert_remove_update_optimize() {
+        let mut rng     = IsaacRng::new_unseeded();
+        let mut tree    = DBVT::new();
+        let mut objects = Vec::new();
+
+        for i in range(0u, 200) {
+            let bv = random_aabb(&mut rng);
+            let id = tree.insert_new(i, bv.clone());
+
+            objects.push((id, i, bv));
+        }
+
+        check_invariants(&tree);
+        check_queries(&tree, objects.as_slice(), &mut rng);
+
+        // Remove one object out of three.
+        let mut removed = Vec::new();
+        let mut kept    = Vec::new();
+
+        for (i, o) in objects.into_iter().enumerate() {
+            if i % 3 == 0 {
+                assert!(tree.remove(*o.ref0()).map(|l| l.object) == Some(*o.ref1()));
+                removed.push(*o.ref0());
+            }
+            else {
+                kept.push(o);
+            }
+        }
+
+        let mut objects = kept;
+
+        check_invariants(&tree);
+        check_queries(&tree, objects.as_slice(), &mut rng);
+
+        // Move the remaining objects.
+        for o in objects.iter_mut() {
+            let bv = random_aabb(&mut rng);
+
+            tree.update_bounding_volume(*o.ref0(), bv.clone());
+            *o.mut2() = bv;
+        }
+
+        check_invariants(&tree);
+        check_queries(&tree, objects.as_slice(), &mut rng);
+
+        // Reuse the removed slots: the old identifiers must remain invalid.
+        for i in range(200u, 250) {
+            let bv = random_aabb(&mut rng);
+            let id = tree.insert_new(i, bv.clone());
+
+            objects.push((id, i, bv));
+        }
+
+        for id in removed.iter() {
+            assert!(!tree.contains(*id));
+            assert!(tree.get(*id).is_none());
+            assert!(tree.remove(*id).is_none());
+        }
+
+        tree.optimize(10);
+
+        check_invariants(&tree);
+        check_queries(&tree, objects.as_slice(), &mut rng);
+    }
+
+    #[test]
+    fn test_dbvt_optimize_degenerate_insertion_order() {
+        let mut rng     = IsaacRng::new_unseeded();
+        let mut tree    = DBVT::new();
+        let mut objects = Vec::new();
+
+        // Unit cubes alternately inserted along the `x` and `y` axes. The coordinates are exact so
+        // that the surface areas are computed without rounding errors.
+        for i in range(0u, 64) {
+            let center = if i % 2 == 0 {
+                Pnt3::new(0.0, i as f64 - 32.0, 0.0)
+            }
+            else {
+                Pnt3::new(i as f64 - 32.0, 0.0, 0.0)
+            };
+
+            let bv = AABB::new(center + Vec3::new(-0.5, -0.5, -0.5), center + Vec3::new(0.5, 0.5, 0.5));
+            let id = tree.insert_new(i, bv.clone());
+
+            objects.push((id, i, bv));
+        }
+
+        check_invariants(&tree);
+
+        let mut area  = tree.total_surface_area();
+        let mut depth = tree.depth();
+
+        // A balanced tree would have a depth of 7.
+        assert!(depth > 10);
+
+        for _ in range(0u, 10) {
+            tree.optimize(1);
+            check_invariants(&tree);
+
+            let new_area = tree.total_surface_area();
+
+            assert!(new_area <= area);
+            area = new_area;
+        }
+
+        assert!(tree.depth() < depth);
+
+        check_queries(&tree, objects.as_slice(), &mut rng);
+    }
+
+    #[test]
+    fn test_dbvt_single_leaf_no_self_interference() {
+        let mut tree: TestDBVT = DBVT::new();
+        let id = tree.insert_new(0, AABB::new(Pnt3::new(0.0, 0.0, 0.0), Pnt3::new(1.0, 1.0, 1.0)));
+        let mut out = Vec::new();
+
+        tree.interferences_with_leaf(id, &mut out);
+
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_dbvt_empty_nearest() {
+        let tree: TestDBVT = DBVT::new();
+        let pt = Pnt3::new(0.0f64, 0.0, 0.0);
+
+        assert!(tree.k_nearest(&pt, 3, &mut |_, _| 0.0).is_empty());
+        assert!(tree.within_radius(&pt, 3.0, &mut |_, _| 0.0).is_empty());
+    }
+}
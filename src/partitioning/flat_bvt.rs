@@ -0,0 +1,600 @@
+//! A read-only Bounding Volume Tree with nodes stored on a contiguous buffer.
+
+use std::collections::BinaryHeap;
+use std::mem;
+use na::Bounded;
+use na;
+use ray::{Ray, RayPacket};
+use partitioning::{BVT, BVTNode, BinaryPartition, BVTVisitor, BVTTVisitor, BVTCostFn};
+use bounding_volume::{BoundingVolume, HasSurfaceArea, AABB};
+use utils::data::ref_with_cost::RefWithCost;
+use math::{Scalar, Point, Vect};
+
+
+/// A Bounding Volume Tree with nodes stored on a contiguous buffer.
+///
+/// The nodes are stored in depth-first order so the left child of an internal node always follows
+/// it directly. Unlike the `BVT`, traversals do not have to follow pointers to boxed nodes. It is
+/// the acceleration structure of the `Mesh`.
+#[deriving(Clone, Encodable, Decodable)]
+pub struct FlatBVT<B, BV> {
+    nodes: Vec<FlatBVTNode<B, BV>>
+}
+
+/// A node of a flattened bounding volume tree.
+#[deriving(Clone, Encodable, Decodable)]
+pub enum FlatBVTNode<B, BV> {
+    /// An internal node, with the indices of its left and right children.
+    Internal(BV, uint, uint),
+    /// A leaf.
+    Leaf(BV, B)
+}
+
+impl<B, BV> FlatBVT<B, BV> {
+    /// Builds a flattened bounding volume tree from a `BVT`.
+    pub fn new_from_bvt(bvt: BVT<B, BV>) -> FlatBVT<B, BV> {
+        let mut nodes = Vec::new();
+
+        match bvt.unwrap_root() {
+            Some(root) => flatten(root, &mut nodes),
+            None       => { }
+        }
+
+        FlatBVT {
+            nodes: nodes
+        }
+    }
+
+    /// Builds a flattened bounding volume tree using an user-defined construction function.
+    pub fn new_with_partitioner(leaves:      Vec<(B, BV)>,
+                                partitioner: |uint, Vec<(B, BV)>| -> (BV, BinaryPartition<B, BV>))
+                                -> FlatBVT<B, BV> {
+        FlatBVT::new_from_bvt(BVT::new_with_partitioner(leaves, partitioner))
+    }
+
+    /// The nodes of this tree, in depth-first order.
+    #[inline]
+    pub fn nodes(&self) -> &[FlatBVTNode<B, BV>] {
+        self.nodes.as_slice()
+    }
+
+    /// Visit this tree using… a visitor!
+    pub fn visit<Vis: BVTVisitor<B, BV>>(&self, visitor: &mut Vis) {
+        if self.nodes.is_empty() {
+            return;
+        }
+
+        let mut stack = vec!(0u);
+
+        loop {
+            match stack.pop() {
+                Some(i) => {
+                    match self.nodes[i] {
+                        FlatBVTNode::Internal(ref bv, left, right) => {
+                            if visitor.visit_internal(bv) {
+                                stack.push(right);
+                                stack.push(left);
+                            }
+                        },
+                        FlatBVTNode::Leaf(ref bv, ref b) => visitor.visit_leaf(b, bv)
+                    }
+                },
+                None => break
+            }
+        }
+    }
+
+    /// Visits the bounding volume traversal tree implicitely formed with `other`.
+    pub fn visit_bvtt<Vis: BVTTVisitor<B, BV>>(&self, other: &FlatBVT<B, BV>, visitor: &mut Vis) {
+        if self.nodes.is_empty() || other.nodes.is_empty() {
+            return;
+        }
+
+        self.visit_bvtt_from(0, other, 0, visitor)
+    }
+
+    /// Visits the bounding volume traversal tree implicitely formed with this tree itself.
+    ///
+    /// Each pair of distinct leaves is visited at most once: a leaf is never paired with itself,
+    /// and the mirror `(b, a)` of a pair `(a, b)` is never visited.
+    pub fn visit_self_bvtt<Vis: BVTTVisitor<B, BV>>(&self, visitor: &mut Vis) {
+        for node in self.nodes.iter() {
+            match *node {
+                FlatBVTNode::Internal(_, left, right) => self.visit_bvtt_from(left, self, right, visitor),
+                FlatBVTNode::Leaf(_, _)               => { }
+            }
+        }
+    }
+
+    // Visits the bounding volume traversal tree formed by the subtree rooted at the node `ia` of
+    // `self` and the subtree rooted at the node `ib` of `other`.
+    fn visit_bvtt_from<Vis: BVTTVisitor<B, BV>>(&self, ia: uint, other: &FlatBVT<B, BV>, ib: uint, visitor: &mut Vis) {
+        let mut stack = vec!((ia, ib));
+
+        loop {
+            match stack.pop() {
+                Some((ia, ib)) => {
+                    match (&self.nodes[ia], &other.nodes[ib]) {
+                        (&FlatBVTNode::Internal(ref bva, la, ra), &FlatBVTNode::Internal(ref bvb, lb, rb)) => {
+                            if visitor.visit_internal_internal(bva, bvb) {
+                                stack.push((ra, rb));
+                                stack.push((ra, lb));
+                                stack.push((la, rb));
+                                stack.push((la, lb));
+                            }
+                        },
+                        (&FlatBVTNode::Internal(ref bva, la, ra), &FlatBVTNode::Leaf(ref bvb, ref bb)) => {
+                            if visitor.visit_internal_leaf(bva, bb, bvb) {
+                                stack.push((ra, ib));
+                                stack.push((la, ib));
+                            }
+                        },
+                        (&FlatBVTNode::Leaf(ref bva, ref ba), &FlatBVTNode::Internal(ref bvb, lb, rb)) => {
+                            if visitor.visit_leaf_internal(ba, bva, bvb) {
+                                stack.push((ia, rb));
+                                stack.push((ia, lb));
+                            }
+                        },
+                        (&FlatBVTNode::Leaf(ref bva, ref ba), &FlatBVTNode::Leaf(ref bvb, ref bb)) => {
+                            visitor.visit_leaf_leaf(ba, bva, bb, bvb)
+                        }
+                    }
+                },
+                None => break
+            }
+        }
+    }
+
+    /// Performs a best-fist-search on the tree.
+    ///
+    /// Returns the content of the best leaf nound, and a result of user-defined type.
+    pub fn best_first_search<'a, N, BFS, R>(&'a self, algorithm: &mut BFS) -> Option<(&'a B, R)>
+        where N:   Scalar,
+              BFS: BVTCostFn<N, B, BV, R> {
+        self.best_first_search_with_max_cost(algorithm, Bounded::max_value())
+    }
+
+    /// Performs a best-fist-search on the tree, ignoring everything with a cost greater than or
+    /// equal to `max_cost`.
+    pub fn best_first_search_with_max_cost<'a, N, BFS, R>(&'a self, algorithm: &mut BFS, max_cost: N)
+                                                          -> Option<(&'a B, R)>
+        where N:   Scalar,
+              BFS: BVTCostFn<N, B, BV, R> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        let mut queue: BinaryHeap<RefWithCost<'a, N, FlatBVTNode<B, BV>>> = BinaryHeap::new();
+        let mut best_cost = max_cost;
+        let mut result    = None;
+
+        match algorithm.compute_bv_cost(self.nodes[0].bounding_volume()) {
+            Some(cost) if cost < best_cost => queue.push(RefWithCost::new(&self.nodes[0], -cost)),
+            _                              => return None
+        }
+
+        loop {
+            match queue.pop() {
+                Some(node) => {
+                    if -node.cost >= best_cost {
+                        break; // solution found.
+                    }
+
+                    match *node.object {
+                        FlatBVTNode::Internal(_, left, right) => {
+                            for child in [ left, right ].iter() {
+                                let child = &self.nodes[*child];
+
+                                match algorithm.compute_bv_cost(child.bounding_volume()) {
+                                    Some(cost) => {
+                                        if cost < best_cost {
+                                            queue.push(RefWithCost::new(child, -cost))
+                                        }
+                                    },
+                                    None => { }
+                                }
+                            }
+                        },
+                        FlatBVTNode::Leaf(_, ref b) => {
+                            match algorithm.compute_b_cost(b) {
+                                Some((candidate_cost, candidate_result)) => {
+                                    if candidate_cost < best_cost {
+                                        best_cost = candidate_cost;
+                                        result    = Some((b, candidate_result));
+                                    }
+                                }
+                                None => { }
+                            }
+                        }
+                    }
+                }
+                None => break,
+            }
+        }
+
+        result
+    }
+
+    /// Reference to the bounding volume of the tree root.
+    pub fn root_bounding_volume<'r>(&'r self) -> Option<&'r BV> {
+        if self.nodes.is_empty() {
+            None
+        }
+        else {
+            Some(self.nodes[0].bounding_volume())
+        }
+    }
+
+    /// Computes the depth of this tree.
+    pub fn depth(&self) -> uint {
+        if self.nodes.is_empty() {
+            0
+        }
+        else {
+            self.node_depth(0)
+        }
+    }
+
+    fn node_depth(&self, i: uint) -> uint {
+        match self.nodes[i] {
+            FlatBVTNode::Internal(_, left, right) => {
+                1 + na::max(self.node_depth(left), self.node_depth(right))
+            },
+            FlatBVTNode::Leaf(_, _) => 1
+        }
+    }
+}
+
+impl<N, B, BV> FlatBVT<B, BV>
+    where N:  Scalar,
+          BV: BoundingVolume<N> + Clone {
+    /// Updates the bounding volumes of this tree without modifying its structure.
+    ///
+    /// The bounding volume of each leaf is replaced by `leaf_bv(leaf_content)`. Because the
+    /// children of a node are always stored after it, the internal nodes are then recomputed in
+    /// place with a single backward pass over the nodes.
+    pub fn refit(&mut self, leaf_bv: &mut |&B| -> BV) {
+        for i in range(0, self.nodes.len()).rev() {
+            let new_bv = match self.nodes[i] {
+                FlatBVTNode::Internal(_, left, right) => {
+                    self.nodes[left].bounding_volume().merged(self.nodes[right].bounding_volume())
+                },
+                FlatBVTNode::Leaf(_, ref b) => (*leaf_bv)(b)
+            };
+
+            match self.nodes[i] {
+                FlatBVTNode::Internal(ref mut bv, _, _) => *bv = new_bv,
+                FlatBVTNode::Leaf(ref mut bv, _)        => *bv = new_bv
+            }
+        }
+    }
+}
+
+impl<N, B, BV> FlatBVT<B, BV>
+    where N:  Scalar,
+          BV: HasSurfaceArea<N> {
+    /// The sum of the surface areas of the internal nodes, divided by the root surface area.
+    ///
+    /// This is the same as `BVT::surface_area_cost`.
+    pub fn surface_area_cost(&self) -> N {
+        if self.nodes.is_empty() {
+            return na::zero();
+        }
+
+        let root_area = self.nodes[0].bounding_volume().surface_area();
+
+        if na::is_zero(&root_area) {
+            return na::zero();
+        }
+
+        let mut internal_area = na::zero::<N>();
+
+        for node in self.nodes.iter() {
+            match *node {
+                FlatBVTNode::Internal(ref bv, _, _) => internal_area = internal_area + bv.surface_area(),
+                FlatBVTNode::Leaf(_, _)             => { }
+            }
+        }
+
+        internal_area / root_area
+    }
+}
+
+impl<N, P, V, B> FlatBVT<B, AABB<P>>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> {
+    /// Computes the closest intersection between the objects stored on this tree and each ray of
+    /// a packet.
+    ///
+    /// This is the same traversal as `BVT::cast_ray_packet`.
+    pub fn cast_ray_packet<'a, T>(&'a self,
+                                  packet:  &RayPacket<P, V>,
+                                  cast_fn: &mut |&B, &Ray<P, V>| -> Option<(N, T)>)
+                                  -> Vec<Option<(N, T, &'a B)>> {
+        let nrays         = packet.len();
+        let mut res       = Vec::from_fn(nrays, |_| None);
+        let mut best_tois = Vec::from_elem(nrays, Bounded::max_value());
+
+        if self.nodes.is_empty() {
+            return res;
+        }
+
+        let mut dir: V = na::zero();
+
+        for ray in packet.rays().iter() {
+            dir = dir + ray.dir;
+        }
+
+        // `masks[i]` contains the active rays of the i-th node of the stack.
+        let mut masks   = vec!(Vec::from_elem(nrays, false));
+        let mut current = Vec::from_elem(nrays, true);
+        let mut stack   = Vec::new();
+        // Incremented each time a ray finds a closer intersection.
+        let mut nhits   = 0u;
+
+        if packet.intersects_aabb(self.nodes[0].bounding_volume(), current.as_slice(), best_tois.as_slice(),
+                                  masks[0].as_mut_slice()) {
+            stack.push((0u, nhits));
+        }
+
+        loop {
+            match stack.pop() {
+                Some((id, nhits_at_push)) => {
+                    let depth = stack.len();
+                    let node  = &self.nodes[id];
+
+                    mem::swap(&mut current, &mut masks[depth]);
+
+                    if nhits_at_push != nhits {
+                        // Some rays found closer intersections since this node was pushed.
+                        if !packet.intersects_aabb(node.bounding_volume(), current.as_slice(), best_tois.as_slice(),
+                                                   masks[depth].as_mut_slice()) {
+                            continue;
+                        }
+
+                        mem::swap(&mut current, &mut masks[depth]);
+                    }
+
+                    match *node {
+                        FlatBVTNode::Internal(_, left, right) => {
+                            let delta = self.nodes[left].bounding_volume().center() -
+                                        self.nodes[right].bounding_volume().center();
+
+                            // Push the farthest child first so that the nearest one is popped first.
+                            let children = if na::dot(&delta, &dir) <= na::zero() {
+                                [ right, left ]
+                            }
+                            else {
+                                [ left, right ]
+                            };
+
+                            for child in children.iter() {
+                                let slot = stack.len();
+
+                                if masks.len() == slot {
+                                    masks.push(Vec::from_elem(nrays, false));
+                                }
+
+                                if packet.intersects_aabb(self.nodes[*child].bounding_volume(), current.as_slice(),
+                                                          best_tois.as_slice(), masks[slot].as_mut_slice()) {
+                                    stack.push((*child, nhits));
+                                }
+                            }
+                        },
+                        FlatBVTNode::Leaf(_, ref b) => {
+                            for (i, ray) in packet.rays().iter().enumerate() {
+                                if !current[i] {
+                                    continue;
+                                }
+
+                                match (*cast_fn)(b, ray) {
+                                    Some((toi, data)) => {
+                                        if toi < best_tois[i] {
+                                            best_tois[i] = toi;
+                                            res[i]       = Some((toi, data, b));
+                                            nhits        = nhits + 1;
+                                        }
+                                    },
+                                    None => { }
+                                }
+                            }
+                        }
+                    }
+                },
+                None => break
+            }
+        }
+
+        res
+    }
+}
+
+impl<B, BV> FlatBVTNode<B, BV> {
+    /// The bounding volume of this node.
+    #[inline]
+    pub fn bounding_volume<'a>(&'a self) -> &'a BV {
+        match *self {
+            FlatBVTNode::Internal(ref bv, _, _) => bv,
+            FlatBVTNode::Leaf(ref bv, _)        => bv
+        }
+    }
+}
+
+// Pushes the nodes of the given tree in depth-first order.
+fn flatten<B, BV>(root: BVTNode<B, BV>, nodes: &mut Vec<FlatBVTNode<B, BV>>) {
+    // The nodes to be pushed, along with the index of the parent they are the right child of.
+    let mut stack = vec!((root, None));
+
+    loop {
+        match stack.pop() {
+            Some((node, right_child_of)) => {
+                let id = nodes.len();
+
+                match right_child_of {
+                    Some(parent) => {
+                        match nodes[parent] {
+                            FlatBVTNode::Internal(_, _, ref mut right) => *right = id,
+                            FlatBVTNode::Leaf(_, _)                    => unreachable!()
+                        }
+                    },
+                    None => { }
+                }
+
+                match node {
+                    BVTNode::Leaf(bv, b) => nodes.push(FlatBVTNode::Leaf(bv, b)),
+                    BVTNode::Internal(bv, left, right) => {
+                        // The left child is popped next, so its index is `id + 1`.
+                        nodes.push(FlatBVTNode::Internal(bv, id + 1, 0));
+                        stack.push((*right, Some(id)));
+                        stack.push((*left, None));
+                    }
+                }
+            },
+            None => break
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::rand::{IsaacRng, Rng};
+    use na::{Pnt3, Vec3};
+    use bounding_volume::{AABB, BoundingVolume};
+    use point::LocalPointQuery;
+    use partitioning::{BVT, FlatBVT, BVTTVisitor, BVTCostFn, BoundingVolumeInterferencesCollector};
+
+    fn random_leaves(rng: &mut IsaacRng, n: uint) -> Vec<(uint, AABB<Pnt3<f64>>)> {
+        range(0u, n).map(|i| {
+            let center = Pnt3::new(rng.gen::<f64>() * 10.0, rng.gen::<f64>() * 10.0, rng.gen::<f64>() * 10.0);
+            let half   = Vec3::new(rng.gen::<f64>(), rng.gen::<f64>(), rng.gen::<f64>());
+
+            (i, AABB::new(center - half, center + half))
+        }).collect()
+    }
+
+    // Finds the leaf with the AABB closest to a point.
+    struct ClosestLeafCostFn<'a> {
+        leaves: &'a [(uint, AABB<Pnt3<f64>>)],
+        point:  Pnt3<f64>
+    }
+
+    impl<'a> BVTCostFn<f64, uint, AABB<Pnt3<f64>>, f64> for ClosestLeafCostFn<'a> {
+        fn compute_bv_cost(&mut self, bv: &AABB<Pnt3<f64>>) -> Option<f64> {
+            Some(bv.distance_to_point(&self.point))
+        }
+
+        fn compute_b_cost(&mut self, b: &uint) -> Option<(f64, f64)> {
+            let dist = self.leaves[*b].ref1().distance_to_point(&self.point);
+
+            Some((dist, dist))
+        }
+    }
+
+    // Collects the pairs of intersecting leaves of two trees.
+    struct PairsCollector {
+        pairs: Vec<(uint, uint)>
+    }
+
+    impl BVTTVisitor<uint, AABB<Pnt3<f64>>> for PairsCollector {
+        fn visit_internal_internal(&mut self, a: &AABB<Pnt3<f64>>, b: &AABB<Pnt3<f64>>) -> bool {
+            a.intersects(b)
+        }
+
+        fn visit_internal_leaf(&mut self, a: &AABB<Pnt3<f64>>, _: &uint, b: &AABB<Pnt3<f64>>) -> bool {
+            a.intersects(b)
+        }
+
+        fn visit_leaf_internal(&mut self, _: &uint, a: &AABB<Pnt3<f64>>, b: &AABB<Pnt3<f64>>) -> bool {
+            a.intersects(b)
+        }
+
+        fn visit_leaf_leaf(&mut self, a: &uint, bva: &AABB<Pnt3<f64>>, b: &uint, bvb: &AABB<Pnt3<f64>>) {
+            if bva.intersects(bvb) {
+                self.pairs.push((*a, *b))
+            }
+        }
+    }
+
+    #[test]
+    fn test_flat_bvt_matches_bvt() {
+        let mut rng = IsaacRng::new_unseeded();
+        let leaves1 = random_leaves(&mut rng, 300);
+        let leaves2 = random_leaves(&mut rng, 100);
+        let bvt1    = BVT::new_balanced(leaves1.clone());
+        let bvt2    = BVT::new_balanced(leaves2.clone());
+        let flat1   = FlatBVT::new_from_bvt(bvt1.clone());
+        let flat2   = FlatBVT::new_from_bvt(bvt2.clone());
+
+        assert!(flat1.nodes().len() == 2 * leaves1.len() - 1);
+        assert!(flat1.depth() == bvt1.depth());
+
+        for _ in range(0u, 20) {
+            let bv = random_leaves(&mut rng, 1).pop().unwrap().val1();
+            let mut expected = Vec::new();
+            let mut found    = Vec::new();
+
+            bvt1.visit(&mut BoundingVolumeInterferencesCollector::new(&bv, &mut expected));
+            flat1.visit(&mut BoundingVolumeInterferencesCollector::new(&bv, &mut found));
+
+            expected.sort();
+            found.sort();
+            assert!(expected == found);
+
+            let point = bv.center();
+            let mut cost_fn = ClosestLeafCostFn { leaves: leaves1.as_slice(), point: point.clone() };
+            // Several leaves may be at the same distance, so only the distances are compared.
+            let expected    = bvt1.best_first_search(&mut cost_fn).map(|(_, d)| d);
+            let found       = flat1.best_first_search(&mut cost_fn).map(|(_, d)| d);
+
+            assert!(expected.is_some() && expected == found);
+        }
+
+        let mut expected = PairsCollector { pairs: Vec::new() };
+        let mut found    = PairsCollector { pairs: Vec::new() };
+
+        bvt1.visit_bvtt(&bvt2, &mut expected);
+        flat1.visit_bvtt(&flat2, &mut found);
+
+        expected.pairs.sort();
+        found.pairs.sort();
+        assert!(!expected.pairs.is_empty() && expected.pairs == found.pairs);
+
+        let empty: FlatBVT<uint, AABB<Pnt3<f64>>> = FlatBVT::new_from_bvt(BVT::new_balanced(Vec::new()));
+
+        assert!(empty.nodes().is_empty() && empty.depth() == 0);
+    }
+
+    #[test]
+    fn test_flat_bvt_refit_matches_bvt() {
+        let mut rng  = IsaacRng::new_unseeded();
+        let leaves   = random_leaves(&mut rng, 200);
+        let mut bvt  = BVT::new_balanced(leaves.clone());
+        let mut flat = FlatBVT::new_from_bvt(bvt.clone());
+
+        // Move every leaf so that the tree has to be refitted.
+        let moved = random_leaves(&mut rng, 200);
+
+        bvt.refit(&mut |i| moved[*i].ref1().clone());
+        flat.refit(&mut |i| moved[*i].ref1().clone());
+
+        let expected = FlatBVT::new_from_bvt(bvt.clone());
+
+        for (a, b) in flat.nodes().iter().zip(expected.nodes().iter()) {
+            assert!(a.bounding_volume() == b.bounding_volume());
+        }
+
+        assert!(flat.surface_area_cost() == bvt.surface_area_cost());
+
+        let mut expected = PairsCollector { pairs: Vec::new() };
+        let mut found    = PairsCollector { pairs: Vec::new() };
+
+        bvt.visit_self_bvtt(&mut expected);
+        flat.visit_self_bvtt(&mut found);
+
+        expected.pairs.sort();
+        found.pairs.sort();
+        assert!(!expected.pairs.is_empty() && expected.pairs == found.pairs);
+        assert!(found.pairs.iter().all(|&(a, b)| a != b));
+    }
+}
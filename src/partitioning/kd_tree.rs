@@ -0,0 +1,291 @@
+//! A k-d tree for nearest-neighbor queries on point clouds.
+
+use std::num::Float;
+use std::collections::BinaryHeap;
+use na;
+use bounding_volume::AABB;
+use partitioning::bvt;
+use utils::data::ref_with_cost::RefWithCost;
+use math::{Scalar, Point, Vect};
+
+
+/// A balanced k-d tree on a point cloud.
+///
+/// The tree is stored implicitly: each node is the median point of a range of a permutation of
+/// the points, and its children are the two halves of this range. All the queries return indices
+/// into the point cloud given at construction.
+#[deriving(Clone, Encodable, Decodable)]
+pub struct KdTree<P> {
+    points:  Vec<P>,
+    indices: Vec<uint>,
+    axes:    Vec<uint>
+}
+
+impl<N, P, V> KdTree<P>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> {
+    /// Builds a k-d tree from a point cloud.
+    ///
+    /// Each node splits its points along the axis with the largest spread.
+    pub fn new(points: &[P]) -> KdTree<P> {
+        let mut res = KdTree {
+            points:  points.to_vec(),
+            indices: range(0, points.len()).collect(),
+            axes:    Vec::from_elem(points.len(), 0u)
+        };
+
+        res.build(0, points.len());
+
+        res
+    }
+
+    /// The points of this tree.
+    #[inline]
+    pub fn points(&self) -> &[P] {
+        self.points.as_slice()
+    }
+
+    /// The number of points of this tree.
+    #[inline]
+    pub fn len(&self) -> uint {
+        self.points.len()
+    }
+
+    fn build(&mut self, begin: uint, end: uint) {
+        if end - begin <= 1 {
+            return;
+        }
+
+        // Find the axis with the largest spread.
+        let mut mins = self.points[self.indices[begin]].clone();
+        let mut maxs = mins.clone();
+
+        for i in self.indices.slice(begin, end).iter() {
+            mins = na::inf(&mins, &self.points[*i]);
+            maxs = na::sup(&maxs, &self.points[*i]);
+        }
+
+        let extents  = maxs - mins;
+        let mut axis = 0;
+
+        for i in range(1, na::dim::<P>()) {
+            if extents[i] > extents[axis] {
+                axis = i;
+            }
+        }
+
+        {
+            let points = &self.points;
+
+            self.indices.slice_mut(begin, end).sort_by(|a, b| {
+                points[*a][axis].partial_cmp(&points[*b][axis]).unwrap_or(Equal)
+            });
+        }
+
+        let mid = (begin + end) / 2;
+
+        self.axes[mid] = axis;
+        self.build(begin, mid);
+        self.build(mid + 1, end);
+    }
+
+    /// Finds the point closest to `pt`.
+    ///
+    /// Returns the distance to this point and its index, or `None` if the tree is empty.
+    pub fn nearest(&self, pt: &P) -> Option<(N, uint)> {
+        self.k_nearest(pt, 1).pop()
+    }
+
+    /// Finds the `k` points closest to `pt`.
+    ///
+    /// Returns their distances to `pt` and their indices, sorted by increasing distance.
+    pub fn k_nearest(&self, pt: &P, k: uint) -> Vec<(N, uint)> {
+        let mut res = BinaryHeap::with_capacity(k);
+
+        if k != 0 {
+            self.k_nearest_in(0, self.points.len(), pt, k, &mut res);
+        }
+
+        res.into_sorted_vec().into_iter().map(|e| (e.cost.sqrt(), *e.object)).collect()
+    }
+
+    // NOTE: `res` contains squared distances.
+    fn k_nearest_in<'a>(&'a self, begin: uint, end: uint, pt: &P, k: uint,
+                        res: &mut BinaryHeap<RefWithCost<'a, N, uint>>) {
+        if begin >= end {
+            return;
+        }
+
+        let mid  = (begin + end) / 2;
+        let id   = &self.indices[mid];
+        let axis = self.axes[mid];
+
+        bvt::push_nearest(res, k, na::sqdist(pt, &self.points[*id]), id);
+
+        let diff = pt[axis] - self.points[*id][axis];
+
+        let ((b1, e1), (b2, e2)) = if diff < na::zero() {
+            ((begin, mid), (mid + 1, end))
+        }
+        else {
+            ((mid + 1, end), (begin, mid))
+        };
+
+        self.k_nearest_in(b1, e1, pt, k, res);
+
+        match bvt::nearest_bound(res, k) {
+            Some(bound) if diff * diff >= bound => { },
+            _                                   => self.k_nearest_in(b2, e2, pt, k, res)
+        }
+    }
+
+    /// Finds all the points at a distance smaller than or equal to `radius` from `pt`.
+    ///
+    /// Returns their distances to `pt` and their indices, sorted by increasing distance.
+    pub fn within_radius(&self, pt: &P, radius: N) -> Vec<(N, uint)> {
+        let mut res = Vec::new();
+
+        self.within_radius_in(0, self.points.len(), pt, radius * radius, &mut res);
+
+        res.sort_by(|a, b| a.ref0().partial_cmp(b.ref0()).unwrap_or(Equal));
+
+        res.into_iter().map(|(sqdist, i)| (sqdist.sqrt(), i)).collect()
+    }
+
+    fn within_radius_in(&self, begin: uint, end: uint, pt: &P, sqradius: N, res: &mut Vec<(N, uint)>) {
+        if begin >= end {
+            return;
+        }
+
+        let mid    = (begin + end) / 2;
+        let id     = self.indices[mid];
+        let axis   = self.axes[mid];
+        let sqdist = na::sqdist(pt, &self.points[id]);
+
+        if sqdist <= sqradius {
+            res.push((sqdist, id));
+        }
+
+        let diff = pt[axis] - self.points[id][axis];
+
+        if diff <= na::zero() || diff * diff <= sqradius {
+            self.within_radius_in(begin, mid, pt, sqradius, res);
+        }
+
+        if diff >= na::zero() || diff * diff <= sqradius {
+            self.within_radius_in(mid + 1, end, pt, sqradius, res);
+        }
+    }
+
+    /// Finds the indices of all the points contained by an axis-aligned bounding box.
+    pub fn points_in_aabb(&self, aabb: &AABB<P>) -> Vec<uint> {
+        let mut res = Vec::new();
+
+        self.points_in_aabb_in(0, self.points.len(), aabb, &mut res);
+
+        res
+    }
+
+    fn points_in_aabb_in(&self, begin: uint, end: uint, aabb: &AABB<P>, res: &mut Vec<uint>) {
+        if begin >= end {
+            return;
+        }
+
+        let mid   = (begin + end) / 2;
+        let id    = self.indices[mid];
+        let axis  = self.axes[mid];
+        let pt    = &self.points[id];
+        let split = pt[axis];
+
+        if na::partial_le(aabb.mins(), pt) && na::partial_le(pt, aabb.maxs()) {
+            res.push(id);
+        }
+
+        if aabb.mins()[axis] <= split {
+            self.points_in_aabb_in(begin, mid, aabb, res);
+        }
+
+        if aabb.maxs()[axis] >= split {
+            self.points_in_aabb_in(mid + 1, end, aabb, res);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cmp;
+    use std::num::Float;
+    use std::rand::{IsaacRng, Rng};
+    use na::{Pnt3, Vec3};
+    use na;
+    use bounding_volume::AABB;
+    use partitioning::KdTree;
+
+    fn random_point(rng: &mut IsaacRng) -> Pnt3<f64> {
+        Pnt3::new(rng.gen::<f64>() * 10.0, rng.gen::<f64>() * 10.0, rng.gen::<f64>() * 10.0)
+    }
+
+    fn check_result(tree: &KdTree<Pnt3<f64>>, pt: &Pnt3<f64>, found: &[(f64, uint)], expected: &[f64]) {
+        for (i, &(dist, id)) in found.iter().enumerate() {
+            assert!(dist == expected[i]);
+            assert!(dist == na::sqdist(pt, &tree.points()[id]).sqrt());
+        }
+    }
+
+    #[test]
+    fn test_kd_tree_queries() {
+        let mut rng    = IsaacRng::new_unseeded();
+        let mut points = Vec::from_fn(300, |_| random_point(&mut rng));
+
+        // Add duplicate points, and points sharing a coordinate.
+        for i in range(0u, 50) {
+            let dup = points[i];
+            points.push(dup);
+            points.push(Pnt3::new(dup.x, rng.gen::<f64>() * 10.0, dup.z));
+        }
+
+        let tree = KdTree::new(points.as_slice());
+
+        for _ in range(0u, 50) {
+            // Also query exactly at some of the points.
+            let pt = if rng.gen::<bool>() { random_point(&mut rng) } else { points[rng.gen_range(0u, points.len())] };
+            let mut expected: Vec<f64> = points.iter().map(|p| na::sqdist(&pt, p).sqrt()).collect();
+
+            expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+            let (dist, id) = tree.nearest(&pt).unwrap();
+            check_result(&tree, &pt, [ (dist, id) ].as_slice(), expected.as_slice());
+
+            for k in [ 0u, 1, 2, 15, points.len() + 5 ].iter() {
+                let found = tree.k_nearest(&pt, *k);
+
+                assert!(found.len() == cmp::min(*k, points.len()));
+                check_result(&tree, &pt, found.as_slice(), expected.as_slice());
+            }
+
+            let found = tree.within_radius(&pt, 2.0);
+
+            assert!(found.len() == expected.iter().filter(|d| **d <= 2.0).count());
+            check_result(&tree, &pt, found.as_slice(), expected.as_slice());
+
+            let half      = Vec3::new(rng.gen::<f64>() * 3.0, rng.gen::<f64>() * 3.0, rng.gen::<f64>() * 3.0);
+            let aabb      = AABB::new(pt - half, pt + half);
+            let mut found = tree.points_in_aabb(&aabb);
+            let mut expected: Vec<uint> = range(0u, points.len()).filter(|i| {
+                na::partial_le(aabb.mins(), &points[*i]) && na::partial_le(&points[*i], aabb.maxs())
+            }).collect();
+
+            found.sort();
+            expected.sort();
+            assert!(found == expected);
+        }
+
+        let empty: KdTree<Pnt3<f64>> = KdTree::new([].as_slice());
+        let origin = Pnt3::new(0.0f64, 0.0, 0.0);
+
+        assert!(empty.nearest(&origin).is_none());
+        assert!(empty.k_nearest(&origin, 2).is_empty());
+        assert!(empty.within_radius(&origin, 1.0).is_empty());
+    }
+}
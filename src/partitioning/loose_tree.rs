@@ -0,0 +1,658 @@
+//! A loose octree (in 3D) or quadtree (in 2D) with fixed bounds.
+
+use std::uint;
+use na;
+use bounding_volume::{BoundingVolume, AABB};
+use ray::Ray;
+use partitioning::bvt_visitor::{BVTVisitor, BoundingVolumeInterferencesCollector,
+                                RayInterferencesCollector, PointInterferencesCollector};
+use math::{Scalar, Point, Vect};
+
+
+// Index used for non-existing nodes.
+static NONE: uint = uint::MAX;
+
+/// Stable identifier of an object stored on a `LooseTree`.
+///
+/// It remains valid until the object is removed from the tree. The storage of a removed object
+/// may be reused by another object, but its identifier will differ by its generation so that a
+/// stale identifier is never mistaken for the new object.
+#[deriving(Clone, PartialEq, Eq, Hash, Show, Encodable, Decodable)]
+pub struct LooseTreeId {
+    index:      uint,
+    generation: uint
+}
+
+/// A loose tree with fixed bounds: an octree in 3D, and a quadtree in 2D.
+///
+/// Each cell of the tree is subdivided into `2^dim` children cells. The objects are stored on the
+/// deepest cell containing their center and such that the cell, enlarged by the looseness factor,
+/// contains their bounding volume. Thus, inserting, moving, and removing an object costs
+/// `O(depth)`. The cells are created lazily and freed as soon as they become empty. Objects
+/// outside of the tree bounds are stored on the root cell.
+#[deriving(Clone, Encodable, Decodable)]
+pub struct LooseTree<N, P, B> {
+    looseness:   N,
+    max_depth:   uint,
+    nodes:       Vec<LooseTreeNode<P>>,
+    free_blocks: Vec<uint>,
+    proxies:     Vec<Option<LooseTreeProxy<P, B>>>,
+    generations: Vec<uint>,
+    free_ids:    Vec<uint>,
+    len:         uint
+}
+
+/// An object stored on a `LooseTree`.
+#[deriving(Clone, Encodable, Decodable)]
+pub struct LooseTreeProxy<P, B> {
+    /// The bounding volume of this object.
+    pub bounding_volume: AABB<P>,
+    /// An user-defined object.
+    pub object:          B,
+    /// The cell containing this object.
+    node:                uint,
+    /// Index of this object on its cell objects list.
+    slot:                uint
+}
+
+#[deriving(Clone, Encodable, Decodable)]
+struct LooseTreeNode<P> {
+    /// The cell covered by this node.
+    cell:        AABB<P>,
+    /// The cell enlarged by the looseness factor.
+    loose:       AABB<P>,
+    /// The objects stored on this node.
+    objects:     Vec<uint>,
+    /// Index of the first of the `2^dim` children of this node, or `NONE`.
+    first_child: uint,
+    parent:      uint,
+    depth:       uint,
+    /// Number of objects stored on the subtree rooted at this node.
+    nobjects:    uint
+}
+
+impl<N, P, V> LooseTreeNode<P>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> {
+    fn new(cell: AABB<P>, looseness: N, parent: uint, depth: uint) -> LooseTreeNode<P> {
+        let _2: N  = na::cast(2.0f64);
+        let margin = (*cell.maxs() - *cell.mins()) * ((looseness - na::one()) / _2);
+        let loose  = AABB::new(*cell.mins() - margin, *cell.maxs() + margin);
+
+        LooseTreeNode {
+            cell:        cell,
+            loose:       loose,
+            objects:     Vec::new(),
+            first_child: NONE,
+            parent:      parent,
+            depth:       depth,
+            nobjects:    0
+        }
+    }
+
+    // Index of the child cell containing the point `pt`.
+    fn child_containing(&self, pt: &P) -> uint {
+        let center = self.cell.center();
+        let mut res = 0;
+
+        for i in range(0, na::dim::<P>()) {
+            if pt[i] >= center[i] {
+                res = res | (1 << i);
+            }
+        }
+
+        res
+    }
+
+    // The cell of the i-th child of this node.
+    fn child_cell(&self, i: uint) -> AABB<P> {
+        let center   = self.cell.center();
+        let mut mins = self.cell.mins().clone();
+        let mut maxs = center.clone();
+
+        for j in range(0, na::dim::<P>()) {
+            if i & (1 << j) != 0 {
+                mins[j] = center[j];
+                maxs[j] = self.cell.maxs()[j];
+            }
+        }
+
+        AABB::new(mins, maxs)
+    }
+}
+
+impl<N, P, V, B> LooseTree<N, P, B>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> {
+    /// Creates a new empty loose tree.
+    ///
+    /// # Arguments:
+    /// * `bounds` - the region covered by the tree.
+    /// * `looseness` - the factor by which each cell is enlarged to contain its objects. It must
+    ///                 be greater than or equal to 1. A value of 2 is commonly used.
+    /// * `max_depth` - the maximum depth of the tree.
+    pub fn new(bounds: AABB<P>, looseness: N, max_depth: uint) -> LooseTree<N, P, B> {
+        assert!(looseness >= na::one(), "The looseness factor must be greater than or equal to 1.");
+
+        LooseTree {
+            nodes:       vec!(LooseTreeNode::new(bounds, looseness, NONE, 0)),
+            looseness:   looseness,
+            max_depth:   max_depth,
+            free_blocks: Vec::new(),
+            proxies:     Vec::new(),
+            generations: Vec::new(),
+            free_ids:    Vec::new(),
+            len:         0
+        }
+    }
+
+    /// The region covered by this tree.
+    #[inline]
+    pub fn bounds(&self) -> &AABB<P> {
+        &self.nodes[0].cell
+    }
+
+    /// The number of objects stored on this tree.
+    #[inline]
+    pub fn len(&self) -> uint {
+        self.len
+    }
+
+    /// Whether this tree contains no object.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether the object with the given identifier is on this tree.
+    ///
+    /// This is `false` for the identifier of a removed object.
+    #[inline]
+    pub fn contains(&self, id: LooseTreeId) -> bool {
+        id.index < self.proxies.len()                  &&
+        self.generations[id.index] == id.generation &&
+        self.proxies[id.index].is_some()
+    }
+
+    /// The object with the given identifier.
+    ///
+    /// Returns `None` if the object is not on this tree.
+    #[inline]
+    pub fn get(&self, id: LooseTreeId) -> Option<&LooseTreeProxy<P, B>> {
+        if self.contains(id) {
+            self.proxies[id.index].as_ref()
+        }
+        else {
+            None
+        }
+    }
+
+    /// Inserts a new object and returns its identifier.
+    pub fn insert(&mut self, b: B, bv: AABB<P>) -> LooseTreeId {
+        let proxy = LooseTreeProxy {
+            bounding_volume: bv,
+            object:          b,
+            node:            NONE,
+            slot:            NONE
+        };
+
+        let i = match self.free_ids.pop() {
+            Some(i) => {
+                self.proxies[i] = Some(proxy);
+                i
+            },
+            None => {
+                self.proxies.push(Some(proxy));
+                self.generations.push(0);
+                self.proxies.len() - 1
+            }
+        };
+
+        self.attach(i);
+        self.len = self.len + 1;
+
+        LooseTreeId {
+            index:      i,
+            generation: self.generations[i]
+        }
+    }
+
+    /// Removes an object from the tree and returns it. Fails if the object is not on this tree.
+    pub fn remove(&mut self, id: LooseTreeId) -> LooseTreeProxy<P, B> {
+        assert!(self.contains(id), "Attempting to remove an object which is not on the tree.");
+
+        let i = id.index;
+
+        self.detach(i);
+        self.len = self.len - 1;
+
+        let mut proxy = self.proxies[i].take().unwrap();
+
+        // Invalidate every identifier of the removed object.
+        self.generations[i] = self.generations[i] + 1;
+        self.free_ids.push(i);
+
+        proxy.node = NONE;
+        proxy.slot = NONE;
+
+        proxy
+    }
+
+    /// Replaces the bounding volume of an object, and moves it to its new cell.
+    ///
+    /// Fails if the object is not on this tree.
+    pub fn update_bounding_volume(&mut self, id: LooseTreeId, bv: AABB<P>) {
+        assert!(self.contains(id), "Attempting to update an object which is not on the tree.");
+
+        let i = id.index;
+
+        self.detach(i);
+        self.proxy_mut(i).bounding_volume = bv;
+        self.attach(i);
+    }
+
+    /// Removes all the objects having a bounding volume contained by `region`.
+    ///
+    /// This is useful to page out a region of the world. The removed objects are returned with
+    /// their bounding volumes so that they can be inserted back later.
+    pub fn remove_contained_in(&mut self, region: &AABB<P>) -> Vec<(AABB<P>, B)> {
+        let mut ids   = Vec::new();
+        // The objects of the root are always checked since they may lie outside of its cell.
+        let mut stack = vec!(0u);
+
+        loop {
+            match stack.pop() {
+                Some(node) => {
+                    let n = &self.nodes[node];
+
+                    for i in n.objects.iter() {
+                        if region.contains(&self.proxy(*i).bounding_volume) {
+                            ids.push(*i)
+                        }
+                    }
+
+                    if n.first_child != NONE {
+                        // A cell can only store objects contained by its loose bounds.
+                        for child in range(n.first_child, n.first_child + self.nchildren()) {
+                            let c = &self.nodes[child];
+
+                            if c.nobjects != 0 && c.loose.intersects(region) {
+                                stack.push(child)
+                            }
+                        }
+                    }
+                },
+                None => break
+            }
+        }
+
+        ids.into_iter().map(|i| {
+            let id    = LooseTreeId { index: i, generation: self.generations[i] };
+            let proxy = self.remove(id);
+
+            (proxy.bounding_volume, proxy.object)
+        }).collect()
+    }
+
+    /// Visit this tree using… a visitor!
+    ///
+    /// The visitor internal nodes are the loose cells of the tree. Note that the objects
+    /// stored on the root cell are always visited since they may lie outside of the tree bounds.
+    pub fn visit<Vis: BVTVisitor<B, AABB<P>>>(&self, visitor: &mut Vis) {
+        let root = &self.nodes[0];
+
+        for i in root.objects.iter() {
+            let proxy = self.proxy(*i);
+            visitor.visit_leaf(&proxy.object, &proxy.bounding_volume);
+        }
+
+        if root.first_child != NONE && visitor.visit_internal(&root.loose) {
+            self.visit_children(0, visitor);
+        }
+    }
+
+    fn visit_children<Vis: BVTVisitor<B, AABB<P>>>(&self, node: uint, visitor: &mut Vis) {
+        let first_child = self.nodes[node].first_child;
+
+        for child in range(first_child, first_child + self.nchildren()) {
+            let n = &self.nodes[child];
+
+            if n.nobjects != 0 && visitor.visit_internal(&n.loose) {
+                for i in n.objects.iter() {
+                    let proxy = self.proxy(*i);
+                    visitor.visit_leaf(&proxy.object, &proxy.bounding_volume);
+                }
+
+                if n.first_child != NONE {
+                    self.visit_children(child, visitor);
+                }
+            }
+        }
+    }
+
+    /// Computes the depth of the deepest non-empty cell of this tree.
+    pub fn depth(&self) -> uint {
+        let mut res = 0;
+
+        for node in self.nodes.iter() {
+            if node.nobjects != 0 {
+                res = na::max(res, node.depth + 1);
+            }
+        }
+
+        res
+    }
+
+    #[inline]
+    fn nchildren(&self) -> uint {
+        1 << na::dim::<P>()
+    }
+
+    #[inline]
+    fn proxy(&self, i: uint) -> &LooseTreeProxy<P, B> {
+        self.proxies[i].as_ref().expect("LooseTree internal error: invalid object.")
+    }
+
+    #[inline]
+    fn proxy_mut(&mut self, i: uint) -> &mut LooseTreeProxy<P, B> {
+        self.proxies[i].as_mut().expect("LooseTree internal error: invalid object.")
+    }
+
+    // Stores the object `i` on the deepest cell that can contain it.
+    fn attach(&mut self, i: uint) {
+        let mut curr = 0;
+
+        loop {
+            self.nodes[curr].nobjects = self.nodes[curr].nobjects + 1;
+
+            if self.nodes[curr].depth == self.max_depth {
+                break;
+            }
+
+            let child = {
+                let node  = &self.nodes[curr];
+                let bv    = &self.proxy(i).bounding_volume;
+                let child = node.child_containing(&bv.center());
+                let cell  = node.child_cell(child);
+                let loose = LooseTreeNode::new(cell, self.looseness, NONE, 0).loose;
+
+                if loose.contains(bv) { Some(child) } else { None }
+            };
+
+            match child {
+                Some(child) => {
+                    if self.nodes[curr].first_child == NONE {
+                        self.subdivide(curr);
+                    }
+
+                    curr = self.nodes[curr].first_child + child;
+                },
+                None => break
+            }
+        }
+
+        let slot = self.nodes[curr].objects.len();
+        self.nodes[curr].objects.push(i);
+
+        let proxy = self.proxy_mut(i);
+        proxy.node = curr;
+        proxy.slot = slot;
+    }
+
+    // Removes the object `i` from its cell, and frees the cells that became empty.
+    fn detach(&mut self, i: uint) {
+        let (node, slot) = {
+            let proxy = self.proxy(i);
+            (proxy.node, proxy.slot)
+        };
+
+        let _ = self.nodes[node].objects.swap_remove(slot);
+
+        if slot < self.nodes[node].objects.len() {
+            let moved = self.nodes[node].objects[slot];
+            self.proxy_mut(moved).slot = slot;
+        }
+
+        let mut curr = node;
+
+        while curr != NONE {
+            self.nodes[curr].nobjects = self.nodes[curr].nobjects - 1;
+
+            if self.nodes[curr].nobjects == 0 && self.nodes[curr].first_child != NONE {
+                // All the children are empty, and have no children themselves.
+                self.free_blocks.push(self.nodes[curr].first_child);
+                self.nodes[curr].first_child = NONE;
+            }
+
+            curr = self.nodes[curr].parent;
+        }
+    }
+
+    // Creates the children of the given node.
+    fn subdivide(&mut self, node: uint) {
+        let nchildren = self.nchildren();
+        let depth     = self.nodes[node].depth + 1;
+
+        let first = match self.free_blocks.pop() {
+            Some(first) => first,
+            None        => {
+                let first = self.nodes.len();
+                let dummy = self.nodes[node].clone();
+
+                for _ in range(0, nchildren) {
+                    self.nodes.push(dummy.clone());
+                }
+
+                first
+            }
+        };
+
+        for i in range(0, nchildren) {
+            let cell = self.nodes[node].child_cell(i);
+            self.nodes[first + i] = LooseTreeNode::new(cell, self.looseness, node, depth);
+        }
+
+        self.nodes[node].first_child = first;
+    }
+}
+
+impl<N, P, V, B> LooseTree<N, P, B>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          B: Clone {
+    /// Finds all the objects which have their bounding volumes intersecting the one of a specific
+    /// object. The object itself is not included.
+    ///
+    /// Fails if the object is not on this tree.
+    pub fn interferences_with_object(&self, id: LooseTreeId, out: &mut Vec<B>) {
+        assert!(self.contains(id), "Attempting to query an object which is not on the tree.");
+
+        let itself    = id.index;
+        let bv        = &self.proxy(itself).bounding_volume;
+        let mut stack = vec!(0u);
+
+        loop {
+            match stack.pop() {
+                Some(node) => {
+                    let n = &self.nodes[node];
+
+                    for i in n.objects.iter() {
+                        let proxy = self.proxy(*i);
+
+                        if *i != itself && proxy.bounding_volume.intersects(bv) {
+                            out.push(proxy.object.clone())
+                        }
+                    }
+
+                    if n.first_child != NONE {
+                        for child in range(n.first_child, n.first_child + self.nchildren()) {
+                            let c = &self.nodes[child];
+
+                            if c.nobjects != 0 && c.loose.intersects(bv) {
+                                stack.push(child)
+                            }
+                        }
+                    }
+                },
+                None => break
+            }
+        }
+    }
+
+    /// Finds all the objects which have their bounding volumes intersecting a given bounding
+    /// volume.
+    pub fn interferences_with_bounding_volume(&self, bv: &AABB<P>, out: &mut Vec<B>) {
+        let mut visitor = BoundingVolumeInterferencesCollector::new(bv, out);
+
+        self.visit(&mut visitor)
+    }
+
+    /// Finds all the objects which have their bounding volumes intersecting a given ray.
+    pub fn interferences_with_ray(&self, ray: &Ray<P, V>, out: &mut Vec<B>) {
+        let mut visitor = RayInterferencesCollector::new(ray, out);
+
+        self.visit(&mut visitor)
+    }
+
+    /// Finds all the objects which have their bounding volumes containing a given point.
+    pub fn interferences_with_point(&self, point: &P, out: &mut Vec<B>) {
+        let mut visitor = PointInterferencesCollector::new(point, out);
+
+        self.visit(&mut visitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::rand::{IsaacRng, Rng};
+    use na::{Pnt3, Vec3};
+    use bounding_volume::{AABB, BoundingVolume};
+    use partitioning::loose_tree::{LooseTree, LooseTreeId};
+
+    type TestLooseTree = LooseTree<f64, Pnt3<f64>, uint>;
+
+    // Some of the generated AABBs lie partially outside of the [0, 10]^3 tree bounds.
+    fn random_aabb(rng: &mut IsaacRng) -> AABB<Pnt3<f64>> {
+        let center = Pnt3::new(rng.gen::<f64>() * 12.0 - 1.0, rng.gen::<f64>() * 12.0 - 1.0, rng.gen::<f64>() * 12.0 - 1.0);
+        let half   = Vec3::new(rng.gen::<f64>(), rng.gen::<f64>(), rng.gen::<f64>()) * 1.5;
+
+        AABB::new(center - half, center + half)
+    }
+
+    fn check_queries(tree: &TestLooseTree, objects: &[(LooseTreeId, uint, AABB<Pnt3<f64>>)], rng: &mut IsaacRng) {
+        assert!(tree.len() == objects.len());
+
+        for _ in range(0u, 20) {
+            let bv = random_aabb(rng);
+            let mut expected: Vec<uint> = objects.iter().filter(|o| o.ref2().intersects(&bv)).map(|o| *o.ref1()).collect();
+            let mut found = Vec::new();
+
+            tree.interferences_with_bounding_volume(&bv, &mut found);
+
+            expected.sort();
+            found.sort();
+            assert!(expected == found);
+        }
+
+        for &(id, object, ref bv) in objects.iter() {
+            let mut expected: Vec<uint> = objects.iter().filter(|o| *o.ref1() != object && o.ref2().intersects(bv))
+                                                        .map(|o| *o.ref1()).collect();
+            let mut found = Vec::new();
+
+            tree.interferences_with_object(id, &mut found);
+
+            expected.sort();
+            found.sort();
+            assert!(expected == found);
+        }
+    }
+
+    #[test]
+    fn test_loose_tree_insert_move_remove() {
+        let bounds      = AABB::new(Pnt3::new(0.0, 0.0, 0.0), Pnt3::new(10.0, 10.0, 10.0));
+        let mut rng     = IsaacRng::new_unseeded();
+        let mut tree    = LooseTree::new(bounds, 2.0, 5);
+        let mut objects = Vec::new();
+
+        for i in range(0u, 200) {
+            let bv = random_aabb(&mut rng);
+            let id = tree.insert(i, bv.clone());
+
+            objects.push((id, i, bv));
+        }
+
+        check_queries(&tree, objects.as_slice(), &mut rng);
+
+        for o in objects.iter_mut() {
+            let bv = random_aabb(&mut rng);
+
+            tree.update_bounding_volume(*o.ref0(), bv.clone());
+            *o.mut2() = bv;
+        }
+
+        check_queries(&tree, objects.as_slice(), &mut rng);
+
+        let mut kept = Vec::new();
+
+        for (i, o) in objects.into_iter().enumerate() {
+            if i % 2 == 0 {
+                assert!(tree.remove(*o.ref0()).object == *o.ref1());
+            }
+            else {
+                kept.push(o);
+            }
+        }
+
+        check_queries(&tree, kept.as_slice(), &mut rng);
+
+        let unit = AABB::new(Pnt3::new(0.0, 0.0, 0.0), Pnt3::new(1.0, 1.0, 1.0));
+        let mut single: TestLooseTree = LooseTree::new(unit, 2.0, 5);
+        let id = single.insert(0, AABB::new(Pnt3::new(0.2, 0.2, 0.2), Pnt3::new(0.3, 0.3, 0.3)));
+        let mut out = Vec::new();
+
+        single.interferences_with_object(id, &mut out);
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_loose_tree_stale_id_and_remove_contained_in() {
+        let bounds      = AABB::new(Pnt3::new(0.0, 0.0, 0.0), Pnt3::new(10.0, 10.0, 10.0));
+        let mut rng     = IsaacRng::new_unseeded();
+        let mut tree    = LooseTree::new(bounds, 2.0, 5);
+
+        // A removed identifier is not mistaken for the object reusing its storage.
+        let stale = tree.insert(1000, random_aabb(&mut rng));
+        let _     = tree.remove(stale);
+        let fresh = tree.insert(1001, random_aabb(&mut rng));
+
+        assert!(!tree.contains(stale) && tree.get(stale).is_none());
+        assert!(tree.contains(fresh) && tree.get(fresh).unwrap().object == 1001);
+
+        let mut objects = vec!((fresh, 1001u, tree.get(fresh).unwrap().bounding_volume.clone()));
+
+        for i in range(0u, 300) {
+            let bv = random_aabb(&mut rng);
+            let id = tree.insert(i, bv.clone());
+
+            objects.push((id, i, bv));
+        }
+
+        let region = AABB::new(Pnt3::new(-2.0, 1.0, 2.0), Pnt3::new(6.0, 8.0, 12.0));
+        let mut expected: Vec<uint> = objects.iter().filter(|o| region.contains(o.ref2())).map(|o| *o.ref1()).collect();
+        let mut removed: Vec<uint>  = tree.remove_contained_in(&region).into_iter().map(|(_, b)| b).collect();
+
+        expected.sort();
+        removed.sort();
+        assert!(!expected.is_empty() && expected == removed);
+
+        let kept: Vec<(LooseTreeId, uint, AABB<Pnt3<f64>>)> =
+            objects.into_iter().filter(|o| !region.contains(o.ref2())).collect();
+
+        assert!(kept.iter().all(|o| tree.contains(*o.ref0())));
+        check_queries(&tree, kept.as_slice(), &mut rng);
+    }
+}
+
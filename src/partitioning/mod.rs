@@ -1,20 +1,30 @@
 //! Spatial partitioning tools.
 
-pub use partitioning::dbvt::{DBVT, DBVTLeaf};
+pub use partitioning::dbvt::{DBVT, DBVTLeaf, LeafId};
 pub use partitioning::bvt::{BVT, median_partitioner, median_partitioner_with_centers,
-                            BinaryPartition, BVTNode};
+                            sah_partitioner, sah_partitioner_with_centers, BinaryPartition,
+                            BVTNode};
+pub use partitioning::flat_bvt::{FlatBVT, FlatBVTNode};
+pub use partitioning::loose_tree::{LooseTree, LooseTreeProxy, LooseTreeId};
+pub use partitioning::kd_tree::KdTree;
 #[doc(inline)]
 pub use partitioning::bvt_visitor::{BVTVisitor,
                                     RayInterferencesCollector,
                                     BoundingVolumeInterferencesCollector,
-                                    PointInterferencesCollector};
+                                    PointInterferencesCollector,
+                                    FrustumInterferencesCollector};
 #[doc(inline)]
 pub use partitioning::bvtt_visitor::BVTTVisitor;
 #[doc(inline)]
 pub use partitioning::bvt_cost_fn::{BVTCostFn, RayIntersectionCostFn};
 
+use na::{Pnt2, Pnt3};
+
 mod dbvt;
 mod bvt;
+mod flat_bvt;
+mod loose_tree;
+mod kd_tree;
 
 #[doc(hidden)]
 pub mod bvt_visitor;
@@ -22,3 +32,14 @@ pub mod bvt_visitor;
 pub mod bvtt_visitor;
 #[doc(hidden)]
 pub mod bvt_cost_fn;
+
+/*
+ *
+ * Aliases.
+ *
+ */
+/// A loose octree.
+pub type LooseOctree<N, B> = LooseTree<N, Pnt3<N>, B>;
+
+/// A loose quadtree.
+pub type LooseQuadtree<N, B> = LooseTree<N, Pnt2<N>, B>;
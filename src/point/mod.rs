@@ -2,6 +2,7 @@
 
 #[doc(inline)]
 pub use self::point_query::{LocalPointQuery, PointQuery};
+pub use self::point_batch::{project_points_parallel, distances_to_points_parallel};
 
 #[doc(hidden)]
 pub mod point_query;
@@ -9,6 +10,8 @@ mod point_plane;
 mod point_ball;
 mod point_cuboid;
 mod point_aabb;
+mod point_obb;
+mod point_kdop;
 mod point_bounding_sphere;
 mod point_support_map;
 mod point_segment;
@@ -16,4 +19,5 @@ mod point_triangle;
 mod point_compound;
 mod point_mesh;
 mod point_bezier_surface;
+mod point_batch;
 // point_bvt;
@@ -0,0 +1,76 @@
+use std::sync::Arc;
+use point::LocalPointQuery;
+use utils;
+
+
+/// Projects several points on the same shape, in parallel.
+///
+/// The points are split among at most `nthreads` threads. The i-th result is the projection of
+/// the i-th point.
+pub fn project_points_parallel<N, P, S>(shape:    &Arc<S>,
+                                        pts:      &[P],
+                                        solid:    bool,
+                                        nthreads: uint)
+                                        -> Vec<P>
+    where N: Send,
+          P: Send + Clone,
+          S: Send + Sync + LocalPointQuery<N, P> {
+    let inputs = pts.iter().map(|pt| (pt.clone(), solid)).collect();
+
+    utils::parallel_map(shape, inputs, nthreads, project_point::<N, P, S>)
+}
+
+/// Computes the distances between several points and the same shape, in parallel.
+///
+/// The points are split among at most `nthreads` threads. The i-th result is the distance to
+/// the i-th point.
+pub fn distances_to_points_parallel<N, P, S>(shape: &Arc<S>, pts: &[P], nthreads: uint) -> Vec<N>
+    where N: Send,
+          P: Send + Clone,
+          S: Send + Sync + LocalPointQuery<N, P> {
+    utils::parallel_map(shape, pts.to_vec(), nthreads, distance_to_point::<N, P, S>)
+}
+
+fn project_point<N, P, S>(shape: &S, (pt, solid): (P, bool)) -> P
+    where S: LocalPointQuery<N, P> {
+    shape.project_point(&pt, solid)
+}
+
+fn distance_to_point<N, P, S>(shape: &S, pt: P) -> N
+    where S: LocalPointQuery<N, P> {
+    shape.distance_to_point(&pt)
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+    use std::rand::{IsaacRng, Rng};
+    use na::{Pnt3, Vec3};
+    use point::LocalPointQuery;
+    use shape::Cuboid;
+
+    #[test]
+    fn test_parallel_point_queries_match_sequential() {
+        let mut rng = IsaacRng::new_unseeded();
+        let cuboid  = Arc::new(Cuboid::new(Vec3::new(1.0f64, 2.0, 3.0)));
+        let pts: Vec<Pnt3<f64>> = range(0u, 500).map(|_| {
+            Pnt3::new(rng.gen::<f64>() * 10.0 - 5.0, rng.gen::<f64>() * 10.0 - 5.0, rng.gen::<f64>() * 10.0 - 5.0)
+        }).collect();
+
+        for nthreads in [ 1u, 3, 8, 1000 ].iter() {
+            for solid in [ true, false ].iter() {
+                let projs = super::project_points_parallel(&cuboid, pts.as_slice(), *solid, *nthreads);
+
+                assert!(projs == pts.iter().map(|pt| cuboid.project_point(pt, *solid)).collect::<Vec<Pnt3<f64>>>());
+            }
+
+            let dists = super::distances_to_points_parallel(&cuboid, pts.as_slice(), *nthreads);
+
+            assert!(dists == pts.iter().map(|pt| cuboid.distance_to_point(pt)).collect::<Vec<f64>>());
+        }
+
+        let no_pts: &[Pnt3<f64>] = &[];
+
+        assert!(super::distances_to_points_parallel(&cuboid, no_pts, 4).is_empty());
+    }
+}
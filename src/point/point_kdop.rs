@@ -0,0 +1,239 @@
+use std::num::Float;
+use na::{Transform, Bounded};
+use na;
+use point::{LocalPointQuery, PointQuery};
+use bounding_volume::{KDOP, KDOPDirections};
+use math::{Scalar, Point, Vect};
+
+impl<N, P, V, D> LocalPointQuery<N, P> for KDOP<N, V, D>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          D: KDOPDirections<V> {
+    fn project_point(&self, pt: &P, solid: bool) -> P {
+        let ndirs = self.directions().ndirections();
+
+        if self.contains_point(pt) {
+            if solid {
+                return pt.clone()
+            }
+
+            // The closest boundary point lies on the closest face plane.
+            let _max: N     = Bounded::max_value();
+            let mut best    = _max;
+            let mut shift   = na::zero::<V>();
+
+            for i in range(0, ndirs) {
+                let dir  = self.directions().direction(i);
+                let proj = na::dot(pt.as_vec(), &dir);
+
+                if proj - self.mins()[i] < best {
+                    best  = proj - self.mins()[i];
+                    shift = dir * (-best);
+                }
+
+                if self.maxs()[i] - proj < best {
+                    best  = self.maxs()[i] - proj;
+                    shift = dir * best;
+                }
+            }
+
+            return *pt + shift
+        }
+
+        // The projection is the projection on the affine hull of a face of the k-DOP, which is the
+        // intersection of at most `dim` face planes with independent normals. All those
+        // intersections are tried, and the closest one lying on the k-DOP is kept. Unlike an
+        // iterative method, this never overestimates the distance to the k-DOP.
+        let _eps: N   = Float::epsilon();
+        let mut scale = na::one::<N>();
+
+        for i in range(0, ndirs) {
+            scale = scale.max(self.mins()[i].abs()).max(self.maxs()[i].abs());
+        }
+
+        let _max: N   = Bounded::max_value();
+        let mut best  = (_max, pt.clone());
+        let mut basis = Vec::with_capacity(na::dim::<V>());
+
+        project_on_face_hulls(self, pt, 0, _eps.sqrt() * scale, &mut basis, &mut best);
+
+        best.val1()
+    }
+
+    #[inline]
+    fn distance_to_point(&self, pt: &P) -> N {
+        na::dist(pt, &self.project_point(pt, true))
+    }
+
+    #[inline]
+    fn contains_point(&self, pt: &P) -> bool {
+        range(0, self.directions().ndirections()).all(|i| {
+            let proj = na::dot(pt.as_vec(), &self.directions().direction(i));
+
+            proj >= self.mins()[i] && proj <= self.maxs()[i]
+        })
+    }
+}
+
+// Projects `pt` on the intersections of the face planes of `kdop` with directions starting from the
+// `first`-th one, and of the planes of `basis`. `basis` contains orthonormal plane normals along
+// with their offsets.
+fn project_on_face_hulls<N, P, V, D>(kdop:  &KDOP<N, V, D>,
+                                     pt:    &P,
+                                     first: uint,
+                                     tol:   N,
+                                     basis: &mut Vec<(V, N)>,
+                                     best:  &mut (N, P))
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          D: KDOPDirections<V> {
+    let _eps: N = Float::epsilon();
+
+    for i in range(first, kdop.directions().ndirections()) {
+        let dir = kdop.directions().direction(i);
+
+        for offset in [ kdop.mins()[i], kdop.maxs()[i] ].iter() {
+            // Gram-Schmidt orthonormalization of the plane `dot(x, dir) = offset`.
+            let mut normal = dir.clone();
+            let mut offset = *offset;
+
+            for &(ref n, ref o) in basis.iter() {
+                let proj = na::dot(&normal, n);
+
+                normal = normal - *n * proj;
+                offset = offset - *o * proj;
+            }
+
+            let norm = na::norm(&normal);
+
+            if norm <= _eps.sqrt() {
+                // Not independent from the planes already in the basis.
+                continue;
+            }
+
+            basis.push((normal / norm, offset / norm));
+
+            let mut proj = pt.clone();
+
+            for &(ref n, ref o) in basis.iter() {
+                proj = proj + *n * (*o - na::dot(proj.as_vec(), n));
+            }
+
+            let sqdist = na::sqdist(pt, &proj);
+
+            if sqdist < *best.ref0() && contains_point_with_tolerance(kdop, &proj, tol) {
+                *best = (sqdist, proj);
+            }
+
+            if basis.len() < na::dim::<V>() {
+                project_on_face_hulls(kdop, pt, i + 1, tol, basis, best);
+            }
+
+            let _ = basis.pop();
+        }
+    }
+}
+
+fn contains_point_with_tolerance<N, P, V, D>(kdop: &KDOP<N, V, D>, pt: &P, tol: N) -> bool
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          D: KDOPDirections<V> {
+    range(0, kdop.directions().ndirections()).all(|i| {
+        let proj = na::dot(pt.as_vec(), &kdop.directions().direction(i));
+
+        proj >= kdop.mins()[i] - tol && proj <= kdop.maxs()[i] + tol
+    })
+}
+
+impl<N, P, V, M, D> PointQuery<N, P, M> for KDOP<N, V, D>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Transform<P>,
+          D: KDOPDirections<V> {
+}
+
+#[cfg(test)]
+mod test {
+    use std::rand::{IsaacRng, Rng};
+    use na::{Pnt3, Iso3, Vec3};
+    use na;
+    use bounding_volume::{KDOP, KDOPDirections, DOP14, DOP26, BoundingVolume};
+    use bounding_volume;
+    use point::LocalPointQuery;
+
+    fn random_point(rng: &mut IsaacRng, min: f64, max: f64) -> Pnt3<f64> {
+        let d = max - min;
+
+        Pnt3::new(rng.gen::<f64>() * d + min, rng.gen::<f64>() * d + min, rng.gen::<f64>() * d + min)
+    }
+
+    fn check_against_brute_force<D: KDOPDirections<Vec3<f64>>>(rng: &mut IsaacRng, directions: D) {
+        let m: Iso3<f64> = na::one();
+        let pts: Vec<Pnt3<f64>> = range(0u, 10).map(|_| random_point(rng, 0.0, 4.0)).collect();
+        let kdop: KDOP<f64, Vec3<f64>, D> = bounding_volume::point_cloud_kdop(&m, pts.as_slice(), &directions);
+
+        // Points sampled inside of the k-DOP. The first three directions are the canonical axes.
+        let samples: Vec<Pnt3<f64>> = range(0u, 20000).map(|_| {
+            let mut pt = random_point(rng, 0.0, 1.0);
+
+            for i in range(0u, 3) {
+                pt[i] = kdop.mins()[i] + pt[i] * (kdop.maxs()[i] - kdop.mins()[i]);
+            }
+
+            pt
+        }).filter(|pt| kdop.contains_point(pt)).collect();
+
+        assert!(!samples.is_empty());
+
+        for _ in range(0u, 100) {
+            let pt   = random_point(rng, -4.0, 8.0);
+            let proj = kdop.project_point(&pt, true);
+            let dist = kdop.distance_to_point(&pt);
+
+            if kdop.contains_point(&pt) {
+                assert!(proj == pt && dist == 0.0);
+                continue;
+            }
+
+            assert!(kdop.loosened(1.0e-7).contains_point(&proj));
+            assert!(na::approx_eq(&dist, &na::dist(&pt, &proj)));
+
+            // The largest distance to a slab is a lower bound of the distance.
+            let mut lower = 0.0f64;
+
+            for i in range(0, directions.ndirections()) {
+                let d = na::dot(pt.as_vec(), &directions.direction(i));
+
+                lower = lower.max(kdop.mins()[i] - d).max(d - kdop.maxs()[i]);
+            }
+
+            assert!(dist >= lower - 1.0e-7);
+
+            // The distance must never be overestimated.
+            for s in samples.iter() {
+                assert!(dist <= na::dist(&pt, s) + 1.0e-7);
+            }
+        }
+
+        // From the inside, the boundary is reached through the closest face.
+        let inside = samples[0];
+        let proj   = kdop.project_point(&inside, false);
+
+        assert!(kdop.loosened(1.0e-7).contains_point(&proj));
+        assert!(!kdop.tightened(1.0e-7).contains_point(&proj));
+    }
+
+    #[test]
+    fn test_kdop_point_queries_against_brute_force() {
+        let mut rng = IsaacRng::new_unseeded();
+
+        for _ in range(0u, 5) {
+            check_against_brute_force(&mut rng, DOP14);
+            check_against_brute_force(&mut rng, DOP26);
+        }
+    }
+}
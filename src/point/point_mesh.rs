@@ -16,7 +16,7 @@ impl<N, P, V, E> LocalPointQuery<N, P> for Mesh<N, P, V, E>
     fn project_point(&self, point: &P, _: bool) -> P {
         let mut cost_fn = MeshPointProjCostFn { mesh: self, point: point };
 
-        self.bvt().best_first_search(&mut cost_fn).unwrap().val1()
+        self.flat_bvt().best_first_search(&mut cost_fn).unwrap().val1()
     }
 
     #[inline]
@@ -28,7 +28,7 @@ impl<N, P, V, E> LocalPointQuery<N, P> for Mesh<N, P, V, E>
     fn contains_point(&self, point: &P) -> bool {
         let mut test = PointContainementTest { mesh: self, point: point, found: false };
 
-        self.bvt().visit(&mut test);
+        self.flat_bvt().visit(&mut test);
 
         test.found
     }
@@ -0,0 +1,35 @@
+use na::Transform;
+use bounding_volume::OBB;
+use shape::Cuboid;
+use point::{LocalPointQuery, PointQuery};
+use math::{Scalar, Point, Vect, Isometry};
+
+
+impl<N, P, V, M> LocalPointQuery<N, P> for OBB<V, M>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Isometry<N, P, V> {
+    #[inline]
+    fn project_point(&self, pt: &P, solid: bool) -> P {
+        Cuboid::new(self.half_extents().clone()).project_point_with_transform(self.transform(), pt, solid)
+    }
+
+    #[inline]
+    fn distance_to_point(&self, pt: &P) -> N {
+        Cuboid::new(self.half_extents().clone()).distance_to_point_with_transform(self.transform(), pt)
+    }
+
+    #[inline]
+    fn contains_point(&self, pt: &P) -> bool {
+        Cuboid::new(self.half_extents().clone()).contains_point_with_transform(self.transform(), pt)
+    }
+}
+
+impl<N, P, V, M, M2> PointQuery<N, P, M2> for OBB<V, M>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Isometry<N, P, V>,
+          M2: Transform<P> {
+}
@@ -1,6 +1,7 @@
 //! Procedural mesh generation.
 pub use procedural::trimesh::{TriMesh, IndexBuffer};
 pub use procedural::polyline::Polyline;
+pub use procedural::tetmesh::TetMesh;
 pub use procedural::parametric_surface::{parametric_surface_uniform,
                                                   parametric_surface_uniform_with_distance_error};
 pub use procedural::bezier::{bezier_surface, bezier_curve, bezier_surface_at, bezier_curve_at};
@@ -21,6 +22,7 @@ pub mod utils;
 pub mod path;
 mod trimesh;
 mod polyline;
+mod tetmesh;
 
 mod to_trimesh {
     pub mod to_trimesh;
@@ -67,6 +69,8 @@ mod hacd;
 pub type TriMesh3<N> = TriMesh<N, Pnt3<N>, Vec3<N>>;
 /// A 3D polyline.
 pub type Polyline3<N> = Polyline<N, Pnt3<N>, Vec3<N>>;
+/// A 3D tetrahedral mesh.
+pub type TetMesh3<N> = TetMesh<N, Pnt3<N>, Vec3<N>>;
 
 /// A 2D triangle mesh.
 pub type TriMesh2<N> = TriMesh<N, Pnt2<N>, Vec2<N>>;
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+use std::collections::hash_map::{Vacant, Occupied};
+use na::{Vec3, Vec4, Translate, Transform};
+use na;
+use procedural::{TriMesh, IndexBuffer};
+use utils;
+use math::{Scalar, Point, Vect};
+
+/// Geometric description of a tetrahedral mesh.
+#[deriving(Clone, Show)]
+pub struct TetMesh<N, P, V> {
+    /// Coordinates of the mesh vertices.
+    pub coords:  Vec<P>,
+    /// Index buffer of the mesh.
+    ///
+    /// Each tetrahedron `(a, b, c, d)` is positively oriented, i.e., `d` lies on the half-space
+    /// pointed by the normal of the counterclockwise triangle `(a, b, c)`.
+    pub indices: Vec<Vec4<u32>>
+}
+
+impl<N, P, V> TetMesh<N, P, V> {
+    /// Creates a new `TetMesh`.
+    pub fn new(coords: Vec<P>, indices: Vec<Vec4<u32>>) -> TetMesh<N, P, V> {
+        TetMesh {
+            coords:  coords,
+            indices: indices
+        }
+    }
+
+    /// The number of tetrahedra on this mesh.
+    #[inline]
+    pub fn num_tetrahedra(&self) -> uint {
+        self.indices.len()
+    }
+
+    /// Translates each vertex of this mesh.
+    #[inline]
+    pub fn translate_by<T: Translate<P>>(&mut self, t: &T) {
+        for c in self.coords.iter_mut() {
+            *c = t.translate(c);
+        }
+    }
+
+    /// Transforms each vertex of this mesh.
+    #[inline]
+    pub fn transform_by<T: Transform<P>>(&mut self, t: &T) {
+        for c in self.coords.iter_mut() {
+            *c = t.transform(c);
+        }
+    }
+}
+
+impl<N, P, V> TetMesh<N, P, V>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> {
+    /// The volume of the i-th tetrahedron.
+    #[inline]
+    pub fn tetrahedron_volume(&self, i: uint) -> N {
+        let t = &self.indices[i];
+
+        utils::tetrahedron_volume(&self.coords[t.x as uint], &self.coords[t.y as uint],
+                                  &self.coords[t.z as uint], &self.coords[t.w as uint])
+    }
+
+    /// The total volume of this mesh.
+    pub fn volume(&self) -> N {
+        let mut res = na::zero::<N>();
+
+        for i in range(0, self.indices.len()) {
+            res = res + self.tetrahedron_volume(i);
+        }
+
+        res
+    }
+
+    /// Computes the triangle mesh formed by the faces on the boundary of this tetrahedral mesh.
+    ///
+    /// The triangles are oriented toward the exterior of the mesh.
+    pub fn boundary(&self) -> TriMesh<N, P, V> {
+        let mut faces: HashMap<(u32, u32, u32), (uint, Vec3<u32>)> = HashMap::new();
+
+        for t in self.indices.iter() {
+            let tfaces = [ Vec3::new(t.x, t.z, t.y), Vec3::new(t.x, t.y, t.w),
+                           Vec3::new(t.y, t.z, t.w), Vec3::new(t.x, t.w, t.z) ];
+
+            for f in tfaces.iter() {
+                match faces.entry(face_key(f)) {
+                    Occupied(mut entry) => *entry.get_mut().mut0() += 1,
+                    Vacant(entry)       => { let _ = entry.set((1, f.clone())); }
+                }
+            }
+        }
+
+        let mut idx: Vec<Vec3<u32>> = faces.values().filter(|&&(n, _)| n == 1).map(|&(_, ref f)| f.clone()).collect();
+        let mut coords = self.coords.clone();
+
+        utils::remove_unused_points(&mut coords, idx.as_mut_slice());
+
+        TriMesh::new(coords, None, None, Some(IndexBuffer::Unified(idx)))
+    }
+}
+
+fn face_key(f: &Vec3<u32>) -> (u32, u32, u32) {
+    let mut a = f.x;
+    let mut b = f.y;
+    let mut c = f.z;
+
+    let (a, b, c) = utils::sort3(&mut a, &mut b, &mut c);
+
+    (*a, *b, *c)
+}
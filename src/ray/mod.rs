@@ -1,10 +1,14 @@
 //! Ray-casting related definitions and implementations.
 #[doc(inline)]
-pub use ray::ray::{Ray, LocalRayCast, RayCast, RayIntersection};
+pub use ray::ray::{Ray, LocalRayCast, RayCast, RayIntersection, FeatureId, RayCastOptions, InsideMode};
 pub use ray::ray_plane::plane_toi_with_ray;
-pub use ray::ray_triangle::triangle_ray_intersection;
+pub use ray::ray_triangle::{triangle_ray_intersection, triangle_ray_intersection_with_options};
+pub use ray::ray_segment::segment_ray_intersection;
 pub use ray::ray_support_map::implicit_toi_and_normal_with_ray;
 pub use ray::ray_ball::ball_toi_with_ray;
+pub use ray::ray_bezier_curve::bezier_curve_ray_intersection;
+pub use ray::ray_batch::{cast_rays_parallel, tois_with_rays_parallel};
+pub use ray::ray_packet::{RayPacket, LocalRayPacketCast, RayPacketCast, cast_ray_packet_individually};
 
 use na::{Pnt2, Vec2, Pnt3, Vec3};
 
@@ -14,14 +18,20 @@ mod ray_plane;
 mod ray_ball;
 mod ray_cuboid;
 mod ray_aabb;
+mod ray_obb;
+mod ray_kdop;
 mod ray_bounding_sphere;
 mod ray_support_map;
+mod ray_quadric;
 mod ray_triangle;
+mod ray_segment;
 mod ray_compound;
 mod ray_mesh;
 mod ray_bvt;
 mod ray_bezier_surface;
 mod ray_bezier_curve;
+mod ray_batch;
+mod ray_packet;
 
 /*
  *
@@ -39,3 +49,9 @@ pub type RayIntersection3<N> = RayIntersection<N, Vec3<N>>;
 
 /// A 2D ray intersection.
 pub type RayIntersection2<N> = RayIntersection<N, Vec2<N>>;
+
+/// A 3D ray packet.
+pub type RayPacket3<N> = RayPacket<Pnt3<N>, Vec3<N>>;
+
+/// A 2D ray packet.
+pub type RayPacket2<N> = RayPacket<Pnt2<N>, Vec2<N>>;
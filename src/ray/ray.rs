@@ -1,6 +1,8 @@
 //! Traits and structure needed to cast rays.
 
-use na::{Rotate, Transform, Pnt2};
+use na::{Rotate, Transform, Pnt2, Vec3, Bounded};
+use na;
+use math::Scalar;
 
 /// A Ray.
 #[deriving(Show, Encodable, Decodable, Clone)]
@@ -22,6 +24,102 @@ impl<P, V> Ray<P, V> {
     }
 }
 
+/// Identifier of the feature of a shape hit by a ray.
+///
+/// The meaning of the indices depends on the shape. A `Convex` does not store the topology of its
+/// hull so its features are identified by their points instead. With `n` the number of points of
+/// the `Convex`, and `i < j < k`:
+///   * `Vertex(i)` is the i-th point.
+///   * in 2d, `Face(i * n + j)` is the side with the distinct points `i` and `j` of smallest
+///   indices.
+///   * in 3d, `Edge(i * n + j)` is the edge with the distinct points `i` and `j` of smallest
+///   indices.
+///   * in 3d, `Face((i * n + j) * n + k)` is the face with the points `i` and `j` as above, and
+///   `k` the first point not aligned with them.
+///
+/// When those identifiers do not fit in a `uint`, the feature is `Unknown`.
+#[deriving(Show, PartialEq, Eq, Clone, Encodable, Decodable)]
+pub enum FeatureId {
+    /// The feature is not known.
+    Unknown,
+    /// A vertex of the shape.
+    Vertex(uint),
+    /// An edge of the shape.
+    Edge(uint),
+    /// A face of the shape. For a mesh, this is the index of the hit element: a triangle in 3D, or
+    /// a segment in 2D.
+    Face(uint)
+}
+
+/// How a ray cast handles rays starting inside of a shape.
+#[deriving(Show, PartialEq, Eq, Clone, Encodable, Decodable)]
+pub enum InsideMode {
+    /// The shape is solid: a ray starting inside of it hits it with a time of impact of zero.
+    Solid,
+    /// The shape is hollow: a ray starting inside of it hits its boundary on its way out.
+    Hollow,
+    /// A shape containing the origin of the ray is not hit at all.
+    Ignore
+}
+
+/// Options of a ray cast.
+#[deriving(Show, Clone, Encodable, Decodable)]
+pub struct RayCastOptions<N> {
+    /// Intersections with a time of impact greater than or equal to this value are ignored.
+    pub max_toi:         N,
+    /// Whether the back faces of triangles and meshes are ignored.
+    ///
+    /// A triangle `(a, b, c)` is front-facing if it appears counterclockwise when seen from the
+    /// ray origin. On closed shapes, a ray starting inside can only hit back faces so `Hollow`
+    /// behaves like `Ignore` when this is enabled.
+    pub cull_back_faces: bool,
+    /// How rays starting inside of a shape are handled.
+    pub inside:          InsideMode
+}
+
+impl<N: Scalar> RayCastOptions<N> {
+    /// Creates the default options: unlimited time of impact, no back-face culling, and solid
+    /// shapes.
+    #[inline]
+    pub fn new() -> RayCastOptions<N> {
+        RayCastOptions::new_with_max_toi(Bounded::max_value())
+    }
+
+    /// Creates options limiting the time of impact to `max_toi`.
+    ///
+    /// This is useful for line-of-sight tests.
+    #[inline]
+    pub fn new_with_max_toi(max_toi: N) -> RayCastOptions<N> {
+        RayCastOptions {
+            max_toi:         max_toi,
+            cull_back_faces: false,
+            inside:          InsideMode::Solid
+        }
+    }
+
+    /// The `solid` flag to give to the basic ray casting methods.
+    #[inline]
+    pub fn solid(&self) -> bool {
+        self.inside == InsideMode::Solid || self.inside == InsideMode::Ignore || self.cull_back_faces
+    }
+
+    /// Checks if a cast with the `solid` flag set to `self.solid()` and returning the time of
+    /// impact `toi` is valid for those options.
+    #[inline]
+    pub fn accepts_toi(&self, toi: &N) -> bool {
+        if *toi >= self.max_toi {
+            false
+        }
+        else if self.inside != InsideMode::Solid && self.solid() {
+            // The ray starts inside of the shape.
+            !na::is_zero(toi)
+        }
+        else {
+            true
+        }
+    }
+}
+
 /// Structure containing the result of a successful ray cast.
 pub struct RayIntersection<N, V> {
     /// The time of impact of the ray with the object.  The exact contact point can be computed
@@ -36,7 +134,20 @@ pub struct RayIntersection<N, V> {
 
     /// The textures coordinates at the intersection point.  This is an `Option` because some shape
     /// do not support texture coordinates.
-    pub uvs:    Option<Pnt2<N>>
+    ///
+    /// For 2D segments and bézier curves, the first coordinate is the parameter of the
+    /// intersection point along the curve.
+    pub uvs:    Option<Pnt2<N>>,
+
+    /// The feature of the shape hit by the ray.
+    pub feature: FeatureId,
+
+    /// The indices of the parts of the nested compound shapes hit by the ray, from the outermost
+    /// to the innermost one. This is empty if the shape is not a compound shape.
+    pub parts:   Vec<uint>,
+
+    /// The barycentric coordinates of the intersection point on the hit triangle of a mesh.
+    pub bcoords: Option<Vec3<N>>
 }
 
 impl<N, V> RayIntersection<N, V> {
@@ -44,9 +155,12 @@ impl<N, V> RayIntersection<N, V> {
     /// Creates a new `RayIntersection`.
     pub fn new_with_uvs(toi: N, normal: V, uvs: Option<Pnt2<N>>) -> RayIntersection<N, V> {
         RayIntersection {
-            toi:    toi,
-            normal: normal,
-            uvs:    uvs
+            toi:     toi,
+            normal:  normal,
+            uvs:     uvs,
+            feature: FeatureId::Unknown,
+            parts:   Vec::new(),
+            bcoords: None
         }
     }
 
@@ -54,9 +168,25 @@ impl<N, V> RayIntersection<N, V> {
     /// Creates a new `RayIntersection`.
     pub fn new(toi: N, normal: V) -> RayIntersection<N, V> {
         RayIntersection {
-            toi:    toi,
-            normal: normal,
-            uvs:    None
+            toi:     toi,
+            normal:  normal,
+            uvs:     None,
+            feature: FeatureId::Unknown,
+            parts:   Vec::new(),
+            bcoords: None
+        }
+    }
+
+    #[inline]
+    /// Creates a new `RayIntersection` on the given feature.
+    pub fn new_with_feature(toi: N, normal: V, uvs: Option<Pnt2<N>>, feature: FeatureId) -> RayIntersection<N, V> {
+        RayIntersection {
+            toi:     toi,
+            normal:  normal,
+            uvs:     uvs,
+            feature: feature,
+            parts:   Vec::new(),
+            bcoords: None
         }
     }
 }
@@ -79,6 +209,39 @@ pub trait LocalRayCast<N, P, V> {
         self.toi_and_normal_with_ray(ray, solid)
     }
 
+    /// Computes the time of impact between this shape and a ray, with the given options.
+    #[inline]
+    fn toi_with_ray_and_options(&self, ray: &Ray<P, V>, options: &RayCastOptions<N>) -> Option<N>
+        where N: Scalar {
+        match self.toi_with_ray(ray, options.solid()) {
+            Some(toi) => if options.accepts_toi(&toi) { Some(toi) } else { None },
+            None      => None
+        }
+    }
+
+    /// Computes the intersection point between this shape and a ray, with the given options.
+    #[inline]
+    fn toi_and_normal_with_ray_and_options(&self, ray: &Ray<P, V>, options: &RayCastOptions<N>)
+                                           -> Option<RayIntersection<N, V>>
+        where N: Scalar {
+        match self.toi_and_normal_with_ray(ray, options.solid()) {
+            Some(inter) => if options.accepts_toi(&inter.toi) { Some(inter) } else { None },
+            None        => None
+        }
+    }
+
+    /// Computes the intersection point, normal and texture coordinates between this shape and a
+    /// ray, with the given options.
+    #[inline]
+    fn toi_and_normal_and_uv_with_ray_and_options(&self, ray: &Ray<P, V>, options: &RayCastOptions<N>)
+                                                  -> Option<RayIntersection<N, V>>
+        where N: Scalar {
+        match self.toi_and_normal_and_uv_with_ray(ray, options.solid()) {
+            Some(inter) => if options.accepts_toi(&inter.toi) { Some(inter) } else { None },
+            None        => None
+        }
+    }
+
     /// Tests whether a ray intersects this shape.
     #[inline]
     fn intersects_ray(&self, ray: &Ray<P, V>) -> bool {
@@ -123,9 +286,261 @@ pub trait RayCast<N, P, V, M: Transform<P> + Rotate<V>>: LocalRayCast<N, P, V> {
         })
     }
 
+    /// Computes the time of impact between this transformed shape and a ray, with the given
+    /// options.
+    #[inline]
+    fn toi_with_transform_and_ray_and_options(&self, m: &M, ray: &Ray<P, V>, options: &RayCastOptions<N>)
+                                              -> Option<N>
+        where N: Scalar {
+        let ls_ray = Ray::new(m.inv_transform(&ray.orig), m.inv_rotate(&ray.dir));
+
+        self.toi_with_ray_and_options(&ls_ray, options)
+    }
+
+    /// Computes the time of impact, and normal between this transformed shape and a ray, with the
+    /// given options.
+    #[inline]
+    fn toi_and_normal_with_transform_and_ray_and_options(&self, m: &M, ray: &Ray<P, V>, options: &RayCastOptions<N>)
+                                                         -> Option<RayIntersection<N, V>>
+        where N: Scalar {
+        let ls_ray = Ray::new(m.inv_transform(&ray.orig), m.inv_rotate(&ray.dir));
+
+        self.toi_and_normal_with_ray_and_options(&ls_ray, options).map(|mut inter| {
+            inter.normal = m.rotate(&inter.normal);
+
+            inter
+        })
+    }
+
+    /// Computes time of impact, normal, and texture coordinates (uv) between this transformed
+    /// shape and a ray, with the given options.
+    #[inline]
+    fn toi_and_normal_and_uv_with_transform_and_ray_and_options(&self, m: &M, ray: &Ray<P, V>,
+                                                                options: &RayCastOptions<N>)
+                                                                -> Option<RayIntersection<N, V>>
+        where N: Scalar {
+        let ls_ray = Ray::new(m.inv_transform(&ray.orig), m.inv_rotate(&ray.dir));
+
+        self.toi_and_normal_and_uv_with_ray_and_options(&ls_ray, options).map(|mut inter| {
+            inter.normal = m.rotate(&inter.normal);
+
+            inter
+        })
+    }
+
     /// Tests whether a ray intersects this transformed shape.
     #[inline]
     fn intersects_with_transform_and_ray(&self, m: &M, ray: &Ray<P, V>) -> bool {
         self.toi_with_transform_and_ray(m, ray, true).is_some()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+    use na::{Pnt3, Vec3, Mat3, Iso3};
+    use na;
+    use bounding_volume::AABB;
+    use ray::{Ray, LocalRayCast, RayCastOptions, InsideMode, FeatureId};
+    use shape::{Ball, Triangle, Convex, Mesh3, Compound, CompoundData};
+
+    type TestCompoundData = CompoundData<f64, Pnt3<f64>, Vec3<f64>, Iso3<f64>, Mat3<f64>>;
+
+    fn ray_to(orig: Pnt3<f64>, target: Pnt3<f64>) -> Ray<Pnt3<f64>, Vec3<f64>> {
+        Ray::new(orig, na::normalize(&(target - orig)))
+    }
+
+    fn cube_points() -> Vec<Pnt3<f64>> {
+        // The i-th corner has positive coordinates along the axes set in the bitmask `i`.
+        range(0u, 8).map(|i| {
+            Pnt3::new(((i & 1) * 2) as f64 - 1.0, (((i >> 1) & 1) * 2) as f64 - 1.0, (((i >> 2) & 1) * 2) as f64 - 1.0)
+        }).collect()
+    }
+
+    fn options(max_toi: f64, cull_back_faces: bool, inside: InsideMode) -> RayCastOptions<f64> {
+        let mut res = RayCastOptions::new_with_max_toi(max_toi);
+
+        res.cull_back_faces = cull_back_faces;
+        res.inside          = inside;
+
+        res
+    }
+
+    #[test]
+    fn test_ray_cast_options_triangle() {
+        // Counterclockwise when seen from `z > 0`.
+        let tri   = Triangle::new(Pnt3::new(0.0f64, 0.0, 0.0), Pnt3::new(1.0, 0.0, 0.0), Pnt3::new(0.0, 1.0, 0.0));
+        let front = Ray::new(Pnt3::new(0.2f64, 0.2, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let back  = Ray::new(Pnt3::new(0.2f64, 0.2, -1.0), Vec3::new(0.0, 0.0, 1.0));
+
+        // Line of sight stopping before the triangle.
+        assert!(tri.toi_with_ray_and_options(&front, &RayCastOptions::new_with_max_toi(0.5)).is_none());
+        assert!(tri.toi_with_ray_and_options(&front, &RayCastOptions::new_with_max_toi(1.0)).is_none());
+        assert!(na::approx_eq(&tri.toi_with_ray_and_options(&front, &RayCastOptions::new_with_max_toi(2.0)).unwrap(), &1.0));
+
+        // Back-face culling.
+        let cull = options(10.0, true, InsideMode::Solid);
+
+        assert!(na::approx_eq(&tri.toi_with_ray_and_options(&front, &cull).unwrap(), &1.0));
+        assert!(tri.toi_with_ray_and_options(&back, &cull).is_none());
+        assert!(na::approx_eq(&tri.toi_with_ray_and_options(&back, &RayCastOptions::new()).unwrap(), &1.0));
+    }
+
+    #[test]
+    fn test_ray_cast_options_mesh() {
+        // Seen from `z > 0`, the triangle at `z = 0` is clockwise and the one at `z = -1` is
+        // counterclockwise.
+        let vertices = vec!(
+            Pnt3::new(0.0f64, 0.0, 0.0),  Pnt3::new(0.0, 1.0, 0.0),  Pnt3::new(1.0, 0.0, 0.0),
+            Pnt3::new(0.0f64, 0.0, -1.0), Pnt3::new(1.0, 0.0, -1.0), Pnt3::new(0.0, 1.0, -1.0));
+        let indices = vec!(0u, 1, 2, 3, 4, 5);
+        let mesh: Mesh3<f64> = Mesh3::new(Arc::new(vertices), Arc::new(indices), None, None);
+        let ray  = Ray::new(Pnt3::new(0.2f64, 0.2, 1.0), Vec3::new(0.0, 0.0, -1.0));
+
+        let cull = options(10.0, true, InsideMode::Solid);
+
+        assert!(na::approx_eq(&mesh.toi_with_ray_and_options(&ray, &RayCastOptions::new()).unwrap(), &1.0));
+        assert!(na::approx_eq(&mesh.toi_with_ray_and_options(&ray, &cull).unwrap(), &2.0));
+        assert!(mesh.toi_and_normal_with_ray_and_options(&ray, &cull).unwrap().feature == FeatureId::Face(1));
+
+        // Line of sight stopping between the two triangles.
+        assert!(na::approx_eq(&mesh.toi_with_ray_and_options(&ray, &RayCastOptions::new_with_max_toi(1.5)).unwrap(), &1.0));
+        assert!(mesh.toi_with_ray_and_options(&ray, &options(1.5, true, InsideMode::Solid)).is_none());
+        assert!(mesh.toi_with_ray_and_options(&ray, &RayCastOptions::new_with_max_toi(0.5)).is_none());
+
+        // The options reach the mesh through a compound shape.
+        let mut data: TestCompoundData = CompoundData::new();
+
+        data.push_shape(Iso3::new(na::zero(), na::zero()), mesh, 1.0);
+
+        let compound = Compound::new(data);
+
+        assert!(na::approx_eq(&compound.toi_with_ray_and_options(&ray, &cull).unwrap(), &2.0));
+        assert!(compound.toi_with_ray_and_options(&ray, &options(1.5, true, InsideMode::Solid)).is_none());
+    }
+
+    #[test]
+    fn test_ray_cast_options_inside_ball_and_compound() {
+        let ball = Ball::new(1.0f64);
+        let ray  = Ray::new(Pnt3::new(0.0f64, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+
+        let solid  = options(10.0, false, InsideMode::Solid);
+        let hollow = options(10.0, false, InsideMode::Hollow);
+        let ignore = options(10.0, false, InsideMode::Ignore);
+
+        assert!(na::approx_eq(&ball.toi_with_ray_and_options(&ray, &solid).unwrap(), &0.0));
+        assert!(na::approx_eq(&ball.toi_with_ray_and_options(&ray, &hollow).unwrap(), &1.0));
+        assert!(ball.toi_with_ray_and_options(&ray, &ignore).is_none());
+
+        // Two balls on the ray path, the first one containing the ray origin.
+        let mut data: TestCompoundData = CompoundData::new();
+
+        data.push_shape(Iso3::new(na::zero(), na::zero()), Ball::new(1.0f64), 1.0);
+        data.push_shape(Iso3::new(Vec3::new(5.0, 0.0, 0.0), na::zero()), Ball::new(1.0f64), 1.0);
+
+        let compound = Compound::new(data);
+
+        let inter = compound.toi_and_normal_with_ray_and_options(&ray, &solid).unwrap();
+        assert!(na::approx_eq(&inter.toi, &0.0) && inter.parts == vec!(0u));
+
+        let inter = compound.toi_and_normal_with_ray_and_options(&ray, &hollow).unwrap();
+        assert!(na::approx_eq(&inter.toi, &1.0) && inter.parts == vec!(0u));
+
+        let inter = compound.toi_and_normal_with_ray_and_options(&ray, &ignore).unwrap();
+        assert!(na::approx_eq(&inter.toi, &4.0) && inter.parts == vec!(1u));
+
+        let inter = compound.toi_and_normal_and_uv_with_ray_and_options(&ray, &ignore).unwrap();
+        assert!(na::approx_eq(&inter.toi, &4.0) && inter.parts == vec!(1u));
+
+        // The second ball is beyond the line of sight.
+        assert!(compound.toi_with_ray_and_options(&ray, &options(3.5, false, InsideMode::Ignore)).is_none());
+    }
+
+    #[test]
+    fn test_aabb_feature_id() {
+        let aabb  = AABB::new(Pnt3::new(-1.0f64, -1.0, -1.0), Pnt3::new(1.0, 1.0, 1.0));
+        let cases = [
+            // Faces: `Face(i)` on the positive side of the i-th axis, `Face(i + 3)` on the negative one.
+            (Pnt3::new(0.2f64, 0.3, 5.0),  Pnt3::new(0.2f64, 0.3, 0.0),  FeatureId::Face(2)),
+            (Pnt3::new(0.2f64, 0.3, -5.0), Pnt3::new(0.2f64, 0.3, 0.0),  FeatureId::Face(5)),
+            (Pnt3::new(-5.0f64, 0.3, 0.2), Pnt3::new(0.0f64, 0.3, 0.2),  FeatureId::Face(3)),
+            // Vertices: the bitmask of the positive sides.
+            (Pnt3::new(2.0f64, 2.0, 2.0),   Pnt3::new(1.0f64, 1.0, 1.0),   FeatureId::Vertex(7)),
+            (Pnt3::new(-2.0f64, -2.0, 2.0), Pnt3::new(-1.0f64, -1.0, 1.0), FeatureId::Vertex(4)),
+            // Edges parallel to the i-th axis: `Edge(4 * i + b)`.
+            (Pnt3::new(2.0f64, 2.0, 0.3),  Pnt3::new(1.0f64, 1.0, 0.3),  FeatureId::Edge(11)),
+            (Pnt3::new(0.3f64, -2.0, 2.0), Pnt3::new(0.3f64, -1.0, 1.0), FeatureId::Edge(2)),
+            (Pnt3::new(-2.0f64, 0.3, 2.0), Pnt3::new(-1.0f64, 0.3, 1.0), FeatureId::Edge(6))
+        ];
+
+        for &(orig, target, ref feature) in cases.iter() {
+            let inter = aabb.toi_and_normal_with_ray(&ray_to(orig, target), true).unwrap();
+
+            assert!(inter.feature == *feature);
+        }
+
+        // The ray starts inside of the AABB.
+        let inside = Ray::new(Pnt3::new(0.0f64, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert!(aabb.toi_and_normal_with_ray(&inside, true).unwrap().feature == FeatureId::Unknown);
+    }
+
+    #[test]
+    fn test_convex_feature_id() {
+        let convex = Convex::new(cube_points());
+        let n      = 8u;
+        let cases  = [
+            (Pnt3::new(2.0f64, 2.0, 2.0), Pnt3::new(1.0f64, 1.0, 1.0), FeatureId::Vertex(7)),
+            // The edge between the corners 6 and 7.
+            (Pnt3::new(0.3f64, 2.0, 2.0), Pnt3::new(0.3f64, 1.0, 1.0), FeatureId::Edge(6 * n + 7)),
+            // The face `z = 1` with the corners 4, 5, 6 and 7.
+            (Pnt3::new(0.2f64, 0.3, 5.0), Pnt3::new(0.2f64, 0.3, 1.0), FeatureId::Face((4 * n + 5) * n + 6))
+        ];
+
+        for &(orig, target, ref feature) in cases.iter() {
+            let inter = convex.toi_and_normal_with_ray(&ray_to(orig, target), true).unwrap();
+
+            assert!(inter.feature == *feature);
+        }
+    }
+
+    #[test]
+    fn test_nested_compound_parts() {
+        let mut inner: TestCompoundData = CompoundData::new();
+
+        inner.push_shape(Iso3::new(Vec3::new(0.0, -5.0, 0.0), na::zero()), Ball::new(1.0f64), 1.0);
+        inner.push_shape(Iso3::new(Vec3::new(2.0, 0.0, 0.0), na::zero()), Ball::new(1.0f64), 1.0);
+
+        let mut outer: TestCompoundData = CompoundData::new();
+
+        outer.push_shape(Iso3::new(Vec3::new(0.0, 5.0, 0.0), na::zero()), Ball::new(1.0f64), 1.0);
+        outer.push_shape(Iso3::new(Vec3::new(3.0, 0.0, 0.0), na::zero()), Compound::new(inner), 1.0);
+
+        let compound = Compound::new(outer);
+        let ray      = Ray::new(Pnt3::new(0.0f64, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let inter    = compound.toi_and_normal_with_ray(&ray, true).unwrap();
+
+        assert!(na::approx_eq(&inter.toi, &4.0));
+        assert!(inter.parts == vec!(1u, 1u));
+    }
+
+    #[test]
+    fn test_mesh_bcoords() {
+        let vertices = vec!(Pnt3::new(0.0f64, 0.0, 0.0), Pnt3::new(1.0, 0.0, 0.0),
+                            Pnt3::new(0.0, 1.0, 0.0), Pnt3::new(1.0, 1.0, 0.0));
+        let indices  = vec!(0u, 1, 2, 1, 3, 2);
+        let mesh: Mesh3<f64> = Mesh3::new(Arc::new(vertices), Arc::new(indices), None, None);
+
+        let ray   = Ray::new(Pnt3::new(0.2f64, 0.3, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let inter = mesh.toi_and_normal_with_ray(&ray, true).unwrap();
+
+        assert!(inter.feature == FeatureId::Face(0));
+        assert!(na::approx_eq(&inter.bcoords.unwrap(), &Vec3::new(0.5, 0.2, 0.3)));
+
+        // The second triangle is `(1, 3, 2)`.
+        let ray   = Ray::new(Pnt3::new(0.8f64, 0.6, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let inter = mesh.toi_and_normal_with_ray(&ray, true).unwrap();
+
+        assert!(inter.feature == FeatureId::Face(1));
+        assert!(na::approx_eq(&inter.bcoords.unwrap(), &Vec3::new(0.4, 0.4, 0.2)));
+    }
+}
@@ -1,7 +1,8 @@
 use std::mem;
+use std::num::Float;
 use na::{Transform, Rotate, Pnt2, Bounded};
 use na;
-use ray::{Ray, LocalRayCast, RayCast, RayIntersection};
+use ray::{Ray, LocalRayCast, RayCast, RayIntersection, FeatureId};
 use bounding_volume::AABB;
 use math::{Scalar, Point, Vect};
 
@@ -49,7 +50,11 @@ impl<N, P, V> LocalRayCast<N, P, V> for AABB<P>
 
     #[inline]
     fn toi_and_normal_with_ray(&self, ray: &Ray<P, V>, solid: bool) -> Option<RayIntersection<N, V>> {
-        ray_aabb(self, ray, solid).map(|(t, n, _)| RayIntersection::new(t, n))
+        ray_aabb(self, ray, solid).map(|(t, n, _)| {
+            let feature = aabb_feature_id(self, ray, t);
+
+            RayIntersection::new_with_feature(t, n, None, feature)
+        })
     }
 
     fn toi_and_normal_and_uv_with_ray(&self, ray: &Ray<P, V>, solid: bool) -> Option<RayIntersection<N, V>> {
@@ -73,24 +78,79 @@ fn do_toi_and_normal_and_uv_with_ray<N, P, V>(aabb: &AABB<P>, ray: &Ray<P, V>, s
     }
     else {
         ray_aabb(aabb, ray, solid).map(|(t, n, s)| {
-            let pt    = ray.orig + ray.dir * t;
-            let dpt   = pt - *aabb.mins();
-            let scale = *aabb.maxs() - *aabb.mins();
-            let id    = na::abs(&s);
+            let pt      = ray.orig + ray.dir * t;
+            let dpt     = pt - *aabb.mins();
+            let scale   = *aabb.maxs() - *aabb.mins();
+            let id      = na::abs(&s);
+            let feature = aabb_feature_id(aabb, ray, t);
 
             if id == 1 {
-                RayIntersection::new_with_uvs(t, n, Some(Pnt2::new(dpt[1] / scale[1], dpt[2] / scale[2])))
+                RayIntersection::new_with_feature(t, n, Some(Pnt2::new(dpt[1] / scale[1], dpt[2] / scale[2])), feature)
             }
             else if id == 2 {
-                RayIntersection::new_with_uvs(t, n, Some(Pnt2::new(dpt[2] / scale[2], dpt[0] / scale[0])))
+                RayIntersection::new_with_feature(t, n, Some(Pnt2::new(dpt[2] / scale[2], dpt[0] / scale[0])), feature)
             }
             else {
-                RayIntersection::new_with_uvs(t, n, Some(Pnt2::new(dpt[0] / scale[0], dpt[1] / scale[1])))
+                RayIntersection::new_with_feature(t, n, Some(Pnt2::new(dpt[0] / scale[0], dpt[1] / scale[1])), feature)
             }
         })
     }
 }
 
+/// Computes the feature of an AABB hit by a ray at the time of impact `toi`.
+///
+/// The features are numbered as follows, `dim` being the dimension of the space:
+///   * the face orthogonal to the i-th axis is `Face(i)` on the positive side and `Face(i + dim)`
+///   on the negative side.
+///   * the vertex with positive coordinates along the axes set in the bitmask `b` is `Vertex(b)`.
+///   * in 3d, the edge parallel to the i-th axis is `Edge(4 * i + b)` where `b` is the bitmask of
+///   the positive sides along the two other axes, in increasing order.
+fn aabb_feature_id<N, P, V>(aabb: &AABB<P>, ray: &Ray<P, V>, toi: N) -> FeatureId
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> {
+    let dim       = na::dim::<P>();
+    let pt        = ray.orig + ray.dir * toi;
+    let _eps: N   = Float::epsilon();
+    let tol       = _eps.sqrt() * na::norm(&(*aabb.maxs() - *aabb.mins()));
+    let mut nhits = 0u;
+    let mut free  = 0u;
+    let mut last  = 0u;
+    let mut signs = 0u;
+    let mut bit   = 0u;
+
+    for i in range(0, dim) {
+        if (pt[i] - aabb.maxs()[i]).abs() <= tol {
+            nhits = nhits + 1;
+            last  = i;
+            signs = signs | (1 << bit);
+            bit   = bit + 1;
+        }
+        else if (pt[i] - aabb.mins()[i]).abs() <= tol {
+            nhits = nhits + 1;
+            last  = i + dim;
+            bit   = bit + 1;
+        }
+        else {
+            free = i;
+        }
+    }
+
+    if nhits == 1 {
+        FeatureId::Face(last)
+    }
+    else if nhits == dim {
+        FeatureId::Vertex(signs)
+    }
+    else if nhits != 0 {
+        FeatureId::Edge(free * (1 << (dim - 1)) + signs)
+    }
+    else {
+        // The ray starts inside of the AABB.
+        FeatureId::Unknown
+    }
+}
+
 fn ray_aabb<N, P, V>(aabb: &AABB<P>, ray: &Ray<P, V>, solid: bool) -> Option<(N, V, int)>
     where N: Scalar,
           P: Point<N, V>,
@@ -0,0 +1,113 @@
+use std::sync::Arc;
+use ray::{Ray, LocalRayCast, RayIntersection};
+use utils;
+
+
+/// Casts several rays on the same shape, in parallel.
+///
+/// The rays are split among at most `nthreads` threads. The i-th result is the intersection of
+/// the i-th ray with the shape, with its normal and texture coordinates when available.
+pub fn cast_rays_parallel<N, P, V, S>(shape:    &Arc<S>,
+                                      rays:     &[Ray<P, V>],
+                                      solid:    bool,
+                                      nthreads: uint)
+                                      -> Vec<Option<RayIntersection<N, V>>>
+    where N: Send,
+          P: Send + Clone,
+          V: Send + Clone,
+          S: Send + Sync + LocalRayCast<N, P, V> {
+    let inputs = rays.iter().map(|ray| (ray.clone(), solid)).collect();
+
+    utils::parallel_map(shape, inputs, nthreads, cast_ray::<N, P, V, S>)
+}
+
+/// Computes the times of impact of several rays with the same shape, in parallel.
+///
+/// The rays are split among at most `nthreads` threads. The i-th result is the time of impact
+/// of the i-th ray.
+pub fn tois_with_rays_parallel<N, P, V, S>(shape:    &Arc<S>,
+                                           rays:     &[Ray<P, V>],
+                                           solid:    bool,
+                                           nthreads: uint)
+                                           -> Vec<Option<N>>
+    where N: Send,
+          P: Send + Clone,
+          V: Send + Clone,
+          S: Send + Sync + LocalRayCast<N, P, V> {
+    let inputs = rays.iter().map(|ray| (ray.clone(), solid)).collect();
+
+    utils::parallel_map(shape, inputs, nthreads, toi_with_ray::<N, P, V, S>)
+}
+
+fn cast_ray<N, P, V, S>(shape: &S, (ray, solid): (Ray<P, V>, bool)) -> Option<RayIntersection<N, V>>
+    where S: LocalRayCast<N, P, V> {
+    shape.toi_and_normal_and_uv_with_ray(&ray, solid)
+}
+
+fn toi_with_ray<N, P, V, S>(shape: &S, (ray, solid): (Ray<P, V>, bool)) -> Option<N>
+    where S: LocalRayCast<N, P, V> {
+    shape.toi_with_ray(&ray, solid)
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+    use std::rand::{IsaacRng, Rng};
+    use na::{Pnt3, Vec3};
+    use na;
+    use ray::{Ray, LocalRayCast};
+    use shape::Mesh3;
+
+    fn random_point(rng: &mut IsaacRng, scale: f64, offset: f64) -> Pnt3<f64> {
+        Pnt3::new(rng.gen::<f64>() * scale + offset, rng.gen::<f64>() * scale + offset, rng.gen::<f64>() * scale + offset)
+    }
+
+    // A random triangle soup, and random rays aimed at it.
+    fn mesh_and_rays(rng: &mut IsaacRng) -> (Arc<Vec<Pnt3<f64>>>, Arc<Vec<uint>>, Vec<Ray<Pnt3<f64>, Vec3<f64>>>) {
+        let vertices: Vec<Pnt3<f64>> = range(0u, 300).map(|_| random_point(rng, 10.0, 0.0)).collect();
+        let indices: Vec<uint> = range(0u, 300).collect();
+        let rays = range(0u, 200).map(|_| {
+            let orig = random_point(rng, 20.0, -5.0);
+            let dir  = na::normalize(&(random_point(rng, 10.0, 0.0) - orig));
+
+            Ray::new(orig, dir)
+        }).collect();
+
+        (Arc::new(vertices), Arc::new(indices), rays)
+    }
+
+    #[test]
+    fn test_parallel_ray_casts_match_sequential() {
+        let mut rng = IsaacRng::new_unseeded();
+        let (vertices, indices, rays) = mesh_and_rays(&mut rng);
+
+        let mesh: Mesh3<f64>     = Mesh3::new_sah(vertices.clone(), indices.clone(), None, None);
+        let par_mesh: Mesh3<f64> = Mesh3::new_parallel(vertices, indices, None, None, 4);
+        let par_mesh             = Arc::new(par_mesh);
+
+        let mut nhits = 0u;
+
+        for nthreads in [ 1u, 3, 8 ].iter() {
+            let inters = super::cast_rays_parallel(&par_mesh, rays.as_slice(), true, *nthreads);
+            let tois   = super::tois_with_rays_parallel(&par_mesh, rays.as_slice(), true, *nthreads);
+
+            assert!(inters.len() == rays.len() && tois.len() == rays.len());
+
+            for ((ray, inter), toi) in rays.iter().zip(inters.iter()).zip(tois.iter()) {
+                assert!(*toi == mesh.toi_with_ray(ray, true));
+
+                match (inter, &mesh.toi_and_normal_and_uv_with_ray(ray, true)) {
+                    (&Some(ref inter), &Some(ref expected)) => {
+                        assert!(inter.toi == expected.toi && inter.normal == expected.normal);
+                        assert!(inter.uvs == expected.uvs && inter.feature == expected.feature);
+                        nhits = nhits + 1;
+                    },
+                    (&None, &None) => { },
+                    _ => panic!("The parallel and sequential ray casts disagree.")
+                }
+            }
+        }
+
+        assert!(nhits > 0);
+    }
+}
@@ -1,13 +1,140 @@
-use na::{Transform, Rotate};
-use ray::{Ray, LocalRayCast, RayCast, RayIntersection};
+use std::num::Float;
+use na::{Identity, Pnt2, Transform, Rotate, Bounded};
+use na;
+use ray::{Ray, LocalRayCast, RayCast, RayIntersection, segment_ray_intersection};
 use shape::BezierCurve;
+use bounding_volume::{AABB, HasAABB, BoundingVolume};
+use math::{Scalar, Point, Vect};
 
-impl<N, P, V> LocalRayCast<N, P, V> for BezierCurve<P> {
-    fn toi_and_normal_with_ray(&self, _: &Ray<P, V>, _: bool) -> Option<RayIntersection<N, V>> {
-        // XXX: not yet implemented
-        None
+// Maximum number of subdivisions of the curve.
+static MAX_DEPTH: uint = 32;
+
+impl<N, P, V> LocalRayCast<N, P, V> for BezierCurve<P>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> {
+    fn toi_and_normal_with_ray(&self, ray: &Ray<P, V>, _: bool) -> Option<RayIntersection<N, V>> {
+        bezier_curve_ray_intersection(self, ray).map(|(mut inter, t)| {
+            inter.uvs = Some(Pnt2::new(t, na::zero()));
+
+            inter
+        })
     }
 }
 
-impl<N, P, V, M: Transform<P> + Rotate<V>> RayCast<N, P, V, M> for BezierCurve<P> {
+impl<N, P, V, M> RayCast<N, P, V, M> for BezierCurve<P>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Transform<P> + Rotate<V> {
+}
+
+/// Computes the intersection between a 2D bézier curve and a ray.
+///
+/// The curve is subdivided until its pieces are flat enough to be approximated by segments. If an
+/// intersection is found, the time of impact, the normal and the curve parameter of the
+/// intersection point are returned.
+pub fn bezier_curve_ray_intersection<N, P, V>(curve: &BezierCurve<P>, ray: &Ray<P, V>)
+                                              -> Option<(RayIntersection<N, V>, N)>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> {
+    // XXX: a ray almost never hits a curve in 3D.
+    if na::dim::<P>() != 2 || curve.npoints() < 2 {
+        return None;
+    }
+
+    let root_aabb: AABB<P> = curve.aabb(&Identity::new());
+    let _eps: N    = Float::epsilon();
+    let _0_5: N    = na::cast(0.5f64);
+    let tolerance  = na::norm(&(*root_aabb.maxs() - *root_aabb.mins())) * _eps.sqrt();
+
+    let mut best_toi: N = Bounded::max_value();
+    let mut result      = None;
+    // The pieces of the curve to be tested, with their parameter range and subdivision depth.
+    let mut stack       = vec!((curve.clone(), na::zero::<N>(), na::one::<N>(), 0u));
+
+    loop {
+        match stack.pop() {
+            None => break,
+            Some((piece, t0, t1, depth)) => {
+                let aabb: AABB<P> = piece.aabb(&Identity::new());
+
+                match aabb.loosened(tolerance).toi_with_ray(ray, true) {
+                    Some(toi) if toi < best_toi => { },
+                    _                           => continue
+                }
+
+                if depth == MAX_DEPTH || is_flat(piece.control_points(), tolerance) {
+                    let pts = piece.control_points();
+
+                    match segment_ray_intersection(&pts[0], &pts[pts.len() - 1], ray) {
+                        Some((inter, s)) => {
+                            if inter.toi < best_toi {
+                                best_toi = inter.toi;
+                                result   = Some((inter, t0 + (t1 - t0) * s));
+                            }
+                        },
+                        None => { }
+                    }
+                }
+                else {
+                    let mut left  = BezierCurve::new_with_degree(piece.degree());
+                    let mut right = BezierCurve::new_with_degree(piece.degree());
+                    let tmid      = (t0 + t1) * _0_5;
+
+                    piece.subdivide_at(_0_5, &mut left, &mut right);
+
+                    stack.push((right, tmid, t1, depth + 1));
+                    stack.push((left, t0, tmid, depth + 1));
+                }
+            }
+        }
+    }
+
+    result
+}
+
+// Tests if all the control points are closer than `tolerance` to the line joining the endpoints.
+fn is_flat<N, P, V>(pts: &[P], tolerance: N) -> bool
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> {
+    let a  = &pts[0];
+    let ab = pts[pts.len() - 1] - *a;
+    let l  = na::norm(&ab);
+
+    pts.slice(1, pts.len() - 1).iter().all(|pt| {
+        let ap = *pt - *a;
+
+        if na::is_zero(&l) {
+            na::norm(&ap) <= tolerance
+        }
+        else {
+            (ap[0] * ab[1] - ap[1] * ab[0]).abs() <= tolerance * l
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use na::{Pnt2, Vec2};
+    use na;
+    use ray::{Ray, LocalRayCast};
+    use shape::BezierCurve;
+
+    #[test]
+    fn test_ray_bezier_curve() {
+        // A parabola arc with its apex at (0, 0.5).
+        let curve = BezierCurve::new(vec!(Pnt2::new(-1.0f64, 0.0), Pnt2::new(0.0, 1.0), Pnt2::new(1.0, 0.0)));
+        let ray   = Ray::new(Pnt2::new(0.0f64, 2.0), Vec2::new(0.0, -1.0));
+        let inter = curve.toi_and_normal_with_ray(&ray, true).unwrap();
+
+        assert!(na::approx_eq_eps(&inter.toi, &1.5, &1.0e-6));
+        assert!(na::approx_eq_eps(&inter.uvs.unwrap().x, &0.5, &1.0e-6));
+        assert!(na::approx_eq_eps(&inter.normal, &Vec2::new(0.0, 1.0), &1.0e-6));
+
+        let miss = Ray::new(Pnt2::new(2.0f64, 2.0), Vec2::new(0.0, -1.0));
+        assert!(curve.toi_with_ray(&miss, true).is_none());
+    }
 }
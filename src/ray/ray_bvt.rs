@@ -1,5 +1,5 @@
 use na::{Transform, Rotate};
-use ray::{Ray, LocalRayCast, RayCast, RayIntersection};
+use ray::{Ray, LocalRayCast, RayCast, RayIntersection, RayCastOptions};
 use partitioning::BVT;
 use math::Scalar;
 
@@ -35,6 +35,38 @@ impl<N, P, V, B, BV> LocalRayCast<N, P, V> for BVT<B, BV>
                     |(_, res, _)| res)
     }
 
+    #[inline]
+    fn toi_with_ray_and_options(&self, ray: &Ray<P, V>, options: &RayCastOptions<N>) -> Option<N> {
+        self.cast_ray_with_max_toi(
+            ray,
+            options.max_toi.clone(),
+            &mut |b, r| b.toi_with_ray_and_options(r, options).map(
+                |t| (t.clone(), t))).map(
+                    |(_, res, _)| res)
+    }
+
+    #[inline]
+    fn toi_and_normal_with_ray_and_options(&self, ray: &Ray<P, V>, options: &RayCastOptions<N>)
+                                           -> Option<RayIntersection<N, V>> {
+        self.cast_ray_with_max_toi(
+            ray,
+            options.max_toi.clone(),
+            &mut |b, r| b.toi_and_normal_with_ray_and_options(r, options).map(
+                |inter| (inter.toi.clone(), inter))).map(
+                    |(_, res, _)| res)
+    }
+
+    #[inline]
+    fn toi_and_normal_and_uv_with_ray_and_options(&self, ray: &Ray<P, V>, options: &RayCastOptions<N>)
+                                                  -> Option<RayIntersection<N, V>> {
+        self.cast_ray_with_max_toi(
+            ray,
+            options.max_toi.clone(),
+            &mut |b, r| b.toi_and_normal_and_uv_with_ray_and_options(r, options).map(
+                |inter| (inter.toi.clone(), inter))).map(
+                    |(_, res, _)| res)
+    }
+
     // FIXME: optimize insersect_ray ?
 }
 
@@ -1,8 +1,7 @@
 use na::{Translate, Bounded};
-use na;
-use ray::{Ray, LocalRayCast, RayCast, RayIntersection};
-use partitioning::RayInterferencesCollector;
-use shape::{ConcaveShape, Compound};
+use ray::{Ray, LocalRayCast, RayCast, RayIntersection, RayCastOptions, RayPacket, LocalRayPacketCast,
+          RayPacketCast};
+use shape::{ConcaveShape, Compound, Shape};
 use math::{Scalar, Point, Vect, Isometry};
 
 
@@ -13,72 +12,100 @@ impl<N, P, V, M> LocalRayCast<N, P, V> for Compound<N, P, V, M>
           V: Vect<N> + Translate<P>,
           M: Isometry<N, P, V> {
     fn toi_with_ray(&self, ray: &Ray<P, V>, solid: bool) -> Option<N> {
-        // FIXME: optimize that and avoid the allocation using the dedicated ray casting function
-        // from the BVT.
-        let mut interferences: Vec<uint> = Vec::new();
+        self.bvt().cast_ray(ray, &mut |i, r| {
+            self.map_part_at(*i, |objm, obj| obj.toi_with_transform_and_ray(objm, r, solid).map(|t| (t, ())))
+        }).map(|(toi, _, _)| toi)
+    }
 
-        {
-            let mut visitor = RayInterferencesCollector::new(ray, &mut interferences);
-            self.bvt().visit(&mut visitor);
-        }
+    fn toi_and_normal_with_ray(&self, ray: &Ray<P, V>, solid: bool) -> Option<RayIntersection<N, V>> {
+        cast_ray_on_parts(self, ray, Bounded::max_value(), |objm, obj, r| {
+            obj.toi_and_normal_with_transform_and_ray(objm, r, solid)
+        })
+    }
 
-        // compute the minimum toi
-        let mut toi: N = Bounded::max_value();
+    fn toi_and_normal_and_uv_with_ray(&self, ray: &Ray<P, V>, solid: bool) -> Option<RayIntersection<N, V>> {
+        cast_ray_on_parts(self, ray, Bounded::max_value(), |objm, obj, r| {
+            obj.toi_and_normal_and_uv_with_transform_and_ray(objm, r, solid)
+        })
+    }
 
-        for i in interferences.iter() {
-            self.map_part_at(*i, |objm, obj|
-                          match obj.toi_with_transform_and_ray(objm, ray, solid) {
-                              None        => { },
-                              Some(ref t) => toi = toi.min(*t)
-                          }
-                         );
-        }
+    // NOTE: the options are applied on each part so that a part containing the ray origin does
+    // not hide the other ones with the `Ignore` mode.
+    fn toi_with_ray_and_options(&self, ray: &Ray<P, V>, options: &RayCastOptions<N>) -> Option<N> {
+        self.bvt().cast_ray_with_max_toi(ray, options.max_toi.clone(), &mut |i, r| {
+            self.map_part_at(*i, |objm, obj| {
+                obj.toi_with_transform_and_ray_and_options(objm, r, options).map(|t| (t, ()))
+            })
+        }).map(|(toi, _, _)| toi)
+    }
 
-        if toi == Bounded::max_value() {
-            None
-        }
-        else {
-            Some(toi)
-        }
+    fn toi_and_normal_with_ray_and_options(&self, ray: &Ray<P, V>, options: &RayCastOptions<N>)
+                                           -> Option<RayIntersection<N, V>> {
+        cast_ray_on_parts(self, ray, options.max_toi.clone(), |objm, obj, r| {
+            obj.toi_and_normal_with_transform_and_ray_and_options(objm, r, options)
+        })
     }
 
-    fn toi_and_normal_with_ray(&self, ray: &Ray<P, V>, solid: bool) -> Option<RayIntersection<N, V>> {
-        let mut interferences: Vec<uint> = Vec::new();
+    fn toi_and_normal_and_uv_with_ray_and_options(&self, ray: &Ray<P, V>, options: &RayCastOptions<N>)
+                                                  -> Option<RayIntersection<N, V>> {
+        cast_ray_on_parts(self, ray, options.max_toi.clone(), |objm, obj, r| {
+            obj.toi_and_normal_and_uv_with_transform_and_ray_and_options(objm, r, options)
+        })
+    }
+}
 
-        {
-            let mut visitor = RayInterferencesCollector::new(ray, &mut interferences);
-            self.bvt().visit(&mut visitor);
-        }
+// Finds the closest intersection before `max_toi` between a ray and the parts of a compound
+// shape, prepending the index of the hit part to the intersection `parts`.
+fn cast_ray_on_parts<N, P, V, M>(compound: &Compound<N, P, V, M>,
+                                 ray:      &Ray<P, V>,
+                                 max_toi:  N,
+                                 cast_fn:  |&M, &Shape<N, P, V, M>, &Ray<P, V>| -> Option<RayIntersection<N, V>>)
+                                 -> Option<RayIntersection<N, V>>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> + Translate<P>,
+          M: Isometry<N, P, V> {
+    compound.bvt().cast_ray_with_max_toi(ray, max_toi, &mut |i, r| {
+        compound.map_part_at(*i, |objm, obj| {
+            cast_fn(objm, obj, r).map(|mut inter| {
+                inter.parts.insert(0, *i);
 
-        // compute the minimum toi
-        let mut best = RayIntersection::new(Bounded::max_value(), na::zero());
+                (inter.toi.clone(), inter)
+            })
+        })
+    }).map(|(_, inter, _)| inter)
+}
 
-        for i in interferences.iter() {
-            self.map_part_at(*i, |objm, obj|
-                          match obj.toi_and_normal_with_transform_and_ray(objm, ray, solid) {
-                              None        => { },
-                              Some(inter) => {
-                                  if inter.toi < best.toi {
-                                      best = inter
-                                  }
-                              }
-                          }
-                         );
-        }
+impl<N, P, V, M> RayCast<N, P, V, M> for Compound<N, P, V, M>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> + Translate<P>,
+          M: Isometry<N, P, V> {
+}
 
-        if best.toi == Bounded::max_value() {
-            None
-        }
-        else {
-            Some(best)
-        }
-    }
+impl<N, P, V, M> LocalRayPacketCast<N, P, V> for Compound<N, P, V, M>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> + Translate<P>,
+          M: Isometry<N, P, V> {
+    fn toi_and_normal_with_ray_packet(&self, packet: &RayPacket<P, V>, solid: bool)
+                                      -> Vec<Option<RayIntersection<N, V>>> {
+        // FIXME: use the packet traversal of the parts themselves when they support it.
+        let res = self.bvt().cast_ray_packet(packet, &mut |i, ray| {
+            self.map_part_at(*i, |objm, obj| {
+                obj.toi_and_normal_with_transform_and_ray(objm, ray, solid).map(|mut inter| {
+                    inter.parts.insert(0, *i);
 
-    // XXX: we have to implement toi_and_normal_and_uv_with_ray! Otherwise, no uv will be computed
-    // for any of the sub-shapes.
+                    (inter.toi.clone(), inter)
+                })
+            })
+        });
+
+        res.into_iter().map(|inter| inter.map(|(_, inter, _)| inter)).collect()
+    }
 }
 
-impl<N, P, V, M> RayCast<N, P, V, M> for Compound<N, P, V, M>
+impl<N, P, V, M> RayPacketCast<N, P, V, M> for Compound<N, P, V, M>
     where N: Scalar,
           P: Point<N, V>,
           V: Vect<N> + Translate<P>,
@@ -0,0 +1,188 @@
+use na::{Transform, Rotate, Bounded};
+use na;
+use ray::{Ray, LocalRayCast, RayCast, RayIntersection};
+use bounding_volume::{KDOP, KDOPDirections};
+use math::{Scalar, Point, Vect};
+
+
+impl<N, P, V, D> LocalRayCast<N, P, V> for KDOP<N, V, D>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          D: KDOPDirections<V> {
+    #[inline]
+    fn toi_with_ray(&self, ray: &Ray<P, V>, solid: bool) -> Option<N> {
+        ray_kdop(self, ray, solid).map(|(t, _)| t)
+    }
+
+    #[inline]
+    fn toi_and_normal_with_ray(&self, ray: &Ray<P, V>, solid: bool) -> Option<RayIntersection<N, V>> {
+        ray_kdop(self, ray, solid).map(|(t, n)| RayIntersection::new(t, n))
+    }
+}
+
+impl<N, P, V, M, D> RayCast<N, P, V, M> for KDOP<N, V, D>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Transform<P> + Rotate<V>,
+          D: KDOPDirections<V> {
+}
+
+// Slab test generalized to the directions of the k-DOP.
+fn ray_kdop<N, P, V, D>(kdop: &KDOP<N, V, D>, ray: &Ray<P, V>, solid: bool) -> Option<(N, V)>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          D: KDOPDirections<V> {
+    let mut tmax: N       = Bounded::max_value();
+    let mut tmin: N       = -tmax;
+    let mut near_normal   = na::zero::<V>();
+    let mut far_normal    = na::zero::<V>();
+
+    for i in range(0, kdop.directions().ndirections()) {
+        let dir   = kdop.directions().direction(i);
+        let orig  = na::dot(ray.orig.as_vec(), &dir);
+        let speed = na::dot(&ray.dir, &dir);
+
+        if na::is_zero(&speed) {
+            if orig < kdop.mins()[i] || orig > kdop.maxs()[i] {
+                return None
+            }
+        }
+        else {
+            let _1: N = na::one();
+            let denom = _1 / speed;
+            let inter_with_min_plane = (kdop.mins()[i] - orig) * denom;
+            let inter_with_max_plane = (kdop.maxs()[i] - orig) * denom;
+
+            // The normals are oriented toward the ray origin.
+            let (inter_with_near_plane, inter_with_far_plane, n_near, n_far) =
+                if inter_with_min_plane <= inter_with_max_plane {
+                    (inter_with_min_plane, inter_with_max_plane, -dir, -dir)
+                }
+                else {
+                    (inter_with_max_plane, inter_with_min_plane, dir.clone(), dir)
+                };
+
+            if inter_with_near_plane > tmin {
+                tmin        = inter_with_near_plane;
+                near_normal = n_near;
+            }
+
+            if inter_with_far_plane < tmax {
+                tmax       = inter_with_far_plane;
+                far_normal = n_far;
+            }
+
+            if tmin > tmax {
+                return None;
+            }
+        }
+    }
+
+    if tmax < na::zero() {
+        None
+    }
+    else if tmin < na::zero() {
+        // the ray starts inside of the k-DOP
+        if solid {
+            Some((na::zero(), na::zero()))
+        }
+        else {
+            Some((tmax, far_normal))
+        }
+    }
+    else {
+        Some((tmin, near_normal))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::rand::{IsaacRng, Rng};
+    use na::{Pnt3, Iso3, Vec3};
+    use na;
+    use bounding_volume::{KDOP, KDOPDirections, DOP14, DOP26, BoundingVolume};
+    use bounding_volume;
+    use point::LocalPointQuery;
+    use ray::{Ray, LocalRayCast};
+
+    fn random_point(rng: &mut IsaacRng, min: f64, max: f64) -> Pnt3<f64> {
+        let d = max - min;
+
+        Pnt3::new(rng.gen::<f64>() * d + min, rng.gen::<f64>() * d + min, rng.gen::<f64>() * d + min)
+    }
+
+    fn check_against_brute_force<D: KDOPDirections<Vec3<f64>>>(rng: &mut IsaacRng, directions: D) {
+        let m: Iso3<f64> = na::one();
+        let pts: Vec<Pnt3<f64>> = range(0u, 10).map(|_| random_point(rng, 0.0, 4.0)).collect();
+        let kdop: KDOP<f64, Vec3<f64>, D> = bounding_volume::point_cloud_kdop(&m, pts.as_slice(), &directions);
+        let loose = kdop.loosened(1.0e-7);
+        let step  = 0.01f64;
+
+        for i in range(0u, 200) {
+            let orig = random_point(rng, -4.0, 8.0);
+            // Half of the rays are aimed at the k-DOP.
+            let target = if i % 2 == 0 { random_point(rng, 0.0, 4.0) } else { random_point(rng, -20.0, 20.0) };
+            let ray    = Ray::new(orig, na::normalize(&(target - orig)));
+
+            // The first sample of the ray inside of the k-DOP.
+            let first_inside = range(0u, 3000).map(|k| k as f64 * step)
+                                              .find(|t| kdop.contains_point(&(ray.orig + ray.dir * *t)));
+
+            match kdop.toi_and_normal_with_ray(&ray, true) {
+                Some(inter) => {
+                    let hit = ray.orig + ray.dir * inter.toi;
+
+                    assert!(loose.contains_point(&hit));
+
+                    if kdop.contains_point(&ray.orig) {
+                        assert!(inter.toi == 0.0);
+                        continue;
+                    }
+
+                    // No point before the hit is inside of the k-DOP.
+                    match first_inside {
+                        Some(t) => assert!(t >= inter.toi - 1.0e-7 && t <= inter.toi + step),
+                        None    => { }
+                    }
+
+                    // The normal is the one of a face containing the hit point, facing the ray.
+                    assert!(na::dot(&inter.normal, &ray.dir) <= 0.0);
+                    assert!(range(0, directions.ndirections()).any(|k| {
+                        let dir  = directions.direction(k);
+                        let proj = na::dot(hit.as_vec(), &dir);
+
+                        (inter.normal == -dir && na::approx_eq_eps(&proj, &kdop.mins()[k], &1.0e-7)) ||
+                        (inter.normal == dir  && na::approx_eq_eps(&proj, &kdop.maxs()[k], &1.0e-7))
+                    }));
+                },
+                None => assert!(first_inside.is_none())
+            }
+        }
+
+        // From the inside, a non-solid ray cast hits the boundary.
+        let center = pts.iter().fold(na::orig::<Pnt3<f64>>(), |c, p| c + *p.as_vec() / 10.0);
+
+        for _ in range(0u, 20) {
+            let ray = Ray::new(center, na::normalize(&(random_point(rng, -1.0, 1.0) - na::orig())));
+            let toi = kdop.toi_with_ray(&ray, false).unwrap();
+            let hit = ray.orig + ray.dir * toi;
+
+            assert!(toi > 0.0);
+            assert!(loose.contains_point(&hit));
+            assert!(!kdop.contains_point(&(hit + ray.dir * 1.0e-5)));
+        }
+    }
+
+    #[test]
+    fn test_kdop_ray_casts_against_brute_force() {
+        let mut rng = IsaacRng::new_unseeded();
+
+        for _ in range(0u, 5) {
+            check_against_brute_force(&mut rng, DOP14);
+            check_against_brute_force(&mut rng, DOP26);
+        }
+    }
+}
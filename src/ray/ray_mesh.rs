@@ -1,6 +1,7 @@
 use na::{Pnt2, Vec3, Transform, Rotate};
 use na;
-use ray::{Ray, LocalRayCast, RayCast, RayIntersection};
+use ray::{Ray, LocalRayCast, RayCast, RayIntersection, RayCastOptions, FeatureId, RayPacket,
+          LocalRayPacketCast, RayPacketCast};
 use ray;
 use shape::{Mesh, MeshElement};
 use bounding_volume::AABB;
@@ -17,14 +18,22 @@ impl<N, P, V, E> LocalRayCast<N, P, V> for Mesh<N, P, V, E>
     fn toi_with_ray(&self, ray: &Ray<P, V>, _: bool) -> Option<N> {
         let mut cost_fn = MeshRayToiCostFn { mesh: self, ray: ray };
 
-        self.bvt().best_first_search(&mut cost_fn).map(|(_, res)| res)
+        self.flat_bvt().best_first_search(&mut cost_fn).map(|(_, res)| res)
     }
 
     #[inline]
     fn toi_and_normal_with_ray(&self, ray: &Ray<P, V>, _: bool) -> Option<RayIntersection<N, V>> {
-        let mut cost_fn = MeshRayToiAndNormalCostFn { mesh: self, ray: ray };
+        if na::dim::<P>() == 3 {
+            // Use the triangle intersection directly to get the barycentric coordinates too.
+            let mut cost_fn = MeshRayToiAndNormalAndUVsCostFn { mesh: self, ray: ray };
 
-        self.bvt().best_first_search(&mut cost_fn).map(|(_, res)| res)
+            self.flat_bvt().best_first_search(&mut cost_fn).map(|(_, res)| res.val0())
+        }
+        else {
+            let mut cost_fn = MeshRayToiAndNormalCostFn { mesh: self, ray: ray };
+
+            self.flat_bvt().best_first_search(&mut cost_fn).map(|(_, res)| res)
+        }
     }
 
     fn toi_and_normal_and_uv_with_ray(&self, ray: &Ray<P, V>, solid: bool) -> Option<RayIntersection<N, V>> {
@@ -33,53 +42,132 @@ impl<N, P, V, E> LocalRayCast<N, P, V> for Mesh<N, P, V, E>
         }
 
         let mut cost_fn = MeshRayToiAndNormalAndUVsCostFn { mesh: self, ray: ray };
-        let cast = self.bvt().best_first_search(&mut cost_fn);
+        let cast = self.flat_bvt().best_first_search(&mut cost_fn);
+
+        match cast {
+            None                    => None,
+            Some((best, (res, uv))) => Some(self.interpolate_uvs_and_normal(*best, res, &uv, ray))
+        }
+    }
+
+    #[inline]
+    fn toi_with_ray_and_options(&self, ray: &Ray<P, V>, options: &RayCastOptions<N>) -> Option<N> {
+        self.toi_and_normal_with_ray_and_options(ray, options).map(|inter| inter.toi)
+    }
+
+    fn toi_and_normal_with_ray_and_options(&self, ray: &Ray<P, V>, options: &RayCastOptions<N>)
+                                           -> Option<RayIntersection<N, V>> {
+        let mut cost_fn = MeshRayOptionsCostFn { mesh: self, ray: ray, options: options };
+
+        self.flat_bvt().best_first_search_with_max_cost(&mut cost_fn, options.max_toi.clone()).map(|(_, res)| res)
+    }
+
+    fn toi_and_normal_and_uv_with_ray_and_options(&self, ray: &Ray<P, V>, options: &RayCastOptions<N>)
+                                                  -> Option<RayIntersection<N, V>> {
+        if self.uvs().is_none() || na::dim::<P>() != 3 {
+            return self.toi_and_normal_with_ray_and_options(ray, options);
+        }
+
+        let mut cost_fn = MeshRayOptionsCostFn { mesh: self, ray: ray, options: options };
+        let cast = self.flat_bvt().best_first_search_with_max_cost(&mut cost_fn, options.max_toi.clone());
 
         match cast {
-            None                => None,
-            Some((best, inter)) => {
-                let toi = inter.ref0().toi;
-                let n   = inter.ref0().normal.clone();
-                let uv  = inter.val1(); // barycentric coordinates to compute the exact uvs.
-
-                let ibest = *best * 3;
-                let is    = self.indices().slice(ibest, ibest + 3);
-                let uvs   = self.uvs().as_ref().unwrap();
-
-                let uv1 = uvs.deref()[is[0]];
-                let uv2 = uvs.deref()[is[1]];
-                let uv3 = uvs.deref()[is[2]];
-
-                let uvx = uv1.x * uv.x + uv2.x * uv.y + uv3.x * uv.z;
-                let uvy = uv1.y * uv.x + uv2.y * uv.y + uv3.y * uv.z;
-
-                // XXX: this interpolation should be done on the two other ray cast too!
-                match *self.normals() {
-                    None         => {
-                        Some(RayIntersection::new_with_uvs(toi, n, Some(Pnt2::new(uvx, uvy))))
-                    },
-                    Some(ref ns) => {
-                        let n1 = &ns.deref()[is[0]];
-                        let n2 = &ns.deref()[is[1]];
-                        let n3 = &ns.deref()[is[2]];
-
-                        let mut n123 = *n1 * uv.x + *n2 * uv.y + *n3 * uv.z;
-
-                        if na::is_zero(&n123.normalize()) {
-                            Some(RayIntersection::new_with_uvs(toi, n, Some(Pnt2::new(uvx, uvy))))
-                        }
-                        else {
-                            if na::dot(&n123, &ray.dir) > na::zero() {
-                                Some(RayIntersection::new_with_uvs(toi, -n123, Some(Pnt2::new(uvx, uvy))))
-                            }
-                            else {
-                                Some(RayIntersection::new_with_uvs(toi, n123, Some(Pnt2::new(uvx, uvy))))
-                            }
-                        }
+            None              => None,
+            Some((best, res)) => {
+                let uv = res.bcoords.clone().unwrap();
+
+                Some(self.interpolate_uvs_and_normal(*best, res, &uv, ray))
+            }
+        }
+    }
+}
+
+impl<N, P, V, E> LocalRayPacketCast<N, P, V> for Mesh<N, P, V, E>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          E: MeshElement<P> + LocalRayCast<N, P, V> {
+    fn toi_and_normal_with_ray_packet(&self, packet: &RayPacket<P, V>, _: bool)
+                                      -> Vec<Option<RayIntersection<N, V>>> {
+        let res = self.flat_bvt().cast_ray_packet(packet, &mut |b, ray| {
+            let id = *b;
+
+            if na::dim::<P>() == 3 {
+                let vs = self.vertices().as_slice();
+                let i  = id * 3;
+                let is = self.indices().slice(i, i + 3);
+
+                ray::triangle_ray_intersection(&vs[is[0]], &vs[is[1]], &vs[is[2]], ray).map(|(mut inter, bcoords)| {
+                    inter.feature = FeatureId::Face(id);
+                    inter.bcoords = Some(bcoords);
+
+                    (inter.toi.clone(), inter)
+                })
+            }
+            else {
+                self.element_at(id).toi_and_normal_with_ray(ray, true).map(|mut inter| {
+                    inter.feature = FeatureId::Face(id);
+
+                    (inter.toi.clone(), inter)
+                })
+            }
+        });
+
+        res.into_iter().map(|inter| inter.map(|(_, inter, _)| inter)).collect()
+    }
+}
+
+impl<N, P, V, E, M> RayPacketCast<N, P, V, M> for Mesh<N, P, V, E>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Transform<P> + Rotate<V>,
+          E: MeshElement<P> + LocalRayCast<N, P, V> {
+}
+
+impl<N, P, V, E> Mesh<N, P, V, E>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          E: MeshElement<P> {
+    // `uv` are the barycentric coordinates used to compute the exact uvs.
+    fn interpolate_uvs_and_normal(&self, best: uint, mut res: RayIntersection<N, V>, uv: &Vec3<N>,
+                                  ray: &Ray<P, V>) -> RayIntersection<N, V> {
+        let ibest = best * 3;
+        let is    = self.indices().slice(ibest, ibest + 3);
+        let uvs   = self.uvs().as_ref().unwrap();
+
+        let uv1 = uvs.deref()[is[0]];
+        let uv2 = uvs.deref()[is[1]];
+        let uv3 = uvs.deref()[is[2]];
+
+        let uvx = uv1.x * uv.x + uv2.x * uv.y + uv3.x * uv.z;
+        let uvy = uv1.y * uv.x + uv2.y * uv.y + uv3.y * uv.z;
+
+        res.uvs = Some(Pnt2::new(uvx, uvy));
+
+        // XXX: this interpolation should be done on the two other ray cast too!
+        match *self.normals() {
+            None         => { },
+            Some(ref ns) => {
+                let n1 = &ns.deref()[is[0]];
+                let n2 = &ns.deref()[is[1]];
+                let n3 = &ns.deref()[is[2]];
+
+                let mut n123 = *n1 * uv.x + *n2 * uv.y + *n3 * uv.z;
+
+                if !na::is_zero(&n123.normalize()) {
+                    if na::dot(&n123, &ray.dir) > na::zero() {
+                        res.normal = -n123;
+                    }
+                    else {
+                        res.normal = n123;
                     }
                 }
             }
         }
+
+        res
     }
 }
 
@@ -134,7 +222,11 @@ for MeshRayToiAndNormalCostFn<'a, N, P, V, E>
 
     #[inline]
     fn compute_b_cost(&mut self, b: &uint) -> Option<(N, RayIntersection<N, V>)> {
-        self.mesh.element_at(*b).toi_and_normal_with_ray(self.ray, true).map(|inter| (inter.toi, inter))
+        self.mesh.element_at(*b).toi_and_normal_with_ray(self.ray, true).map(|mut inter| {
+            inter.feature = FeatureId::Face(*b);
+
+            (inter.toi, inter)
+        })
     }
 }
 
@@ -157,13 +249,63 @@ for MeshRayToiAndNormalAndUVsCostFn<'a, N, P, V, E>
     #[inline]
     fn compute_b_cost(&mut self, b: &uint) -> Option<(N, (RayIntersection<N, V>, Vec3<N>))> {
         let vs = self.mesh.vertices().as_slice();
-        let i  = *b * 3;
+        let id = *b;
+        let i  = id * 3;
         let is = self.mesh.indices().slice(i, i + 3);
 
         let a = &vs[is[0]];
         let b = &vs[is[1]];
         let c = &vs[is[2]];
 
-        ray::triangle_ray_intersection(a, b, c, self.ray).map(|inter| (inter.ref0().toi.clone(), inter))
+        ray::triangle_ray_intersection(a, b, c, self.ray).map(|(mut inter, bcoords)| {
+            inter.feature = FeatureId::Face(id);
+            inter.bcoords = Some(bcoords.clone());
+
+            (inter.toi.clone(), (inter, bcoords))
+        })
+    }
+}
+
+struct MeshRayOptionsCostFn<'a, N: 'a, P: 'a, V: 'a, E: 'a> {
+    mesh:    &'a Mesh<N, P, V, E>,
+    ray:     &'a Ray<P, V>,
+    options: &'a RayCastOptions<N>
+}
+
+impl<'a, N, P, V, E> BVTCostFn<N, uint, AABB<P>, RayIntersection<N, V>>
+for MeshRayOptionsCostFn<'a, N, P, V, E>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          E: MeshElement<P> + LocalRayCast<N, P, V> {
+    #[inline]
+    fn compute_bv_cost(&mut self, aabb: &AABB<P>) -> Option<N> {
+        aabb.toi_with_ray(self.ray, true)
+    }
+
+    #[inline]
+    fn compute_b_cost(&mut self, b: &uint) -> Option<(N, RayIntersection<N, V>)> {
+        let id = *b;
+
+        if na::dim::<P>() == 3 {
+            let vs = self.mesh.vertices().as_slice();
+            let i  = id * 3;
+            let is = self.mesh.indices().slice(i, i + 3);
+
+            ray::triangle_ray_intersection_with_options(&vs[is[0]], &vs[is[1]], &vs[is[2]], self.ray, self.options)
+                .map(|(mut inter, bcoords)| {
+                    inter.feature = FeatureId::Face(id);
+                    inter.bcoords = Some(bcoords);
+
+                    (inter.toi.clone(), inter)
+                })
+        }
+        else {
+            self.mesh.element_at(id).toi_and_normal_with_ray_and_options(self.ray, self.options).map(|mut inter| {
+                inter.feature = FeatureId::Face(id);
+
+                (inter.toi.clone(), inter)
+            })
+        }
     }
 }
@@ -0,0 +1,35 @@
+use na::{Transform, Rotate};
+use bounding_volume::OBB;
+use shape::Cuboid;
+use ray::{Ray, LocalRayCast, RayCast, RayIntersection};
+use math::{Scalar, Point, Vect, Isometry};
+
+
+impl<N, P, V, M> LocalRayCast<N, P, V> for OBB<V, M>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Isometry<N, P, V> {
+    #[inline]
+    fn toi_with_ray(&self, ray: &Ray<P, V>, solid: bool) -> Option<N> {
+        Cuboid::new(self.half_extents().clone()).toi_with_transform_and_ray(self.transform(), ray, solid)
+    }
+
+    #[inline]
+    fn toi_and_normal_with_ray(&self, ray: &Ray<P, V>, solid: bool) -> Option<RayIntersection<N, V>> {
+        Cuboid::new(self.half_extents().clone()).toi_and_normal_with_transform_and_ray(self.transform(), ray, solid)
+    }
+
+    #[inline]
+    fn toi_and_normal_and_uv_with_ray(&self, ray: &Ray<P, V>, solid: bool) -> Option<RayIntersection<N, V>> {
+        Cuboid::new(self.half_extents().clone()).toi_and_normal_and_uv_with_transform_and_ray(self.transform(), ray, solid)
+    }
+}
+
+impl<N, P, V, M, M2> RayCast<N, P, V, M2> for OBB<V, M>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Isometry<N, P, V>,
+          M2: Transform<P> + Rotate<V> {
+}
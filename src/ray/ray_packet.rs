@@ -0,0 +1,189 @@
+//! Packets of coherent rays.
+
+use na::{Transform, Rotate, Bounded};
+use na;
+use ray::{Ray, LocalRayCast, RayIntersection};
+use bounding_volume::AABB;
+use math::{Scalar, Point, Vect};
+
+/// A set of rays cast together.
+///
+/// Packets are efficient for coherent rays, e.g., rays starting from the same origin with close
+/// directions: they tend to traverse the same nodes of a `BVT` which are then tested once for the
+/// whole packet. The inverse of the ray directions are precomputed for the ray-AABB tests.
+#[deriving(Show, Clone)]
+pub struct RayPacket<P, V> {
+    rays:     Vec<Ray<P, V>>,
+    inv_dirs: Vec<V>
+}
+
+impl<N, P, V> RayPacket<P, V>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> {
+    /// Creates a new packet of rays.
+    pub fn new(rays: Vec<Ray<P, V>>) -> RayPacket<P, V> {
+        let inv_dirs = rays.iter().map(|ray| {
+            let mut inv_dir = ray.dir.clone();
+
+            for i in range(0u, na::dim::<V>()) {
+                inv_dir[i] = if na::is_zero(&ray.dir[i]) {
+                    Bounded::max_value()
+                }
+                else {
+                    na::one::<N>() / ray.dir[i]
+                };
+            }
+
+            inv_dir
+        }).collect();
+
+        RayPacket {
+            rays:     rays,
+            inv_dirs: inv_dirs
+        }
+    }
+
+    /// Creates a new packet of rays starting at the same origin.
+    pub fn new_with_origin(orig: P, dirs: &[V]) -> RayPacket<P, V> {
+        RayPacket::new(dirs.iter().map(|dir| Ray::new(orig.clone(), dir.clone())).collect())
+    }
+
+    /// The rays of this packet.
+    #[inline]
+    pub fn rays(&self) -> &[Ray<P, V>] {
+        self.rays.as_slice()
+    }
+
+    /// The number of rays of this packet.
+    #[inline]
+    pub fn len(&self) -> uint {
+        self.rays.len()
+    }
+
+    /// Transforms every ray of this packet by the inverse of `m`.
+    pub fn inv_transformed<M: Transform<P> + Rotate<V>>(&self, m: &M) -> RayPacket<P, V> {
+        RayPacket::new(self.rays.iter().map(|ray| {
+            Ray::new(m.inv_transform(&ray.orig), m.inv_rotate(&ray.dir))
+        }).collect())
+    }
+
+    /// Tests the active rays of this packet against an AABB.
+    ///
+    /// Only the rays with `active[i]` set are tested. The i-th ray hits the AABB if it enters it
+    /// before `max_tois[i]`, in which case `out_active[i]` is set. Returns `true` if at least one
+    /// ray hits the AABB.
+    pub fn intersects_aabb(&self, aabb: &AABB<P>, active: &[bool], max_tois: &[N], out_active: &mut [bool])
+                           -> bool {
+        let mut any = false;
+
+        // This loop is plain scalar code so that it works with any scalar type and dimension.
+        for i in range(0u, self.rays.len()) {
+            if !active[i] {
+                out_active[i] = false;
+                continue;
+            }
+
+            let orig    = &self.rays[i].orig;
+            let inv_dir = &self.inv_dirs[i];
+            let mut tmin: N = na::zero();
+            let mut tmax    = max_tois[i];
+
+            for d in range(0u, na::dim::<P>()) {
+                let t1 = (aabb.mins()[d] - orig[d]) * inv_dir[d];
+                let t2 = (aabb.maxs()[d] - orig[d]) * inv_dir[d];
+
+                tmin = tmin.max(t1.min(t2));
+                tmax = tmax.min(t1.max(t2));
+            }
+
+            out_active[i] = tmin <= tmax;
+            any           = any || out_active[i];
+        }
+
+        any
+    }
+}
+
+/// Traits of objects which can be tested for intersection with a packet of rays.
+pub trait LocalRayPacketCast<N, P, V> {
+    /// Computes the intersection of each ray of the packet with this shape.
+    ///
+    /// The i-th result is the intersection with the i-th ray of the packet.
+    fn toi_and_normal_with_ray_packet(&self, packet: &RayPacket<P, V>, solid: bool)
+                                      -> Vec<Option<RayIntersection<N, V>>>;
+}
+
+/// Traits of objects which can be transformed and tested for intersection with a packet of rays.
+pub trait RayPacketCast<N, P, V, M: Transform<P> + Rotate<V>>: LocalRayPacketCast<N, P, V> {
+    /// Computes the intersection of each ray of the packet with this transformed shape.
+    fn toi_and_normal_with_transform_and_ray_packet(&self, m: &M, packet: &RayPacket<P, V>, solid: bool)
+                                                    -> Vec<Option<RayIntersection<N, V>>>
+        where N: Scalar,
+              P: Point<N, V>,
+              V: Vect<N> {
+        let ls_packet = packet.inv_transformed(m);
+
+        self.toi_and_normal_with_ray_packet(&ls_packet, solid).into_iter().map(|inter| {
+            inter.map(|mut inter| {
+                inter.normal = m.rotate(&inter.normal);
+
+                inter
+            })
+        }).collect()
+    }
+}
+
+/// Casts each ray of a packet individually on a shape.
+///
+/// This is the fallback for shapes that do not have a dedicated packet traversal.
+pub fn cast_ray_packet_individually<N, P, V, S>(shape: &S, packet: &RayPacket<P, V>, solid: bool)
+                                                -> Vec<Option<RayIntersection<N, V>>>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          S: LocalRayCast<N, P, V> {
+    packet.rays().iter().map(|ray| shape.toi_and_normal_with_ray(ray, solid)).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+    use na::{Pnt3, Vec3};
+    use na;
+    use ray::{Ray, LocalRayCast, RayPacket, LocalRayPacketCast};
+    use shape::Mesh3;
+
+    #[test]
+    fn test_ray_packet_matches_individual_rays() {
+        // Two quads at z = 0 and z = 1.
+        let vertices = vec!(
+            Pnt3::new(-1.0f64, -1.0, 0.0), Pnt3::new(1.0, -1.0, 0.0), Pnt3::new(1.0, 1.0, 0.0), Pnt3::new(-1.0, 1.0, 0.0),
+            Pnt3::new(-1.0f64, -1.0, 1.0), Pnt3::new(1.0, -1.0, 1.0), Pnt3::new(1.0, 1.0, 1.0), Pnt3::new(-1.0, 1.0, 1.0));
+        let indices = vec!(0u, 1, 2, 0, 2, 3, 4, 5, 6, 4, 6, 7);
+        let mesh: Mesh3<f64> = Mesh3::new(Arc::new(vertices), Arc::new(indices), None, None);
+
+        let dirs = [
+            Vec3::new(0.0f64, 0.0, 1.0),
+            na::normalize(&Vec3::new(0.25f64, 0.25, 1.0)),
+            na::normalize(&Vec3::new(3.0f64, 0.0, 1.0)),
+            Vec3::new(0.0f64, 0.0, -1.0)
+        ];
+        let packet = RayPacket::new_with_origin(Pnt3::new(0.0f64, 0.0, -2.0), dirs.as_slice());
+        let res    = mesh.toi_and_normal_with_ray_packet(&packet, true);
+
+        for (ray, inter) in packet.rays().iter().zip(res.iter()) {
+            let expected = mesh.toi_with_ray(ray, true);
+
+            assert!(expected.is_some() == inter.is_some());
+
+            match (expected, inter) {
+                (Some(toi), &Some(ref inter)) => assert!(na::approx_eq(&toi, &inter.toi)),
+                _                             => { }
+            }
+        }
+
+        assert!(res[2].is_none() && res[3].is_none());
+        assert!(na::approx_eq(&res[0].as_ref().unwrap().toi, &2.0));
+    }
+}
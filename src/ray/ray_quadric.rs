@@ -0,0 +1,612 @@
+//! Analytic ray casting on the quadric-based shapes aligned with the `y` axis.
+//!
+//! Those shapes are convex so a line intersects them on a single interval. The interval is
+//! computed by clipping the line with the quadric surfaces and the planes bounding each shape.
+
+use std::num::Float;
+use na::{Pnt2, Transform, Rotate, Bounded, Norm};
+use na;
+use ray::{Ray, LocalRayCast, RayCast, RayIntersection};
+use shape::{Cylinder, Cone, Capsule};
+use math::{Scalar, Point, Vect};
+
+// The interval of a line inside of a convex shape, with the outward normals at its ends:
+// `(entry toi, entry normal, exit toi, exit normal)`.
+type Interval<N, V> = (N, V, N, V);
+
+impl<N, P, V> LocalRayCast<N, P, V> for Cylinder<N>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> {
+    #[inline]
+    fn toi_and_normal_with_ray(&self, ray: &Ray<P, V>, solid: bool) -> Option<RayIntersection<N, V>> {
+        let radial = infinite_cylinder_interval(self.radius(), ray);
+        let axial  = slab_interval(self.half_height(), ray);
+
+        intersection_with_interval(intersect_intervals(radial, axial), solid)
+    }
+
+    #[inline]
+    fn toi_and_normal_and_uv_with_ray(&self, ray: &Ray<P, V>, solid: bool) -> Option<RayIntersection<N, V>> {
+        let hh = self.half_height();
+
+        self.toi_and_normal_with_ray(ray, solid).map(|mut inter| {
+            inter.uvs = cylindrical_uv(&(ray.orig + ray.dir * inter.toi), -hh, hh);
+
+            inter
+        })
+    }
+}
+
+impl<N, P, V, M> RayCast<N, P, V, M> for Cylinder<N>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Transform<P> + Rotate<V> {
+}
+
+impl<N, P, V> LocalRayCast<N, P, V> for Cone<N>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> {
+    #[inline]
+    fn toi_and_normal_with_ray(&self, ray: &Ray<P, V>, solid: bool) -> Option<RayIntersection<N, V>> {
+        let lateral = cone_interval(self.half_height(), self.radius(), ray);
+        let axial   = slab_interval(self.half_height(), ray);
+
+        intersection_with_interval(intersect_intervals(lateral, axial), solid)
+    }
+
+    #[inline]
+    fn toi_and_normal_and_uv_with_ray(&self, ray: &Ray<P, V>, solid: bool) -> Option<RayIntersection<N, V>> {
+        let hh = self.half_height();
+
+        self.toi_and_normal_with_ray(ray, solid).map(|mut inter| {
+            inter.uvs = cylindrical_uv(&(ray.orig + ray.dir * inter.toi), -hh, hh);
+
+            inter
+        })
+    }
+}
+
+impl<N, P, V, M> RayCast<N, P, V, M> for Cone<N>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Transform<P> + Rotate<V> {
+}
+
+impl<N, P, V> LocalRayCast<N, P, V> for Capsule<N>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> {
+    fn toi_and_normal_with_ray(&self, ray: &Ray<P, V>, solid: bool) -> Option<RayIntersection<N, V>> {
+        let hh = self.half_height();
+        let r  = self.radius();
+
+        let body = intersect_intervals(infinite_cylinder_interval(r, ray), slab_interval(hh, ray));
+        let top  = ball_interval(hh, r, ray);
+        let bot  = ball_interval(-hh, r, ray);
+
+        // The capsule is convex so the union of the intervals of its parts is an interval.
+        intersection_with_interval(merge_intervals(merge_intervals(body, top), bot), solid)
+    }
+
+    #[inline]
+    fn toi_and_normal_and_uv_with_ray(&self, ray: &Ray<P, V>, solid: bool) -> Option<RayIntersection<N, V>> {
+        let extent = self.half_height() + self.radius();
+
+        self.toi_and_normal_with_ray(ray, solid).map(|mut inter| {
+            inter.uvs = cylindrical_uv(&(ray.orig + ray.dir * inter.toi), -extent, extent);
+
+            inter
+        })
+    }
+}
+
+impl<N, P, V, M> RayCast<N, P, V, M> for Capsule<N>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Transform<P> + Rotate<V> {
+}
+
+/*
+ * Intervals.
+ */
+// Computes the ray intersection from the interval of the ray line inside of the shape.
+fn intersection_with_interval<N, V>(interval: Option<Interval<N, V>>, solid: bool)
+                                    -> Option<RayIntersection<N, V>>
+    where N: Scalar,
+          V: Vect<N> {
+    match interval {
+        None => None,
+        Some((tmin, nmin, tmax, nmax)) => {
+            if tmax < na::zero() {
+                None
+            }
+            else if tmin >= na::zero() {
+                Some(RayIntersection::new(tmin, nmin))
+            }
+            else if solid {
+                // The ray origin is inside of the shape.
+                Some(RayIntersection::new(na::zero(), -nmax))
+            }
+            else {
+                Some(RayIntersection::new(tmax, -nmax))
+            }
+        }
+    }
+}
+
+fn intersect_intervals<N, V>(i1: Option<Interval<N, V>>, i2: Option<Interval<N, V>>)
+                             -> Option<Interval<N, V>>
+    where N: Scalar {
+    match (i1, i2) {
+        (Some((min1, nmin1, max1, nmax1)), Some((min2, nmin2, max2, nmax2))) => {
+            let (tmin, nmin) = if min1 > min2 { (min1, nmin1) } else { (min2, nmin2) };
+            let (tmax, nmax) = if max1 < max2 { (max1, nmax1) } else { (max2, nmax2) };
+
+            if tmin > tmax {
+                None
+            }
+            else {
+                Some((tmin, nmin, tmax, nmax))
+            }
+        },
+        _ => None
+    }
+}
+
+// The intervals must overlap for the result to be an interval.
+fn merge_intervals<N, V>(i1: Option<Interval<N, V>>, i2: Option<Interval<N, V>>)
+                         -> Option<Interval<N, V>>
+    where N: Scalar {
+    match (i1, i2) {
+        (Some((min1, nmin1, max1, nmax1)), Some((min2, nmin2, max2, nmax2))) => {
+            let (tmin, nmin) = if min1 < min2 { (min1, nmin1) } else { (min2, nmin2) };
+            let (tmax, nmax) = if max1 > max2 { (max1, nmax1) } else { (max2, nmax2) };
+
+            Some((tmin, nmin, tmax, nmax))
+        },
+        (Some(i), None) => Some(i),
+        (None, i)       => i
+    }
+}
+
+// The interval of the whole line, used when the line never crosses a surface.
+#[inline]
+fn unbounded_interval<N, V>() -> Interval<N, V>
+    where N: Scalar,
+          V: Vect<N> {
+    let inf: N = Bounded::max_value();
+
+    (-inf, na::zero(), inf, na::zero())
+}
+
+// Interval of the line inside of the slab `|y| <= half_height`.
+fn slab_interval<N, P, V>(half_height: N, ray: &Ray<P, V>) -> Option<Interval<N, V>>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> {
+    let oy = ray.orig[1];
+    let dy = ray.dir[1];
+
+    if na::is_zero(&dy) {
+        if oy.abs() > half_height {
+            None
+        }
+        else {
+            Some(unbounded_interval())
+        }
+    }
+    else {
+        let tbot = (-half_height - oy) / dy;
+        let ttop = (half_height - oy) / dy;
+        let _1: N = na::one();
+
+        if dy > na::zero() {
+            Some((tbot, y_axis(-_1), ttop, y_axis(_1)))
+        }
+        else {
+            Some((ttop, y_axis(_1), tbot, y_axis(-_1)))
+        }
+    }
+}
+
+// Interval of the line inside of the infinite cylinder of radius `radius` around the `y` axis.
+fn infinite_cylinder_interval<N, P, V>(radius: N, ray: &Ray<P, V>) -> Option<Interval<N, V>>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> {
+    let o = radial(ray.orig.as_vec());
+    let d = radial(&ray.dir);
+
+    let a = na::sqnorm(&d);
+    let b = na::dot(&o, &d);
+    let c = na::sqnorm(&o) - radius * radius;
+
+    if na::is_zero(&a) {
+        // The line is parallel to the axis.
+        if c > na::zero() {
+            None
+        }
+        else {
+            Some(unbounded_interval())
+        }
+    }
+    else {
+        let delta = b * b - a * c;
+
+        if delta < na::zero() {
+            None
+        }
+        else {
+            let sqrt_delta = delta.sqrt();
+            let t1 = (-b - sqrt_delta) / a;
+            let t2 = (-b + sqrt_delta) / a;
+
+            Some((t1, (o + d * t1) / radius, t2, (o + d * t2) / radius))
+        }
+    }
+}
+
+// Interval of the line inside of the infinite half-cone with its apex at `y = half_height`, and
+// with a radius `radius` at `y = -half_height`.
+fn cone_interval<N, P, V>(half_height: N, radius: N, ray: &Ray<P, V>) -> Option<Interval<N, V>>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> {
+    let k  = radius / (half_height + half_height);
+    let k2 = k * k;
+    let o  = radial(ray.orig.as_vec());
+    let d  = radial(&ray.dir);
+    let w  = half_height - ray.orig[1];
+    let dy = ray.dir[1];
+    let _2: N = na::cast(2.0f64);
+
+    // The points of the double cone satisfy `a * t^2 + 2 * b * t + c <= 0`. Clipping the result
+    // with the slab removes the upper nappe.
+    let a = na::sqnorm(&d) - k2 * dy * dy;
+    let b = na::dot(&o, &d) + k2 * w * dy;
+    let c = na::sqnorm(&o) - k2 * w * w;
+
+    let normal_at = |t: N| cone_normal(k, &(o + d * t));
+    let inf: N    = Bounded::max_value();
+
+    if na::is_zero(&a) {
+        // The line is parallel to the cone surface.
+        if na::is_zero(&b) {
+            if c > na::zero() { None } else { Some(unbounded_interval()) }
+        }
+        else {
+            let t = -c / (_2 * b);
+
+            if b > na::zero() {
+                Some((-inf, na::zero(), t, normal_at(t)))
+            }
+            else {
+                Some((t, normal_at(t), inf, na::zero()))
+            }
+        }
+    }
+    else {
+        let delta = b * b - a * c;
+
+        if delta < na::zero() {
+            if a > na::zero() { None } else { Some(unbounded_interval()) }
+        }
+        else {
+            let sqrt_delta = delta.sqrt();
+            let t1 = (-b - sqrt_delta) / a;
+            let t2 = (-b + sqrt_delta) / a;
+            let tmin = t1.min(t2);
+            let tmax = t1.max(t2);
+
+            if a > na::zero() {
+                Some((tmin, normal_at(tmin), tmax, normal_at(tmax)))
+            }
+            else if dy > na::zero() {
+                // The line goes up: the lower nappe is hit first.
+                Some((-inf, na::zero(), tmin, normal_at(tmin)))
+            }
+            else {
+                Some((tmax, normal_at(tmax), inf, na::zero()))
+            }
+        }
+    }
+}
+
+// Interval of the line inside of the ball of radius `radius` centered at `y = center`.
+fn ball_interval<N, P, V>(center: N, radius: N, ray: &Ray<P, V>) -> Option<Interval<N, V>>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> {
+    let mut dcenter = ray.orig.as_vec().clone();
+    dcenter[1] = dcenter[1] - center;
+
+    let a = na::sqnorm(&ray.dir);
+    let b = na::dot(&dcenter, &ray.dir);
+    let c = na::sqnorm(&dcenter) - radius * radius;
+
+    let delta = b * b - a * c;
+
+    if na::is_zero(&a) || delta < na::zero() {
+        None
+    }
+    else {
+        let sqrt_delta = delta.sqrt();
+        let t1 = (-b - sqrt_delta) / a;
+        let t2 = (-b + sqrt_delta) / a;
+
+        Some((t1, (dcenter + ray.dir * t1) / radius, t2, (dcenter + ray.dir * t2) / radius))
+    }
+}
+
+/*
+ * Utilities.
+ */
+// The part of `v` orthogonal to the `y` axis.
+#[inline]
+fn radial<N, V>(v: &V) -> V
+    where N: Scalar,
+          V: Vect<N> {
+    let mut res = v.clone();
+    res[1] = na::zero();
+
+    res
+}
+
+#[inline]
+fn y_axis<N, V>(y: N) -> V
+    where N: Scalar,
+          V: Vect<N> {
+    let mut res: V = na::zero();
+    res[1] = y;
+
+    res
+}
+
+// Outward normal of the cone lateral surface at a point with the radial part `pt`.
+#[inline]
+fn cone_normal<N, V>(k: N, pt: &V) -> V
+    where N: Scalar,
+          V: Vect<N> {
+    let mut res = pt.clone();
+
+    if na::is_zero(&res.normalize()) {
+        // This is the apex.
+        y_axis(na::one())
+    }
+    else {
+        res[1] = k;
+
+        na::normalize(&res)
+    }
+}
+
+// Texture coordinates of a point with a cylindrical mapping around the `y` axis. The `v`
+// coordinate goes from zero at `y = top` to one at `y = bottom`.
+#[inline]
+fn cylindrical_uv<N, P, V>(pt: &P, bottom: N, top: N) -> Option<Pnt2<N>>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> {
+    if na::dim::<P>() == 3 {
+        let two_pi: N = Float::two_pi();
+        let _0_5:   N = na::cast(0.5f64);
+        let uvx = _0_5 + pt[2].atan2(pt[0]) / two_pi;
+        let uvy = (top - pt[1]) / (top - bottom);
+
+        Some(Pnt2::new(uvx, uvy.max(na::zero()).min(na::one())))
+    }
+    else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::rand::{IsaacRng, Rng};
+    use std::num::Float;
+    use na::{Pnt3, Vec3, Pnt2, Vec2, Identity};
+    use na;
+    use geometry::algorithms::johnson_simplex::JohnsonSimplex;
+    use support_map::SupportMap;
+    use ray::{Ray, LocalRayCast};
+    use ray;
+    use shape::{Cylinder, Cone, Capsule};
+    use math::{Point, Vect};
+
+    fn random_vect<V: Vect<f64>>(rng: &mut IsaacRng, half_extent: f64) -> V {
+        let mut res: V = na::zero();
+
+        for i in range(0u, na::dim::<V>()) {
+            res[i] = (rng.gen::<f64>() * 2.0 - 1.0) * half_extent;
+        }
+
+        res
+    }
+
+    // A ray starting anywhere around the shape and aiming at a point close to it.
+    fn random_ray<P: Point<f64, V>, V: Vect<f64>>(rng: &mut IsaacRng) -> Ray<P, V> {
+        let orig   = random_vect::<V>(rng, 3.0);
+        let target = random_vect::<V>(rng, 2.0);
+
+        Ray::new(na::orig::<P>() + orig, na::normalize(&(target - orig)))
+    }
+
+    // Compares the analytic ray cast with the GJK-based one, for solid and non-solid casts.
+    fn assert_same_as_gjk<P, V, G>(shape: &G, ray: &Ray<P, V>)
+        where P: Point<f64, V>,
+              V: Vect<f64>,
+              G: LocalRayCast<f64, P, V> + SupportMap<P, V, Identity> {
+        let inside = match shape.toi_with_ray(ray, true) {
+            Some(toi) => na::is_zero(&toi),
+            None      => false
+        };
+
+        for &solid in [ true, false ].iter() {
+            let analytic = shape.toi_and_normal_with_ray(ray, solid);
+            let gjk      = ray::implicit_toi_and_normal_with_ray(
+                &Identity::new(), shape, &mut JohnsonSimplex::<f64, P, V>::new_w_tls(), ray, solid);
+
+            match (analytic, gjk) {
+                (None, None) => { },
+                (Some(a), Some(g)) => {
+                    assert!(na::approx_eq_eps(&a.toi, &g.toi, &1.0e-5));
+
+                    if !inside {
+                        assert!(na::approx_eq_eps(&a.normal, &g.normal, &1.0e-3));
+                    }
+                    else if !solid {
+                        // The analytic normal points toward the inside of the shape when the ray
+                        // starts inside of it.
+                        assert!(na::approx_eq_eps(&a.normal, &-g.normal, &1.0e-3));
+                    }
+                },
+                _ => panic!("The analytic and GJK ray casts disagree for {}.", ray)
+            }
+        }
+    }
+
+    fn assert_same_as_gjk_on_random_rays<P, V, G>(shape: &G)
+        where P: Point<f64, V>,
+              V: Vect<f64>,
+              G: LocalRayCast<f64, P, V> + SupportMap<P, V, Identity> {
+        let mut rng = IsaacRng::new_unseeded();
+
+        for _ in range(0u, 500) {
+            let ray: Ray<P, V> = random_ray(&mut rng);
+
+            assert_same_as_gjk(shape, &ray);
+        }
+    }
+
+    #[test]
+    fn test_analytic_ray_casts() {
+        let cylinder = Cylinder::new(1.0f64, 0.5);
+        let cone     = Cone::new(1.0f64, 1.0);
+        let capsule  = Capsule::new(1.0f64, 0.5);
+
+        let ray = Ray::new(Pnt3::new(-2.0f64, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+
+        let inter = cylinder.toi_and_normal_with_ray(&ray, true).unwrap();
+        assert!(na::approx_eq(&inter.toi, &1.5));
+        assert!(na::approx_eq(&inter.normal, &Vec3::new(-1.0, 0.0, 0.0)));
+
+        // The cone radius is 0.5 at y = 0.
+        assert!(na::approx_eq(&cone.toi_with_ray(&ray, true).unwrap(), &1.5));
+
+        // Hits the top of the capsule.
+        let ray   = Ray::new(Pnt3::new(0.0f64, 3.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+        let inter = capsule.toi_and_normal_with_ray(&ray, true).unwrap();
+        assert!(na::approx_eq(&inter.toi, &1.5));
+        assert!(na::approx_eq(&inter.normal, &Vec3::new(0.0, 1.0, 0.0)));
+
+        // From the inside.
+        let ray = Ray::new(na::orig::<Pnt3<f64>>(), Vec3::new(0.0, 1.0, 0.0));
+        assert!(na::approx_eq(&capsule.toi_with_ray(&ray, true).unwrap(), &0.0));
+        assert!(na::approx_eq(&capsule.toi_with_ray(&ray, false).unwrap(), &1.5));
+        assert!(na::approx_eq(&cone.toi_with_ray(&ray, false).unwrap(), &1.0));
+
+        // In 2D.
+        let ray = Ray::new(Pnt2::new(0.0f64, -3.0), Vec2::new(0.0, 1.0));
+        assert!(na::approx_eq(&cylinder.toi_with_ray(&ray, true).unwrap(), &2.0));
+        assert!(cone.toi_with_ray(&Ray::new(Pnt2::new(0.8f64, 0.5), Vec2::new(0.0, 1.0)), true).is_none());
+    }
+
+    #[test]
+    fn test_analytic_ray_casts_against_gjk() {
+        let cylinder = Cylinder::new(1.0f64, 0.5);
+        let cone     = Cone::new(1.0f64, 1.0);
+        let capsule  = Capsule::new(1.0f64, 0.5);
+
+        assert_same_as_gjk_on_random_rays::<Pnt3<f64>, Vec3<f64>, _>(&cylinder);
+        assert_same_as_gjk_on_random_rays::<Pnt3<f64>, Vec3<f64>, _>(&cone);
+        assert_same_as_gjk_on_random_rays::<Pnt3<f64>, Vec3<f64>, _>(&capsule);
+        assert_same_as_gjk_on_random_rays::<Pnt2<f64>, Vec2<f64>, _>(&cylinder);
+        assert_same_as_gjk_on_random_rays::<Pnt2<f64>, Vec2<f64>, _>(&cone);
+        assert_same_as_gjk_on_random_rays::<Pnt2<f64>, Vec2<f64>, _>(&capsule);
+    }
+
+    #[test]
+    fn test_analytic_ray_casts_on_cone_base_and_grazing_rays() {
+        let cylinder = Cylinder::new(1.0f64, 0.5);
+        let cone     = Cone::new(1.0f64, 1.0);
+        let capsule  = Capsule::new(1.0f64, 0.5);
+
+        // Hits the base of the cone from below, straight or tilted.
+        let ray   = Ray::new(Pnt3::new(0.2f64, -3.0, 0.1), Vec3::new(0.0, 1.0, 0.0));
+        let inter = cone.toi_and_normal_with_ray(&ray, true).unwrap();
+        assert!(na::approx_eq(&inter.toi, &2.0));
+        assert!(na::approx_eq(&inter.normal, &Vec3::new(0.0, -1.0, 0.0)));
+
+        let ray   = Ray::new(Pnt3::new(-1.0f64, -3.0, 0.0), na::normalize(&Vec3::new(0.2, 1.0, 0.0)));
+        let inter = cone.toi_and_normal_with_ray(&ray, true).unwrap();
+        assert!(na::approx_eq(&inter.toi, &(2.0 * 1.04f64.sqrt())));
+        assert!(na::approx_eq(&inter.normal, &Vec3::new(0.0, -1.0, 0.0)));
+        assert_same_as_gjk(&cone, &ray);
+
+        // Leaves the cone through its base: the normal points inward.
+        let ray   = Ray::new(na::orig::<Pnt3<f64>>(), Vec3::new(0.0, -1.0, 0.0));
+        let inter = cone.toi_and_normal_with_ray(&ray, false).unwrap();
+        assert!(na::approx_eq(&inter.toi, &1.0));
+        assert!(na::approx_eq(&inter.normal, &Vec3::new(0.0, 1.0, 0.0)));
+
+        // Tilted ray on the cone lateral surface, in 2D.
+        let ray   = Ray::new(Pnt2::new(-2.0f64, -1.0), na::normalize(&Vec2::new(1.0, 0.5)));
+        let inter = cone.toi_and_normal_with_ray(&ray, true).unwrap();
+        assert!(na::approx_eq(&inter.normal, &na::normalize(&Vec2::new(-1.0, 0.5))));
+        assert_same_as_gjk(&cone, &ray);
+
+        // Grazing rays touch the shapes at a single point.
+        let ray = Ray::new(Pnt3::new(-3.0f64, 0.0, 0.5), Vec3::new(1.0, 0.0, 0.0));
+        assert!(na::approx_eq(&cylinder.toi_with_ray(&ray, true).unwrap(), &3.0));
+
+        let ray = Ray::new(Pnt3::new(-3.0f64, 0.0, 0.5 + 1.0e-6), Vec3::new(1.0, 0.0, 0.0));
+        assert!(cylinder.toi_with_ray(&ray, true).is_none());
+
+        let ray = Ray::new(Pnt3::new(-3.0f64, 1.5, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert!(na::approx_eq(&capsule.toi_with_ray(&ray, true).unwrap(), &3.0));
+
+        let ray = Ray::new(Pnt3::new(-3.0f64, 1.5 + 1.0e-6, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert!(capsule.toi_with_ray(&ray, true).is_none());
+
+        // Touches the apex of the cone.
+        let ray   = Ray::new(Pnt3::new(-3.0f64, 1.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let inter = cone.toi_and_normal_with_ray(&ray, true).unwrap();
+        assert!(na::approx_eq(&inter.toi, &3.0));
+        assert!(na::approx_eq(&inter.normal, &Vec3::new(0.0, 1.0, 0.0)));
+    }
+
+    #[test]
+    fn test_analytic_ray_casts_uvs() {
+        let cylinder = Cylinder::new(1.0f64, 0.5);
+        let cone     = Cone::new(1.0f64, 1.0);
+        let capsule  = Capsule::new(1.0f64, 0.5);
+
+        let ray   = Ray::new(Pnt3::new(0.0f64, 0.0, -2.0), Vec3::new(0.0, 0.0, 1.0));
+        let inter = cylinder.toi_and_normal_and_uv_with_ray(&ray, true).unwrap();
+        assert!(na::approx_eq(&inter.toi, &1.5));
+        assert!(na::approx_eq(&inter.uvs.unwrap(), &Pnt2::new(0.25, 0.5)));
+
+        let inter = cone.toi_and_normal_and_uv_with_ray(&ray, true).unwrap();
+        assert!(na::approx_eq(&inter.toi, &1.5));
+        assert!(na::approx_eq(&inter.uvs.unwrap(), &Pnt2::new(0.25, 0.5)));
+
+        let ray   = Ray::new(Pnt3::new(2.0f64, 0.75, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+        let inter = capsule.toi_and_normal_and_uv_with_ray(&ray, true).unwrap();
+        assert!(na::approx_eq(&inter.toi, &1.5));
+        assert!(na::approx_eq(&inter.uvs.unwrap(), &Pnt2::new(0.5, 0.25)));
+
+        // The base of the cone has `v = 1`.
+        let ray   = Ray::new(Pnt3::new(0.2f64, -3.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        let inter = cone.toi_and_normal_and_uv_with_ray(&ray, true).unwrap();
+        assert!(na::approx_eq(&inter.uvs.unwrap(), &Pnt2::new(0.5, 1.0)));
+
+        // No uvs in 2D.
+        let ray = Ray::new(Pnt2::new(0.0f64, -3.0), Vec2::new(0.0, 1.0));
+        assert!(cylinder.toi_and_normal_and_uv_with_ray(&ray, true).unwrap().uvs.is_none());
+    }
+}
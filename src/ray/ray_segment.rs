@@ -0,0 +1,196 @@
+use na::{Identity, Pnt2, Transform, Rotate};
+use na;
+use geometry::algorithms::johnson_simplex::JohnsonSimplex;
+use shape::Segment;
+use ray::{Ray, LocalRayCast, RayCast, RayIntersection, implicit_toi_and_normal_with_ray};
+use math::{Scalar, Point, Vect};
+
+impl<N, P, V> LocalRayCast<N, P, V> for Segment<P>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> {
+    fn toi_and_normal_with_ray(&self, ray: &Ray<P, V>, solid: bool) -> Option<RayIntersection<N, V>> {
+        if na::dim::<P>() == 2 {
+            segment_ray_intersection(self.a(), self.b(), ray).map(|(mut inter, s)| {
+                inter.uvs = Some(Pnt2::new(s, na::zero()));
+
+                inter
+            })
+        }
+        else {
+            implicit_toi_and_normal_with_ray(&Identity::new(), self, &mut JohnsonSimplex::<N, P, V>::new_w_tls(), ray, solid)
+        }
+    }
+}
+
+impl<N, P, V, M> RayCast<N, P, V, M> for Segment<P>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          M: Transform<P> + Rotate<V> {
+}
+
+/// Computes the intersection between a 2D segment and a ray.
+///
+/// If an intersection is found, the time of impact, the normal and the parameter `s` of the
+/// intersection point `a + (b - a) * s` are returned. The normal is oriented toward the ray
+/// origin.
+pub fn segment_ray_intersection<N, P, V>(a: &P, b: &P, ray: &Ray<P, V>) -> Option<(RayIntersection<N, V>, N)>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> {
+    assert!(na::dim::<P>() == 2, "The segment-ray intersection is only implemented in 2D.");
+
+    let ab    = *b - *a;
+    let oa    = *a - ray.orig;
+    let denom = perp(&ray.dir, &ab);
+
+    if na::is_zero(&denom) {
+        if !na::is_zero(&perp(&oa, &ray.dir)) {
+            // Parallel but not collinear.
+            return None;
+        }
+
+        // The ray and the segment are collinear: the first hit is an endpoint, or the origin if
+        // it lies on the segment.
+        let sqnorm = na::sqnorm(&ray.dir);
+        let ta     = na::dot(&oa, &ray.dir) / sqnorm;
+        let tb     = na::dot(&(*b - ray.orig), &ray.dir) / sqnorm;
+
+        if ta < na::zero() && tb < na::zero() {
+            return None;
+        }
+
+        let normal = -na::normalize(&ray.dir);
+
+        if ta < na::zero() || tb < na::zero() {
+            let s = -ta / (tb - ta);
+
+            Some((RayIntersection::new(na::zero(), normal), s))
+        }
+        else if ta <= tb {
+            Some((RayIntersection::new(ta, normal), na::zero()))
+        }
+        else {
+            Some((RayIntersection::new(tb, normal), na::one()))
+        }
+    }
+    else {
+        let toi = perp(&oa, &ab) / denom;
+        let s   = perp(&oa, &ray.dir) / denom;
+
+        if toi < na::zero() || s < na::zero() || s > na::one() {
+            None
+        }
+        else {
+            let mut normal: V = na::zero();
+
+            normal[0] = -ab[1];
+            normal[1] = ab[0];
+
+            if na::dot(&normal, &ray.dir) > na::zero() {
+                normal = -normal;
+            }
+
+            Some((RayIntersection::new(toi, na::normalize(&normal)), s))
+        }
+    }
+}
+
+// The 2D cross product.
+#[inline]
+fn perp<N: Scalar, V: Vect<N>>(a: &V, b: &V) -> N {
+    a[0] * b[1] - a[1] * b[0]
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+    use na::{Pnt2, Vec2};
+    use shape::{Segment, Mesh2};
+    use ray::{Ray, LocalRayCast, FeatureId};
+
+    #[test]
+    fn test_ray_segment_2d() {
+        let seg = Segment::new(Pnt2::new(0.0f64, -1.0), Pnt2::new(0.0, 1.0));
+
+        let ray   = Ray::new(Pnt2::new(-2.0, 0.5), Vec2::new(1.0, 0.0));
+        let inter = seg.toi_and_normal_with_ray(&ray, true).unwrap();
+
+        assert!(inter.toi == 2.0);
+        assert!(inter.normal == Vec2::new(-1.0, 0.0));
+        assert!(inter.uvs == Some(Pnt2::new(0.75, 0.0)));
+
+        // The normal faces the ray origin.
+        let ray   = Ray::new(Pnt2::new(2.0, -0.5), Vec2::new(-1.0, 0.0));
+        let inter = seg.toi_and_normal_with_ray(&ray, true).unwrap();
+
+        assert!(inter.toi == 2.0);
+        assert!(inter.normal == Vec2::new(1.0, 0.0));
+        assert!(inter.uvs == Some(Pnt2::new(0.25, 0.0)));
+
+        // Past an endpoint, and pointing away.
+        assert!(seg.toi_with_ray(&Ray::new(Pnt2::new(-2.0, 1.5), Vec2::new(1.0, 0.0)), true).is_none());
+        assert!(seg.toi_with_ray(&Ray::new(Pnt2::new(-2.0, 0.5), Vec2::new(-1.0, 0.0)), true).is_none());
+
+        // Parallel but not collinear.
+        assert!(seg.toi_with_ray(&Ray::new(Pnt2::new(-2.0, -3.0), Vec2::new(0.0, 1.0)), true).is_none());
+    }
+
+    #[test]
+    fn test_ray_segment_collinear() {
+        let a = Pnt2::new(0.0f64, -1.0);
+        let b = Pnt2::new(0.0, 1.0);
+
+        // The first endpoint hit is `a` or `b` depending on the ray direction.
+        let ray         = Ray::new(Pnt2::new(0.0, -3.0), Vec2::new(0.0, 1.0));
+        let (inter, s)  = super::segment_ray_intersection(&a, &b, &ray).unwrap();
+
+        assert!(inter.toi == 2.0 && s == 0.0);
+        assert!(inter.normal == Vec2::new(0.0, -1.0));
+
+        let ray         = Ray::new(Pnt2::new(0.0, 3.0), Vec2::new(0.0, -1.0));
+        let (inter, s)  = super::segment_ray_intersection(&a, &b, &ray).unwrap();
+
+        assert!(inter.toi == 2.0 && s == 1.0);
+        assert!(inter.normal == Vec2::new(0.0, 1.0));
+
+        // The origin lies on the segment.
+        let ray         = Ray::new(Pnt2::new(0.0, 0.5), Vec2::new(0.0, 1.0));
+        let (inter, s)  = super::segment_ray_intersection(&a, &b, &ray).unwrap();
+
+        assert!(inter.toi == 0.0 && s == 0.75);
+
+        // Behind the ray.
+        let ray = Ray::new(Pnt2::new(0.0, 3.0), Vec2::new(0.0, 1.0));
+
+        assert!(super::segment_ray_intersection(&a, &b, &ray).is_none());
+    }
+
+    #[test]
+    fn test_ray_mesh2_features_and_uvs() {
+        // The outline of the unit square, counterclockwise.
+        let vertices = Arc::new(vec!(Pnt2::new(0.0f64, 0.0), Pnt2::new(1.0, 0.0), Pnt2::new(1.0, 1.0), Pnt2::new(0.0, 1.0)));
+        let indices  = Arc::new(vec!(0u, 1, 1, 2, 2, 3, 3, 0));
+        let mesh: Mesh2<f64> = Mesh2::new(vertices, indices, None, None);
+
+        let casts = [
+            (Pnt2::new(0.25, -1.0), Vec2::new(0.0, 1.0),  0u, 0.25),
+            (Pnt2::new(2.0, 0.25),  Vec2::new(-1.0, 0.0), 1,  0.25),
+            (Pnt2::new(0.25, 2.0),  Vec2::new(0.0, -1.0), 2,  0.75),
+            (Pnt2::new(-1.0, 0.75), Vec2::new(1.0, 0.0),  3,  0.25)
+        ];
+
+        for &(orig, dir, face, s) in casts.iter() {
+            let ray   = Ray::new(orig, dir);
+            let inter = mesh.toi_and_normal_and_uv_with_ray(&ray, true).unwrap();
+
+            assert!(inter.toi == 1.0);
+            assert!(inter.normal == -dir);
+            assert!(inter.feature == FeatureId::Face(face));
+            assert!(inter.uvs.unwrap().x == s);
+        }
+
+        assert!(mesh.toi_with_ray(&Ray::new(Pnt2::new(2.0, 2.0), Vec2::new(1.0, 0.0)), true).is_none());
+    }
+}
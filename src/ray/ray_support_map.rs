@@ -1,12 +1,15 @@
+use std::num::Float;
+use std::uint;
 use na::{Identity, Translation, Rotate, Transform};
 use na;
 use geometry::algorithms::gjk;
 use geometry::algorithms::simplex::Simplex;
 use geometry::algorithms::johnson_simplex::JohnsonSimplex;
-use shape::{MinkowskiSum, Segment, Cylinder, Cone, Capsule, Convex};
+use shape::{MinkowskiSum, Convex};
 use support_map::SupportMap;
-use ray::{Ray, LocalRayCast, RayCast, RayIntersection};
+use ray::{Ray, LocalRayCast, RayCast, RayIntersection, FeatureId};
 use math::{Scalar, Point, Vect};
+use utils;
 
 
 /// Cast a ray on a shape using the GJK algorithm.
@@ -52,81 +55,173 @@ pub fn implicit_toi_and_normal_with_ray<N, P, V, M, S, G>(m:       &M,
     }
 }
 
-impl<N, P, V> LocalRayCast<N, P, V> for Cylinder<N>
+impl<N, P, V> LocalRayCast<N, P, V> for Convex<P>
     where N: Scalar,
           P: Point<N, V>,
           V: Vect<N> {
-    fn toi_and_normal_with_ray(&self, ray: &Ray<P, V>, solid: bool) -> Option<RayIntersection<N, V>> {
+    #[inline]
+    fn toi_with_ray(&self, ray: &Ray<P, V>, solid: bool) -> Option<N> {
+        // Do not pay for the feature identification when only the time of impact is needed.
         implicit_toi_and_normal_with_ray(&Identity::new(), self, &mut JohnsonSimplex::<N, P, V>::new_w_tls(), ray, solid)
+            .map(|inter| inter.toi)
     }
-}
-
-impl<N, P, V, M> RayCast<N, P, V, M> for Cylinder<N>
-    where N: Scalar,
-          P: Point<N, V>,
-          V: Vect<N>,
-          M: Transform<P> + Rotate<V> {
-}
 
-impl<N, P, V> LocalRayCast<N, P, V> for Cone<N>
-    where N: Scalar,
-          P: Point<N, V>,
-          V: Vect<N> {
     fn toi_and_normal_with_ray(&self, ray: &Ray<P, V>, solid: bool) -> Option<RayIntersection<N, V>> {
         implicit_toi_and_normal_with_ray(&Identity::new(), self, &mut JohnsonSimplex::<N, P, V>::new_w_tls(), ray, solid)
-    }
-}
+            .map(|mut inter| {
+                inter.feature = convex_feature_id(self.points(), ray, &inter);
 
-impl<N, P, V, M> RayCast<N, P, V, M> for Cone<N>
-    where N: Scalar,
-          P: Point<N, V>,
-          V: Vect<N>,
-          M: Transform<P> + Rotate<V> {
+                inter
+            })
+    }
 }
 
-impl<N, P, V> LocalRayCast<N, P, V> for Capsule<N>
+// Computes the feature of the convex hull of `pts` hit by a ray, with the encoding described on
+// `FeatureId`.
+//
+// The hull topology is not stored by `Convex`, so the features are identified by the points
+// lying on the supporting plane at the hit point. `Unknown` is returned if the ray starts inside
+// of the hull.
+fn convex_feature_id<N, P, V>(pts: &[P], ray: &Ray<P, V>, inter: &RayIntersection<N, V>) -> FeatureId
     where N: Scalar,
           P: Point<N, V>,
           V: Vect<N> {
-    fn toi_and_normal_with_ray(&self, ray: &Ray<P, V>, solid: bool) -> Option<RayIntersection<N, V>> {
-        implicit_toi_and_normal_with_ray(&Identity::new(), self, &mut JohnsonSimplex::<N, P, V>::new_w_tls(), ray, solid)
+    let dim = na::dim::<P>();
+    let n   = pts.len();
+
+    if n == 0 || dim > 3 || na::is_zero(&inter.toi) {
+        return FeatureId::Unknown;
     }
-}
 
-impl<N, P, V, M> RayCast<N, P, V, M> for Capsule<N>
-    where N: Scalar,
-          P: Point<N, V>,
-          V: Vect<N>,
-          M: Transform<P> + Rotate<V> {
-}
+    let pt     = ray.orig + ray.dir * inter.toi;
+    let normal = na::normalize(&inter.normal);
 
-impl<N, P, V> LocalRayCast<N, P, V> for Convex<P>
-    where N: Scalar,
-          P: Point<N, V>,
-          V: Vect<N> {
-    fn toi_and_normal_with_ray(&self, ray: &Ray<P, V>, solid: bool) -> Option<RayIntersection<N, V>> {
-        implicit_toi_and_normal_with_ray(&Identity::new(), self, &mut JohnsonSimplex::<N, P, V>::new_w_tls(), ray, solid)
+    // The tolerance is relative to the size of the point cloud. It must be larger than the
+    // precision of the GJK ray cast.
+    let mut mins = pts[0].clone();
+    let mut maxs = pts[0].clone();
+
+    for p in pts.iter() {
+        mins = na::inf(&mins, p);
+        maxs = na::sup(&maxs, p);
+    }
+
+    let _eps: N = Float::epsilon();
+    let tol     = _eps.sqrt() * na::cast(100.0f64) * na::norm(&(maxs - mins));
+
+    // The points on the supporting plane, in increasing index order.
+    let support: Vec<uint> = range(0, n).filter(|i| na::dot(&(pts[*i] - pt), &normal).abs() <= tol).collect();
+
+    for i in support.iter() {
+        if na::sqdist(&pts[*i], &pt) <= tol * tol {
+            return FeatureId::Vertex(*i);
+        }
+    }
+
+    if support.is_empty() {
+        return FeatureId::Unknown;
+    }
+
+    // The largest identifier is `n^2` in 2d and for edges, and `n^3` for faces in 3d.
+    let edges_fit = uint::MAX / n >= n;
+    let faces_fit = edges_fit && uint::MAX / n / n >= n;
+
+    let a = support[0];
+    let b = match support.iter().find(|i| na::sqdist(&pts[**i], &pts[a]) > tol * tol) {
+        Some(b) => *b,
+        None    => return FeatureId::Unknown
+    };
+
+    match support.iter().find(|i| line_distance(&pts[a], &pts[b], &pts[**i]) > tol) {
+        None => {
+            // The supporting points are aligned.
+            if !edges_fit {
+                FeatureId::Unknown
+            }
+            else if dim == 2 {
+                FeatureId::Face(a * n + b)
+            }
+            else {
+                FeatureId::Edge(a * n + b)
+            }
+        },
+        Some(c) => {
+            if dim != 3 || !faces_fit {
+                return FeatureId::Unknown;
+            }
+
+            // The hit point may be on the boundary of the face.
+            for (id, i) in support.iter().enumerate() {
+                for j in support.slice_from(id + 1).iter() {
+                    if is_boundary_edge(pts, support.as_slice(), *i, *j, &pt, &normal, tol) {
+                        let mut edge = support.iter().filter(|k| line_distance(&pts[*i], &pts[*j], &pts[**k]) <= tol);
+
+                        // The first point of the edge is `i` itself, or a point closer than `tol`
+                        // to the line. The second one may then be missed if `i` and `j` are barely
+                        // distinguishable.
+                        return match edge.next() {
+                            Some(e1) => {
+                                match edge.find(|k| na::sqdist(&pts[**k], &pts[*e1]) > tol * tol) {
+                                    Some(e2) => FeatureId::Edge(*e1 * n + *e2),
+                                    None     => FeatureId::Unknown
+                                }
+                            },
+                            None => FeatureId::Unknown
+                        };
+                    }
+                }
+            }
+
+            FeatureId::Face((a * n + b) * n + *c)
+        }
     }
 }
 
-impl<N, P, V, M> RayCast<N, P, V, M> for Convex<P>
+// Distance from `p` to the line passing through `a` and `b`.
+fn line_distance<N, P, V>(a: &P, b: &P, p: &P) -> N
     where N: Scalar,
           P: Point<N, V>,
-          V: Vect<N>,
-          M: Transform<P> + Rotate<V> {
+          V: Vect<N> {
+    let dir = na::normalize(&(*b - *a));
+    let ap  = *p - *a;
+
+    na::norm(&(ap - dir * na::dot(&ap, &dir)))
 }
 
-impl<N, P, V> LocalRayCast<N, P, V> for Segment<P>
+// Tests if `pt` lies on the segment `[pts[i], pts[j]]`, and if this segment is on the boundary of
+// the 3d face formed by the points `support` with the normal `normal`.
+fn is_boundary_edge<N, P, V>(pts: &[P], support: &[uint], i: uint, j: uint, pt: &P, normal: &V, tol: N) -> bool
     where N: Scalar,
           P: Point<N, V>,
           V: Vect<N> {
-    fn toi_and_normal_with_ray(&self, ray: &Ray<P, V>, solid: bool) -> Option<RayIntersection<N, V>> {
-        // XXX: optimize if na::dim::<P, V>() == 2
-        implicit_toi_and_normal_with_ray(&Identity::new(), self, &mut JohnsonSimplex::<N, P, V>::new_w_tls(), ray, solid)
+    let ab = pts[j] - pts[i];
+    let l  = na::norm(&ab);
+
+    if l <= tol {
+        return false;
     }
+
+    let t    = na::clamp(na::dot(&(*pt - pts[i]), &ab) / (l * l), na::zero(), na::one());
+    let proj = pts[i] + ab * t;
+
+    if na::sqdist(pt, &proj) > tol * tol {
+        return false;
+    }
+
+    let mut pos = false;
+    let mut neg = false;
+
+    for k in support.iter() {
+        let side = na::dot(&utils::cross3(&ab, &(pts[*k] - pts[i])), normal);
+
+        pos = pos || side > tol * l;
+        neg = neg || side < -tol * l;
+    }
+
+    !(pos && neg)
 }
 
-impl<N, P, V, M> RayCast<N, P, V, M> for Segment<P>
+impl<N, P, V, M> RayCast<N, P, V, M> for Convex<P>
     where N: Scalar,
           P: Point<N, V>,
           V: Vect<N>,
@@ -2,7 +2,7 @@ use na::{Vec3, Identity, Transform, Rotate};
 use na;
 use geometry::algorithms::johnson_simplex::JohnsonSimplex;
 use shape::Triangle;
-use ray::{Ray, LocalRayCast, RayCast, RayIntersection, implicit_toi_and_normal_with_ray};
+use ray::{Ray, LocalRayCast, RayCast, RayIntersection, RayCastOptions, implicit_toi_and_normal_with_ray};
 use math::{Scalar, Point, Vect};
 
 use utils;
@@ -20,6 +20,31 @@ impl<N, P, V> LocalRayCast<N, P, V> for Triangle<P>
             implicit_toi_and_normal_with_ray(&Identity::new(), self, &mut JohnsonSimplex::<N, P, V>::new_w_tls(), ray, solid)
         }
     }
+
+    #[inline]
+    fn toi_with_ray_and_options(&self, ray: &Ray<P, V>, options: &RayCastOptions<N>) -> Option<N> {
+        self.toi_and_normal_with_ray_and_options(ray, options).map(|inter| inter.toi)
+    }
+
+    #[inline]
+    fn toi_and_normal_with_ray_and_options(&self, ray: &Ray<P, V>, options: &RayCastOptions<N>)
+                                           -> Option<RayIntersection<N, V>> {
+        if na::dim::<P>() == 3 {
+            triangle_ray_intersection_with_options(self.a(), self.b(), self.c(), ray, options).map(|(r, _)| r)
+        }
+        else {
+            match self.toi_and_normal_with_ray(ray, options.solid()) {
+                Some(inter) => if options.accepts_toi(&inter.toi) { Some(inter) } else { None },
+                None        => None
+            }
+        }
+    }
+
+    #[inline]
+    fn toi_and_normal_and_uv_with_ray_and_options(&self, ray: &Ray<P, V>, options: &RayCastOptions<N>)
+                                                  -> Option<RayIntersection<N, V>> {
+        self.toi_and_normal_with_ray_and_options(ray, options)
+    }
 }
 
 impl<N, P, V, M> RayCast<N, P, V, M> for Triangle<P>
@@ -112,3 +137,36 @@ pub fn triangle_ray_intersection<N, P, V>(a: &P, b: &P, c: &P, ray: &Ray<P, V>)
 
     Some((RayIntersection::new(toi, normal), Vec3::new(-v - w + na::one(), v, w)))
 }
+
+/// Computes the intersection between a triangle and a ray, with the given options.
+///
+/// If back-face culling is enabled, the triangle is hit only if it appears counterclockwise from
+/// the ray origin. Since a triangle has no interior, the `inside` mode only matters for rays
+/// starting on its plane.
+pub fn triangle_ray_intersection_with_options<N, P, V>(a: &P, b: &P, c: &P, ray: &Ray<P, V>,
+                                                       options: &RayCastOptions<N>)
+                                                       -> Option<(RayIntersection<N, V>, Vec3<N>)>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> {
+    if options.cull_back_faces {
+        let n = utils::cross3(&(*b - *a), &(*c - *a));
+
+        if na::dot(&n, &ray.dir) >= na::zero() {
+            // The ray sees the back face.
+            return None;
+        }
+    }
+
+    match triangle_ray_intersection(a, b, c, ray) {
+        Some(res) => {
+            if options.accepts_toi(&res.ref0().toi) {
+                Some(res)
+            }
+            else {
+                None
+            }
+        },
+        None => None
+    }
+}
@@ -8,7 +8,7 @@ use na::{Cross, Translate, Zero};
 use na;
 use bounding_volume::{AABB, HasAABB, BoundingVolume};
 use volumetric::{Volumetric, InertiaTensor};
-use partitioning::BVT;
+use partitioning::{BVT, BVTVisitor, BVTCostFn};
 use shape::{Shape, ConcaveShape};
 use math::{Scalar, Point, Vect, Isometry, HasInertiaMatrix};
 
@@ -102,6 +102,20 @@ impl<N, P, V, AV, M, I> Compound<N, P, V, M>
           I: Send + Sync + Zero + Add<I, I> + Mul<N, I> + InertiaTensor<N, P, AV, M> + Any {
     /// Builds a new compound shape.
     pub fn new(data: CompoundData<N, P, V, M, I>) -> Compound<N, P, V, M> {
+        Compound::new_with_bvt_builder(data, |leaves| BVT::new_balanced(leaves))
+    }
+
+    /// Builds a new compound shape with a `BVT` constructed using the Surface Area Heuristic.
+    pub fn new_sah(data: CompoundData<N, P, V, M, I>) -> Compound<N, P, V, M> {
+        Compound::new_with_bvt_builder(data, |leaves| BVT::new_sah(leaves))
+    }
+
+    /// Builds a new compound shape with a user-defined `BVT` construction function.
+    ///
+    /// The construction function is given the AABB of each part, along with its index.
+    pub fn new_with_bvt_builder(data:    CompoundData<N, P, V, M, I>,
+                                builder: |Vec<(uint, AABB<P>)>| -> BVT<uint, AABB<P>>)
+                                -> Compound<N, P, V, M> {
         let mut bvs    = Vec::new();
         let mut leaves = Vec::new();
 
@@ -112,7 +126,7 @@ impl<N, P, V, AV, M, I> Compound<N, P, V, M>
             leaves.push((i, bv));
         }
 
-        let bvt = BVT::new_balanced(leaves);
+        let bvt = builder(leaves);
 
         let (mass, com, inertia) = data.mass_properties(na::one());
         let surface = data.surface();
@@ -204,7 +218,13 @@ impl<N, P, V, M> ConcaveShape<N, P, V, M> for Compound<N, P, V, M>
     }
 
     #[inline]
-    fn bvt(&self) -> &BVT<uint, AABB<P>> {
-        &self.bvt
+    fn visit_bvt<Vis: BVTVisitor<uint, AABB<P>>>(&self, visitor: &mut Vis) {
+        self.bvt.visit(visitor)
+    }
+
+    #[inline]
+    fn best_first_search_bvt<'a, BFS, R>(&'a self, algorithm: &mut BFS) -> Option<(&'a uint, R)>
+        where BFS: BVTCostFn<N, uint, AABB<P>, R> {
+        self.bvt.best_first_search(algorithm)
     }
 }
@@ -1,11 +1,12 @@
 //! 2d line strip, 3d triangle Mesh, and nd subsimplex mesh.
 
 use std::sync::Arc;
-use na::{Translate, Rotate, Transform, AbsoluteRotate, Translation, Identity, Pnt2, One};
+use na::{Translate, Rotate, Transform, AbsoluteRotate, Translation, Identity, Pnt2, Pnt3, One};
 use na;
-use partitioning::BVT;
-use bounding_volume::{HasAABB, AABB};
-use shape::{Shape, ConcaveShape};
+use partitioning::{BVT, FlatBVT, BVTVisitor, BVTTVisitor, BVTCostFn};
+use bounding_volume::{HasAABB, AABB, BoundingVolume};
+use shape::{Shape, ConcaveShape, Mesh3};
+use utils;
 use math::{Scalar, Point, Vect};
 
 
@@ -19,8 +20,9 @@ pub trait MeshElement<P> {
 
 /// Shapeetry commonly known as a 2d line strip or a 3d triangle mesh.
 pub struct Mesh<N, P, V, E> {
-    bvt:      BVT<uint, AABB<P>>,
-    bvs:      Vec<AABB<P>>,
+    flat_bvt:   FlatBVT<uint, AABB<P>>,
+    build_cost: N,
+    bvs:        Vec<AABB<P>>,
     vertices: Arc<Vec<P>>,
     indices:  Arc<Vec<uint>>,
     uvs:      Option<Arc<Vec<Pnt2<N>>>>,
@@ -33,12 +35,13 @@ impl<N, P, V, E> Clone for Mesh<N, P, V, E>
           V: Send + Sync {
     fn clone(&self) -> Mesh<N, P, V, E> {
         Mesh {
-            bvt:      self.bvt.clone(),
-            bvs:      self.bvs.clone(),
-            vertices: self.vertices.clone(),
-            indices:  self.indices.clone(),
-            uvs:      self.uvs.clone(),
-            normals:  self.normals.clone()
+            flat_bvt:   self.flat_bvt.clone(),
+            build_cost: self.build_cost.clone(),
+            bvs:        self.bvs.clone(),
+            vertices:   self.vertices.clone(),
+            indices:    self.indices.clone(),
+            uvs:        self.uvs.clone(),
+            normals:    self.normals.clone()
         }
     }
 }
@@ -54,42 +57,122 @@ impl<N, P, V, E> Mesh<N, P, V, E>
                uvs:      Option<Arc<Vec<Pnt2<N>>>>,
                normals:  Option<Arc<Vec<V>>>) // a loosening margin for the BVT.
                -> Mesh<N, P, V, E> {
+        Mesh::new_with_bvt_builder(vertices, indices, uvs, normals,
+                                   |leaves| BVT::new_balanced(leaves))
+    }
+
+    /// Builds a new mesh with a `BVT` constructed using the Surface Area Heuristic.
+    ///
+    /// This is slower to build than `Mesh::new` but usually yields faster ray casts and
+    /// proximity queries.
+    pub fn new_sah(vertices: Arc<Vec<P>>,
+                   indices:  Arc<Vec<uint>>,
+                   uvs:      Option<Arc<Vec<Pnt2<N>>>>,
+                   normals:  Option<Arc<Vec<V>>>)
+                   -> Mesh<N, P, V, E> {
+        Mesh::new_with_bvt_builder(vertices, indices, uvs, normals,
+                                   |leaves| BVT::new_sah(leaves))
+    }
+
+    /// Builds a new mesh, constructing its `BVT` with at most `nthreads` threads.
+    ///
+    /// The `BVT` is constructed using the Surface Area Heuristic, and the resulting mesh is the
+    /// same as with `Mesh::new_sah`.
+    pub fn new_parallel(vertices: Arc<Vec<P>>,
+                        indices:  Arc<Vec<uint>>,
+                        uvs:      Option<Arc<Vec<Pnt2<N>>>>,
+                        normals:  Option<Arc<Vec<V>>>,
+                        nthreads: uint)
+                        -> Mesh<N, P, V, E> {
+        Mesh::new_with_bvt_builder(vertices, indices, uvs, normals,
+                                   |leaves| BVT::new_sah_parallel(leaves, nthreads))
+    }
+
+    /// Builds a new mesh with a user-defined `BVT` construction function.
+    ///
+    /// The construction function is given the AABB of each element, along with its index.
+    pub fn new_with_bvt_builder(vertices: Arc<Vec<P>>,
+                                indices:  Arc<Vec<uint>>,
+                                uvs:      Option<Arc<Vec<Pnt2<N>>>>,
+                                normals:  Option<Arc<Vec<V>>>,
+                                builder:  |Vec<(uint, AABB<P>)>| -> BVT<uint, AABB<P>>)
+                                -> Mesh<N, P, V, E> {
         assert!(indices.len() % MeshElement::nvertices(None::<E>) == 0);
 
         for uvs in uvs.iter() {
             assert!(uvs.len() == vertices.len());
         }
 
-        let mut leaves = Vec::new();
-        let mut bvs    = Vec::new();
+        let bvs    = element_aabbs::<N, P, V, E>(vertices.as_slice(), indices.as_slice());
+        let leaves = bvs.iter().enumerate().map(|(i, bv)| (i, bv.clone())).collect();
+        let bvt    = FlatBVT::new_from_bvt(builder(leaves));
+        let cost   = bvt.surface_area_cost();
 
-        {
-            let vs = vertices.deref();
-            let is = indices.deref();
-
-            for (i, is) in is.as_slice().chunks(MeshElement::nvertices(None::<E>)).enumerate() {
-                let vs: &[P] = vs.as_slice();
-                let element: E = MeshElement::new_with_vertices_and_indices(vs, is);
-                // loosen for better persistancy
-                let bv = element.aabb(&Identity::new());
-                leaves.push((i, bv.clone()));
-                bvs.push(bv);
-            }
+        Mesh {
+            flat_bvt:   bvt,
+            build_cost: cost,
+            bvs:        bvs,
+            vertices:   vertices,
+            indices:    indices,
+            uvs:        uvs,
+            normals:    normals
         }
+    }
 
-        let bvt = BVT::new_balanced(leaves);
+    /// Replaces the vertices of this mesh, keeping its topology.
+    ///
+    /// The bounding volumes of the elements are recomputed and the acceleration structure is
+    /// refitted in place. Its structure is left unchanged so its quality may degrade if the
+    /// vertices move a lot. Use `update_vertices_with_rebuild` to rebuild it when needed.
+    pub fn update_vertices(&mut self, vertices: Arc<Vec<P>>) {
+        assert!(vertices.len() == self.vertices.len(),
+                "The number of vertices of a mesh cannot change during an update.");
 
-        Mesh {
-            bvt:      bvt,
-            bvs:      bvs,
-            vertices: vertices,
-            indices:  indices,
-            uvs:      uvs,
-            normals:  normals
+        self.bvs      = element_aabbs::<N, P, V, E>(vertices.as_slice(), self.indices.as_slice());
+        self.vertices = vertices;
+
+        {
+            let bvs = &self.bvs;
+            self.flat_bvt.refit(&mut |i| bvs[*i].clone());
+        }
+    }
+
+    /// Replaces the vertices of this mesh and rebuilds its acceleration structure if its quality
+    /// degraded too much.
+    ///
+    /// The acceleration structure is first refitted as with `update_vertices`. Then, if its `surface_area_cost`
+    /// exceeds `rebuild_threshold` times the cost it had after its last construction, it is
+    /// rebuilt from scratch with `builder`. This should be the builder used to create this mesh,
+    /// e.g., `BVT::new_sah` for a mesh created with `Mesh::new_sah`.
+    pub fn update_vertices_with_rebuild(&mut self,
+                                        vertices:          Arc<Vec<P>>,
+                                        rebuild_threshold: N,
+                                        builder:           |Vec<(uint, AABB<P>)>| -> BVT<uint, AABB<P>>) {
+        self.update_vertices(vertices);
+
+        if self.flat_bvt.surface_area_cost() > self.build_cost * rebuild_threshold {
+            let leaves      = self.bvs.iter().enumerate().map(|(i, bv)| (i, bv.clone())).collect();
+            self.flat_bvt   = FlatBVT::new_from_bvt(builder(leaves));
+            self.build_cost = self.flat_bvt.surface_area_cost();
         }
     }
 }
 
+fn element_aabbs<N, P, V, E>(vertices: &[P], indices: &[uint]) -> Vec<AABB<P>>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Translate<P> + Vect<N>,
+          E: MeshElement<P> + HasAABB<P, Identity> {
+    let mut bvs = Vec::new();
+
+    for is in indices.chunks(MeshElement::nvertices(None::<E>)) {
+        let element: E = MeshElement::new_with_vertices_and_indices(vertices, is);
+        bvs.push(element.aabb(&Identity::new()));
+    }
+
+    bvs
+}
+
 /* // FIXME: implement for Mesh3d
 impl<P, V, E: MeshElement<P>> Mesh<P, V, E> {
     /// Builds a new mesh from a triangle mesh.
@@ -148,9 +231,11 @@ impl<N, P, V, E: MeshElement<P>> Mesh<N, P, V, E> {
     }
 
     /// The acceleration structure used for efficient collision detection and ray casting.
+    ///
+    /// Its nodes are stored on a contiguous buffer.
     #[inline]
-    pub fn bvt(&self) -> &BVT<uint, AABB<P>> {
-        &self.bvt
+    pub fn flat_bvt(&self) -> &FlatBVT<uint, AABB<P>> {
+        &self.flat_bvt
     }
 }
 
@@ -166,6 +251,75 @@ impl<N, P: Send + Sync, V, E: MeshElement<P>> Mesh<N, P, V, E> {
     }
 }
 
+impl<N: Scalar> Mesh3<N> {
+    /// Computes all the pairs of intersecting triangles of this mesh.
+    ///
+    /// Triangles sharing a vertex or an edge are not considered intersecting. Each pair `(i, j)`
+    /// is reported once, with `i < j`.
+    pub fn self_intersections(&self) -> Vec<(uint, uint)> {
+        let mut res = Vec::new();
+
+        {
+            let mut visitor = SelfIntersectionsCollector {
+                vertices: self.vertices.as_slice(),
+                indices:  self.indices.as_slice(),
+                out:      &mut res
+            };
+
+            self.flat_bvt.visit_self_bvtt(&mut visitor);
+        }
+
+        res
+    }
+}
+
+struct SelfIntersectionsCollector<'a, N: 'a> {
+    vertices: &'a [Pnt3<N>],
+    indices:  &'a [uint],
+    out:      &'a mut Vec<(uint, uint)>
+}
+
+impl<'a, N: Scalar> SelfIntersectionsCollector<'a, N> {
+    fn are_adjacent(&self, i: uint, j: uint) -> bool {
+        let ti = self.indices.slice(i * 3, i * 3 + 3);
+        let tj = self.indices.slice(j * 3, j * 3 + 3);
+
+        ti.iter().any(|a| tj.iter().any(|b| *a == *b || self.vertices[*a] == self.vertices[*b]))
+    }
+}
+
+impl<'a, N: Scalar> BVTTVisitor<uint, AABB<Pnt3<N>>> for SelfIntersectionsCollector<'a, N> {
+    #[inline]
+    fn visit_internal_internal(&mut self, a: &AABB<Pnt3<N>>, b: &AABB<Pnt3<N>>) -> bool {
+        a.intersects(b)
+    }
+
+    #[inline]
+    fn visit_internal_leaf(&mut self, a: &AABB<Pnt3<N>>, _: &uint, b: &AABB<Pnt3<N>>) -> bool {
+        a.intersects(b)
+    }
+
+    #[inline]
+    fn visit_leaf_internal(&mut self, _: &uint, a: &AABB<Pnt3<N>>, b: &AABB<Pnt3<N>>) -> bool {
+        a.intersects(b)
+    }
+
+    fn visit_leaf_leaf(&mut self, i: &uint, bvi: &AABB<Pnt3<N>>, j: &uint, bvj: &AABB<Pnt3<N>>) {
+        if !bvi.intersects(bvj) || self.are_adjacent(*i, *j) {
+            return;
+        }
+
+        let vs = self.vertices;
+        let ti = self.indices.slice(*i * 3, *i * 3 + 3);
+        let tj = self.indices.slice(*j * 3, *j * 3 + 3);
+
+        if utils::triangles_intersect3(&vs[ti[0]], &vs[ti[1]], &vs[ti[2]],
+                                       &vs[tj[0]], &vs[tj[1]], &vs[tj[2]]) {
+            self.out.push((na::min(*i, *j), na::max(*i, *j)))
+        }
+    }
+}
+
 impl<N, P, V, M, E> ConcaveShape<N, P, V, M> for Mesh<N, P, V, E>
     where N: Scalar,
           P: Point<N, V>,
@@ -192,7 +346,70 @@ impl<N, P, V, M, E> ConcaveShape<N, P, V, M> for Mesh<N, P, V, E>
     }
 
     #[inline]
-    fn bvt(&self) -> &BVT<uint, AABB<P>> {
-        &self.bvt
+    fn visit_bvt<Vis: BVTVisitor<uint, AABB<P>>>(&self, visitor: &mut Vis) {
+        self.flat_bvt.visit(visitor)
+    }
+
+    #[inline]
+    fn best_first_search_bvt<'a, BFS, R>(&'a self, algorithm: &mut BFS) -> Option<(&'a uint, R)>
+        where BFS: BVTCostFn<N, uint, AABB<P>, R> {
+        self.flat_bvt.best_first_search(algorithm)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+    use na::{Pnt3, Vec3};
+    use na;
+    use partitioning::BVT;
+    use ray::{Ray, LocalRayCast};
+    use shape::Mesh3;
+
+    // Four triangles on the plane z = 0, with their lower-left corners at the given abscissae.
+    fn triangles(xs: &[f64]) -> Arc<Vec<Pnt3<f64>>> {
+        let mut vertices = Vec::new();
+
+        for x in xs.iter() {
+            vertices.push(Pnt3::new(*x, 0.0, 0.0));
+            vertices.push(Pnt3::new(*x + 1.0, 0.0, 0.0));
+            vertices.push(Pnt3::new(*x, 1.0, 0.0));
+        }
+
+        Arc::new(vertices)
+    }
+
+    fn hits(mesh: &Mesh3<f64>, x: f64) -> bool {
+        let ray = Ray::new(Pnt3::new(x + 0.2, 0.2, -1.0), Vec3::new(0.0, 0.0, 1.0));
+
+        mesh.toi_with_ray(&ray, true).is_some()
+    }
+
+    #[test]
+    fn test_mesh_update_vertices_refit_and_rebuild() {
+        let indices = Arc::new(range(0u, 12).collect());
+        let mut mesh: Mesh3<f64> = Mesh3::new(triangles(&[0.0, 0.1, 10.0, 10.1]), indices, None, None);
+        let build_cost = mesh.flat_bvt().surface_area_cost();
+
+        // A small motion: the refitted tree is good enough.
+        let mut rebuilt = false;
+        mesh.update_vertices_with_rebuild(triangles(&[0.5, 0.6, 10.5, 10.6]), 1.5,
+                                          |leaves| { rebuilt = true; BVT::new_balanced(leaves) });
+
+        assert!(!rebuilt);
+        assert!(hits(&mesh, 0.5) && hits(&mesh, 10.5) && !hits(&mesh, 5.0));
+
+        // Swap the clusters: both children of the root now span the whole mesh.
+        mesh.update_vertices(triangles(&[10.0, 0.1, 0.0, 10.1]));
+
+        assert!(mesh.flat_bvt().surface_area_cost() > build_cost * 1.5);
+        assert!(hits(&mesh, 0.0) && hits(&mesh, 10.0) && !hits(&mesh, 5.0));
+
+        mesh.update_vertices_with_rebuild(triangles(&[10.0, 0.1, 0.0, 10.1]), 1.5,
+                                          |leaves| { rebuilt = true; BVT::new_balanced(leaves) });
+
+        assert!(rebuilt);
+        assert!(na::approx_eq(&mesh.flat_bvt().surface_area_cost(), &build_cost));
+        assert!(hits(&mesh, 0.0) && hits(&mesh, 10.0) && !hits(&mesh, 5.0));
     }
 }
@@ -4,7 +4,7 @@ use std::mem;
 use std::any::{Any, AnyRefExt};
 use ray::RayCast;
 use point::PointQuery;
-use partitioning::BVT;
+use partitioning::{BVTVisitor, BVTCostFn};
 use bounding_volume::{HasBoundingSphere, HasAABB, AABB};
 
 /// Trait (that should be) implemented by every shape.
@@ -35,11 +35,16 @@ pub trait ConcaveShape<N, P, V, M> : Shape<N, P, V, M> {
     /// shape.
     fn map_transformed_part_at<T>(&self, m: &M, uint, |&M, &Shape<N, P, V, M>| -> T) -> T;
 
-    // FIXME: the following two methods really are not generic enough.
+    // FIXME: the following methods really are not generic enough.
     /// Gets the AABB of the shape identified by the index `i`.
     fn aabb_at(&self, i: uint) -> &AABB<P>;
-    /// Gets the acceleration structure of the concave shape.
-    fn bvt(&self) -> &BVT<uint, AABB<P>>;
+    /// Visits the acceleration structure of the concave shape.
+    ///
+    /// Its leaves are the indices of the sub-shapes.
+    fn visit_bvt<Vis: BVTVisitor<uint, AABB<P>>>(&self, visitor: &mut Vis);
+    /// Performs a best-first search on the acceleration structure of the concave shape.
+    fn best_first_search_bvt<'a, BFS, R>(&'a self, algorithm: &mut BFS) -> Option<(&'a uint, R)>
+        where BFS: BVTCostFn<N, uint, AABB<P>, R>;
 }
 
 impl<N, P, V, M, T> Shape<N, P, V, M> for T
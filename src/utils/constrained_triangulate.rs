@@ -0,0 +1,1001 @@
+//! Constrained Delaunay triangulation of planar straight-line graphs.
+
+use std::num::Float;
+use std::collections::{HashMap, HashSet};
+use na::{Pnt2, Vec3, Bounded};
+use na;
+use procedural::{TriMesh2, TriMesh, IndexBuffer, Polyline2};
+use utils;
+use bounding_volume;
+use math::Scalar;
+
+struct CDTTriangle<N> {
+    idx:      Vec3<uint>, // counterclockwise.
+    center:   Pnt2<N>,
+    sqradius: N,
+    inside:   bool,
+    valid:    bool
+}
+
+impl<N: Scalar> CDTTriangle<N> {
+    fn circumcircle_contains_point(&self, pt: &Pnt2<N>) -> bool {
+        na::sqdist(pt, &self.center) < self.sqradius
+    }
+
+    fn edges(&self) -> [(uint, uint), ..3] {
+        [ (self.idx.x, self.idx.y), (self.idx.y, self.idx.z), (self.idx.z, self.idx.x) ]
+    }
+}
+
+/// Parameters of the Ruppert refinement performed by the `ConstrainedTriangulator`.
+#[deriving(Clone, Show)]
+pub struct RefinementParams<N> {
+    /// The minimum angle (in radian) any output triangle should have.
+    ///
+    /// The refinement is guaranteed to terminate for angles smaller than about `0.36` (20.7
+    /// degrees). Greater values usually work but might end up limited by `max_steiner_points`.
+    pub min_angle:          N,
+    /// The maximum area any output triangle should have.
+    pub max_area:           N,
+    /// The maximum number of points the refinement is allowed to add.
+    pub max_steiner_points: uint
+}
+
+impl<N: Scalar> RefinementParams<N> {
+    /// Creates refinement parameters with a bound on the minimum angle and on the maximum area.
+    pub fn new(min_angle: N, max_area: N) -> RefinementParams<N> {
+        RefinementParams {
+            min_angle:          min_angle,
+            max_area:           max_area,
+            max_steiner_points: 100000
+        }
+    }
+
+    /// Creates refinement parameters with a bound on the minimum angle only.
+    pub fn new_with_min_angle(min_angle: N) -> RefinementParams<N> {
+        RefinementParams::new(min_angle, Bounded::max_value())
+    }
+}
+
+/// Errors returned by the constrained triangulation.
+#[deriving(Clone, Show)]
+pub enum ConstrainedTriangulationError<N> {
+    /// Some constrained edges could not be recovered.
+    ///
+    /// The triangulation obtained so far is given, without refinement. It does not contain every
+    /// constrained edge.
+    UnrecoveredConstraints(TriMesh2<N>)
+}
+
+/// Constrained Delaunay triangulation utility.
+///
+/// Accumulates outer boundaries, holes, isolated points and required edges. The triangulation
+/// itself is performed by `to_trimesh`. Regions are classified using the even-odd rule: a
+/// triangle is kept if it lies inside of an odd number of boundaries and holes.
+pub struct ConstrainedTriangulator<N> {
+    vertices:    Vec<Pnt2<N>>,
+    triangles:   Vec<CDTTriangle<N>>,
+    // Directed edge -> index of the counterclockwise triangle containing it.
+    edges:       HashMap<(uint, uint), uint>,
+    constraints: HashSet<(uint, uint)>,
+    loops:       Vec<Vec<Pnt2<N>>>,
+    input_edges: Vec<(uint, uint)>,
+    refinement:  Option<RefinementParams<N>>
+}
+
+impl<N: Scalar> ConstrainedTriangulator<N> {
+    /// Creates a new, empty, constrained triangulator.
+    pub fn new() -> ConstrainedTriangulator<N> {
+        ConstrainedTriangulator {
+            vertices:    Vec::new(),
+            triangles:   Vec::new(),
+            edges:       HashMap::new(),
+            constraints: HashSet::new(),
+            loops:       Vec::new(),
+            input_edges: Vec::new(),
+            refinement:  None
+        }
+    }
+
+    /// Adds a closed outer boundary.
+    ///
+    /// The last point is implicitly linked to the first one.
+    pub fn push_boundary(&mut self, pts: &[Pnt2<N>]) {
+        self.push_loop(pts)
+    }
+
+    /// Adds a closed hole.
+    ///
+    /// The last point is implicitly linked to the first one.
+    pub fn push_hole(&mut self, pts: &[Pnt2<N>]) {
+        // NOTE: with the even-odd rule, holes and boundaries are handled the same way.
+        self.push_loop(pts)
+    }
+
+    /// Adds an isolated point that must be part of the triangulation.
+    pub fn push_point(&mut self, pt: Pnt2<N>) {
+        let _ = self.input_vertex(pt);
+    }
+
+    /// Adds an edge that must be part of the triangulation.
+    pub fn push_constraint(&mut self, a: Pnt2<N>, b: Pnt2<N>) {
+        let ia = self.input_vertex(a);
+        let ib = self.input_vertex(b);
+
+        if ia != ib {
+            self.input_edges.push((ia, ib));
+        }
+    }
+
+    /// Enables the Ruppert refinement of the triangulation.
+    pub fn refine_with(&mut self, params: RefinementParams<N>) {
+        assert!(params.min_angle >= na::zero(), "The minimum angle must be positive.");
+        assert!(params.max_area > na::zero(), "The maximum area must be strictly positive.");
+        self.refinement = Some(params)
+    }
+
+    /// Performs the triangulation and returns the resulting mesh.
+    ///
+    /// Returns an error if some constrained edges could not be recovered.
+    pub fn to_trimesh(mut self) -> Result<TriMesh2<N>, ConstrainedTriangulationError<N>> {
+        if self.vertices.len() < 3 {
+            return Ok(TriMesh::new(self.vertices, None, None, Some(IndexBuffer::Unified(Vec::new()))));
+        }
+
+        self.init_supertriangle();
+
+        // Insert every input point (the three first points are the super-triangle).
+        for i in range(3u, self.vertices.len()) {
+            self.insert_existing_vertex(i);
+        }
+
+        // Recover the constrained edges.
+        let input_edges = self.input_edges.clone();
+        let mut recovered = true;
+
+        for &(a, b) in input_edges.iter() {
+            recovered = self.recover_edge(a + 3, b + 3) && recovered;
+        }
+
+        if !recovered {
+            return Err(ConstrainedTriangulationError::UnrecoveredConstraints(self.output()));
+        }
+
+        let refinement = self.refinement.clone();
+
+        for params in refinement.iter() {
+            self.refine(params);
+        }
+
+        Ok(self.output())
+    }
+
+    fn push_loop(&mut self, pts: &[Pnt2<N>]) {
+        if pts.len() < 3 {
+            return;
+        }
+
+        let ids: Vec<uint> = pts.iter().map(|pt| self.input_vertex(pt.clone())).collect();
+
+        for i in range(0u, ids.len()) {
+            let a = ids[i];
+            let b = ids[(i + 1) % ids.len()];
+
+            if a != b {
+                self.input_edges.push((a, b));
+            }
+        }
+
+        self.loops.push(pts.to_vec());
+    }
+
+    fn input_vertex(&mut self, pt: Pnt2<N>) -> uint {
+        match self.vertices.iter().position(|v| *v == pt) {
+            Some(i) => i,
+            None    => {
+                self.vertices.push(pt);
+                self.vertices.len() - 1
+            }
+        }
+    }
+
+    /*
+     * Initialization.
+     */
+    fn init_supertriangle(&mut self) {
+        let (center, radius) = bounding_volume::point_cloud_bounding_sphere(self.vertices.as_slice());
+        let radius = (radius + na::one()) * na::cast(10.0f64);
+
+        // Compute a triangle with (center, radius) as its inscribed circle.
+        let pi: N       = Float::pi();
+        let right_shift = radius / (pi / na::cast(6.0f64)).tan();
+        let up_shift    = (right_shift * right_shift + radius * radius).sqrt();
+
+        let top    = Pnt2::new(center.x, center.y + up_shift);
+        let bleft  = Pnt2::new(center.x - right_shift, center.y - radius);
+        let bright = Pnt2::new(center.x + right_shift, center.y - radius);
+
+        let mut vertices = vec!(bleft, bright, top);
+        vertices.push_all(self.vertices.as_slice());
+        self.vertices = vertices;
+
+        let _ = self.add_triangle(0, 1, 2);
+    }
+
+    /*
+     * Basic mesh operations.
+     */
+    fn is_inside(&self, pt: &Pnt2<N>) -> bool {
+        if self.loops.is_empty() {
+            return true;
+        }
+
+        // Even-odd rule.
+        let mut inside = false;
+
+        for l in self.loops.iter() {
+            for i in range(0u, l.len()) {
+                let a = &l[i];
+                let b = &l[(i + 1) % l.len()];
+
+                if (a.y > pt.y) != (b.y > pt.y) {
+                    let x = a.x + (pt.y - a.y) * (b.x - a.x) / (b.y - a.y);
+
+                    if pt.x < x {
+                        inside = !inside;
+                    }
+                }
+            }
+        }
+
+        inside
+    }
+
+    fn add_triangle(&mut self, a: uint, b: uint, c: uint) -> uint {
+        let idx = if orient(&self.vertices[a], &self.vertices[b], &self.vertices[c]) >= na::zero() {
+            Vec3::new(a, b, c)
+        }
+        else {
+            Vec3::new(a, c, b)
+        };
+
+        let (center, radius) = utils::circumcircle(&self.vertices[idx.x],
+                                                   &self.vertices[idx.y],
+                                                   &self.vertices[idx.z]);
+        let inside = idx.x > 2 && idx.y > 2 && idx.z > 2 && {
+            let centroid = utils::center(&[ self.vertices[idx.x].clone(),
+                                            self.vertices[idx.y].clone(),
+                                            self.vertices[idx.z].clone() ]);
+            self.is_inside(&centroid)
+        };
+
+        let id = self.triangles.len();
+
+        self.triangles.push(CDTTriangle {
+            idx:      idx,
+            center:   center,
+            sqradius: radius * radius,
+            inside:   inside,
+            valid:    true
+        });
+
+        let edges = self.triangles[id].edges();
+
+        for e in edges.iter() {
+            let _ = self.edges.insert(*e, id);
+        }
+
+        id
+    }
+
+    fn remove_triangle(&mut self, id: uint) {
+        self.triangles[id].valid = false;
+
+        let edges = self.triangles[id].edges();
+
+        for e in edges.iter() {
+            let remove = match self.edges.get(e) {
+                Some(t) => *t == id,
+                None    => false
+            };
+
+            if remove {
+                let _ = self.edges.remove(e);
+            }
+        }
+    }
+
+    fn is_constrained(&self, a: uint, b: uint) -> bool {
+        self.constraints.contains(&sorted(a, b))
+    }
+
+    fn locate(&self, pt: &Pnt2<N>) -> Option<uint> {
+        let eps: N = Float::epsilon();
+        let mut best         = None;
+        let mut best_cost: N = -Float::infinity();
+
+        for (i, t) in self.triangles.iter().enumerate() {
+            if t.valid {
+                let pa = &self.vertices[t.idx.x];
+                let pb = &self.vertices[t.idx.y];
+                let pc = &self.vertices[t.idx.z];
+
+                // Smallest (normalized) signed distance to the triangle edges.
+                let cost = (orient(pa, pb, pt) / na::dist(pa, pb).max(eps))
+                           .min(orient(pb, pc, pt) / na::dist(pb, pc).max(eps))
+                           .min(orient(pc, pa, pt) / na::dist(pc, pa).max(eps));
+
+                if cost >= na::zero() {
+                    return Some(i);
+                }
+
+                if cost > best_cost {
+                    best_cost = cost;
+                    best      = Some(i);
+                }
+            }
+        }
+
+        // Handles points slightly outside of every triangle because of rounding errors.
+        best
+    }
+
+    fn insert_existing_vertex(&mut self, ipt: uint) {
+        let pt = self.vertices[ipt].clone();
+
+        let start = match self.locate(&pt) {
+            Some(t) => t,
+            None    => return
+        };
+
+        {
+            let tstart = &self.triangles[start];
+
+            if tstart.idx.x == ipt || tstart.idx.y == ipt || tstart.idx.z == ipt {
+                return;
+            }
+        }
+
+        let mut cavity   = HashSet::new();
+        let mut stack    = vec!(start);
+        let mut boundary = Vec::new();
+
+        let _ = cavity.insert(start);
+
+        loop {
+            match stack.pop() {
+                None    => break,
+                Some(t) => {
+                    let edges = self.triangles[t].edges();
+
+                    for &(a, b) in edges.iter() {
+                        if self.is_constrained(a, b) {
+                            boundary.push((a, b));
+                            continue;
+                        }
+
+                        match self.edges.get(&(b, a)) {
+                            Some(&n) => {
+                                if cavity.contains(&n) {
+                                    continue;
+                                }
+
+                                if self.triangles[n].circumcircle_contains_point(&pt) {
+                                    let _ = cavity.insert(n);
+                                    stack.push(n);
+                                }
+                                else {
+                                    boundary.push((a, b));
+                                }
+                            },
+                            None => boundary.push((a, b))
+                        }
+                    }
+                }
+            }
+        }
+
+        // An edge might have been registered as part of the boundary before its adjacent triangle
+        // has been added to the cavity.
+        let boundary: Vec<(uint, uint)> = boundary.into_iter().filter(|&(a, b)| {
+            match self.edges.get(&(b, a)) {
+                Some(n) => self.is_constrained(a, b) || !cavity.contains(n),
+                None    => true
+            }
+        }).collect();
+
+        for t in cavity.iter() {
+            self.remove_triangle(*t);
+        }
+
+        for &(a, b) in boundary.iter() {
+            if a != ipt && b != ipt {
+                let _ = self.add_triangle(a, b, ipt);
+            }
+        }
+    }
+
+    fn insert_vertex(&mut self, pt: Pnt2<N>) -> uint {
+        let id = self.vertices.len();
+        self.vertices.push(pt);
+        self.insert_existing_vertex(id);
+
+        id
+    }
+
+    /*
+     * Constraints recovery.
+     */
+    // Returns `false` if some parts of the edge could not be recovered.
+    fn recover_edge(&mut self, a: uint, b: uint) -> bool {
+        let mut to_recover = vec!((a, b));
+        let mut recovered  = true;
+
+        loop {
+            let (a, b) = match to_recover.pop() {
+                Some(e) => e,
+                None    => break
+            };
+
+            if a == b || self.is_constrained(a, b) {
+                continue;
+            }
+
+            // If a vertex lies on the edge, split the constraint.
+            match self.vertex_on_segment(a, b) {
+                Some(v) => {
+                    to_recover.push((a, v));
+                    to_recover.push((v, b));
+                    continue;
+                },
+                None => { }
+            }
+
+            if self.edges.contains_key(&(a, b)) || self.edges.contains_key(&(b, a)) {
+                let _ = self.constraints.insert(sorted(a, b));
+                continue;
+            }
+
+            // Collect the triangles crossed by the edge.
+            let pa = self.vertices[a].clone();
+            let pb = self.vertices[b].clone();
+            let mut crossed        = Vec::new();
+            let mut crossed_constr = None;
+
+            for (i, t) in self.triangles.iter().enumerate() {
+                if t.valid {
+                    for &(c, d) in t.edges().iter() {
+                        if segments_cross(&pa, &pb, &self.vertices[c], &self.vertices[d]) {
+                            crossed.push(i);
+
+                            if self.is_constrained(c, d) {
+                                crossed_constr = Some((c, d));
+                            }
+
+                            break;
+                        }
+                    }
+                }
+            }
+
+            match crossed_constr {
+                Some((c, d)) => {
+                    // Two constraints intersect: split both at their intersection.
+                    let pc    = self.vertices[c].clone();
+                    let pd    = self.vertices[d].clone();
+                    let inter = segments_intersection(&pa, &pb, &pc, &pd);
+
+                    let _ = self.constraints.remove(&sorted(c, d));
+                    let ip = self.insert_vertex(inter);
+
+                    to_recover.push((c, ip));
+                    to_recover.push((ip, d));
+                    to_recover.push((a, ip));
+                    to_recover.push((ip, b));
+                    continue;
+                },
+                None => { }
+            }
+
+            if crossed.is_empty() {
+                // Should not happen; give up on this edge.
+                recovered = false;
+                continue;
+            }
+
+            // Extract the boundary of the cavity.
+            let cavity: HashSet<uint> = crossed.iter().map(|t| *t).collect();
+            let mut next = HashMap::new();
+
+            for t in crossed.iter() {
+                for &(c, d) in self.triangles[*t].edges().iter() {
+                    let is_boundary = match self.edges.get(&(d, c)) {
+                        Some(n) => !cavity.contains(n),
+                        None    => true
+                    };
+
+                    if is_boundary {
+                        let _ = next.insert(c, d);
+                    }
+                }
+            }
+
+            match (follow_chain(&next, a, b), follow_chain(&next, b, a)) {
+                (Some(c1), Some(c2)) => {
+                    for t in crossed.iter() {
+                        self.remove_triangle(*t);
+                    }
+
+                    self.triangulate_pseudo_polygon(c1.slice(1, c1.len() - 1), a, b);
+                    self.triangulate_pseudo_polygon(c2.slice(1, c2.len() - 1), b, a);
+                    let _ = self.constraints.insert(sorted(a, b));
+                },
+                // The cavity is not a simple polygon: leave the triangulation unchanged.
+                _ => recovered = false
+            }
+        }
+
+        recovered
+    }
+
+    fn vertex_on_segment(&self, a: uint, b: uint) -> Option<uint> {
+        let pa  = &self.vertices[a];
+        let pb  = &self.vertices[b];
+        let ab  = *pb - *pa;
+        let len = na::sqnorm(&ab);
+        let eps: N = Float::epsilon();
+        let tol = eps * na::cast(100.0f64);
+
+        for (i, pt) in self.vertices.iter().enumerate() {
+            if i != a && i != b && i > 2 {
+                let ap = *pt - *pa;
+                let t  = na::dot(&ap, &ab);
+
+                if t > na::zero() && t < len {
+                    let dist = orient(pa, pb, pt).abs() / len.sqrt();
+
+                    if dist <= tol * len.sqrt() {
+                        return Some(i);
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    // The polygon formed by `a`, `b`, and `pts` must be counterclockwise with `pts` on the left of
+    // the edge `b -> a`.
+    fn triangulate_pseudo_polygon(&mut self, pts: &[uint], a: uint, b: uint) {
+        if pts.is_empty() {
+            return;
+        }
+
+        let mut ic = 0;
+
+        for i in range(1u, pts.len()) {
+            let (center, radius) = utils::circumcircle(&self.vertices[a],
+                                                       &self.vertices[b],
+                                                       &self.vertices[pts[ic]]);
+
+            if na::sqdist(&self.vertices[pts[i]], &center) < radius * radius {
+                ic = i;
+            }
+        }
+
+        let c = pts[ic];
+
+        self.triangulate_pseudo_polygon(pts.slice_to(ic), a, c);
+        self.triangulate_pseudo_polygon(pts.slice_from(ic + 1), c, b);
+        let _ = self.add_triangle(a, c, b);
+    }
+
+    /*
+     * Ruppert refinement.
+     */
+    fn refine(&mut self, params: &RefinementParams<N>) {
+        let cos_min_angle = params.min_angle.cos();
+        let max_vertices  = self.vertices.len() + params.max_steiner_points;
+        let mut rejected  = HashSet::new();
+        let mut queue: Vec<uint> = range(0, self.triangles.len()).collect();
+
+        while self.vertices.len() < max_vertices {
+            // Split encroached segments first.
+            match self.find_encroached_segment() {
+                Some((a, b)) => {
+                    let first_new = self.triangles.len();
+                    self.split_segment(a, b);
+                    queue.extend(range(first_new, self.triangles.len()));
+                    continue;
+                },
+                None => { }
+            }
+
+            // Find a bad triangle.
+            let mut bad = None;
+
+            while bad.is_none() {
+                match queue.pop() {
+                    None    => break,
+                    Some(t) => {
+                        if self.triangles[t].valid &&
+                           self.triangles[t].inside &&
+                           !rejected.contains(&t) &&
+                           self.is_bad(t, cos_min_angle, params.max_area) {
+                            bad = Some(t)
+                        }
+                    }
+                }
+            }
+
+            let t = match bad {
+                Some(t) => t,
+                None    => {
+                    // Some bad triangles might have been skipped because they were not valid yet
+                    // when they were popped: rescan everything before stopping.
+                    let candidates: Vec<uint> = range(0, self.triangles.len()).filter(|&t| {
+                        self.triangles[t].valid &&
+                        self.triangles[t].inside &&
+                        !rejected.contains(&t) &&
+                        self.is_bad(t, cos_min_angle, params.max_area)
+                    }).collect();
+
+                    if candidates.is_empty() {
+                        break;
+                    }
+
+                    queue = candidates;
+                    continue;
+                }
+            };
+
+            let center = self.triangles[t].center.clone();
+
+            // Segments encroached by the circumcenter.
+            let encroached: Vec<(uint, uint)> = self.constraints.iter().filter(|&&(a, b)| {
+                is_encroached(&self.vertices[a], &self.vertices[b], &center)
+            }).map(|e| *e).collect();
+
+            let first_new = self.triangles.len();
+
+            if !encroached.is_empty() {
+                for &(a, b) in encroached.iter() {
+                    self.split_segment(a, b);
+                }
+
+                // `t` might still be valid, so we retry it later.
+                queue.push(t);
+            }
+            else {
+                let inside = match self.locate(&center) {
+                    Some(loc) => self.triangles[loc].inside,
+                    None      => false
+                };
+
+                if !inside || self.vertices.iter().any(|v| *v == center) {
+                    let _ = rejected.insert(t);
+                }
+                else {
+                    let _ = self.insert_vertex(center);
+                }
+            }
+
+            queue.extend(range(first_new, self.triangles.len()));
+        }
+    }
+
+    fn is_bad(&self, t: uint, cos_min_angle: N, max_area: N) -> bool {
+        let t  = &self.triangles[t];
+        let pa = &self.vertices[t.idx.x];
+        let pb = &self.vertices[t.idx.y];
+        let pc = &self.vertices[t.idx.z];
+
+        let area = orient(pa, pb, pc) * na::cast(0.5f64);
+
+        if area > max_area {
+            return true;
+        }
+
+        let mut a = na::sqdist(pb, pc);
+        let mut b = na::sqdist(pc, pa);
+        let mut c = na::sqdist(pa, pb);
+
+        // `a` becomes the smallest edge; the smallest angle is opposite to it.
+        let (a, b, c) = utils::sort3(&mut a, &mut b, &mut c);
+        let (a, b, c) = (*a, *b, *c);
+
+        let denom = (b * c).sqrt() * na::cast(2.0f64);
+
+        if na::is_zero(&denom) {
+            return false;
+        }
+
+        (b + c - a) / denom > cos_min_angle
+    }
+
+    fn find_encroached_segment(&self) -> Option<(uint, uint)> {
+        for &(a, b) in self.constraints.iter() {
+            for e in [ (a, b), (b, a) ].iter() {
+                match self.edges.get(e) {
+                    Some(t) => {
+                        let t = &self.triangles[*t];
+
+                        if t.inside {
+                            let opp = t.idx.x + t.idx.y + t.idx.z - a - b;
+
+                            if is_encroached(&self.vertices[a], &self.vertices[b], &self.vertices[opp]) {
+                                return Some((a, b));
+                            }
+                        }
+                    },
+                    None => { }
+                }
+            }
+        }
+
+        None
+    }
+
+    fn split_segment(&mut self, a: uint, b: uint) {
+        let mid = na::center(&self.vertices[a], &self.vertices[b]);
+
+        let _ = self.constraints.remove(&sorted(a, b));
+        let m = self.vertices.len();
+        let _ = self.constraints.insert(sorted(a, m));
+        let _ = self.constraints.insert(sorted(m, b));
+        let _ = self.insert_vertex(mid);
+    }
+
+    /*
+     * Output.
+     */
+    fn output(self) -> TriMesh2<N> {
+        let mut idx = Vec::new();
+
+        for t in self.triangles.iter() {
+            if t.valid && t.inside {
+                idx.push(Vec3::new((t.idx.x - 3) as u32, (t.idx.y - 3) as u32, (t.idx.z - 3) as u32));
+            }
+        }
+
+        let mut coords = self.vertices.slice_from(3).to_vec();
+
+        utils::remove_unused_points(&mut coords, idx.as_mut_slice());
+
+        TriMesh::new(coords, None, None, Some(IndexBuffer::Unified(idx)))
+    }
+}
+
+/// Computes the constrained Delaunay triangulation of polygons with holes.
+///
+/// # Arguments:
+/// * `boundaries` - the closed outer boundaries of the polygons.
+/// * `holes` - the closed holes of the polygons.
+/// * `edges` - additional edges that must be part of the triangulation.
+/// * `refinement` - if set, the triangulation is refined by inserting Steiner points until every
+///   triangle satisfies the given minimum angle and maximum area.
+///
+/// Returns an error if some boundary, hole, or additional edges could not be recovered.
+pub fn constrained_triangulate<N: Scalar>(boundaries: &[Polyline2<N>],
+                                          holes:      &[Polyline2<N>],
+                                          edges:      &[(Pnt2<N>, Pnt2<N>)],
+                                          refinement: Option<RefinementParams<N>>)
+                                          -> Result<TriMesh2<N>, ConstrainedTriangulationError<N>> {
+    let mut triangulator = ConstrainedTriangulator::new();
+
+    for boundary in boundaries.iter() {
+        triangulator.push_boundary(boundary.coords.as_slice());
+    }
+
+    for hole in holes.iter() {
+        triangulator.push_hole(hole.coords.as_slice());
+    }
+
+    for &(ref a, ref b) in edges.iter() {
+        triangulator.push_constraint(a.clone(), b.clone());
+    }
+
+    for params in refinement.into_iter() {
+        triangulator.refine_with(params);
+    }
+
+    triangulator.to_trimesh()
+}
+
+/*
+ * Geometric predicates.
+ */
+#[inline]
+fn sorted(a: uint, b: uint) -> (uint, uint) {
+    if a > b { (b, a) } else { (a, b) }
+}
+
+#[inline]
+fn orient<N: Scalar>(a: &Pnt2<N>, b: &Pnt2<N>, c: &Pnt2<N>) -> N {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+// Tests if two segments cross at a single point that is not one of their endpoints.
+#[inline]
+fn segments_cross<N: Scalar>(a: &Pnt2<N>, b: &Pnt2<N>, c: &Pnt2<N>, d: &Pnt2<N>) -> bool {
+    let _0: N = na::zero();
+
+    let o1 = orient(a, b, c);
+    let o2 = orient(a, b, d);
+    let o3 = orient(c, d, a);
+    let o4 = orient(c, d, b);
+
+    ((o1 > _0 && o2 < _0) || (o1 < _0 && o2 > _0)) &&
+    ((o3 > _0 && o4 < _0) || (o3 < _0 && o4 > _0))
+}
+
+#[inline]
+fn segments_intersection<N: Scalar>(a: &Pnt2<N>, b: &Pnt2<N>, c: &Pnt2<N>, d: &Pnt2<N>) -> Pnt2<N> {
+    let o1 = orient(c, d, a);
+    let o2 = orient(c, d, b);
+    let t  = o1 / (o1 - o2);
+
+    *a + (*b - *a) * t
+}
+
+// Tests if `pt` lies inside of the diametral circle of the segment `ab`.
+#[inline]
+fn is_encroached<N: Scalar>(a: &Pnt2<N>, b: &Pnt2<N>, pt: &Pnt2<N>) -> bool {
+    if pt == a || pt == b {
+        return false;
+    }
+
+    na::dot(&(*a - *pt), &(*b - *pt)) < na::zero()
+}
+
+fn follow_chain(next: &HashMap<uint, uint>, from: uint, to: uint) -> Option<Vec<uint>> {
+    let mut chain = vec!(from);
+    let mut curr  = from;
+
+    while curr != to {
+        match next.get(&curr) {
+            Some(n) => {
+                curr = *n;
+                chain.push(curr);
+            },
+            None => return None
+        }
+
+        if chain.len() > next.len() + 1 {
+            return None;
+        }
+    }
+
+    Some(chain)
+}
+
+#[cfg(test)]
+mod test {
+    use na::Pnt2;
+    use na;
+    use procedural::Polyline;
+    use utils;
+
+    fn area(mesh: &::procedural::TriMesh2<f64>) -> f64 {
+        let idx    = mesh.indices.clone().unwrap_unified();
+        let mut res = 0.0;
+
+        for t in idx.iter() {
+            res = res + utils::triangle_area(&mesh.coords[t.x as uint],
+                                             &mesh.coords[t.y as uint],
+                                             &mesh.coords[t.z as uint]);
+        }
+
+        res
+    }
+
+    // Checks that the segment `ab` is covered by edges of the mesh.
+    fn assert_has_constraint(mesh: &::procedural::TriMesh2<f64>, a: &Pnt2<f64>, b: &Pnt2<f64>) {
+        let idx    = mesh.indices.clone().unwrap_unified();
+        let ab     = *b - *a;
+        let sqlen  = na::sqnorm(&ab);
+        let mut on_segment = Vec::new();
+
+        for (i, pt) in mesh.coords.iter().enumerate() {
+            let t = na::dot(&(*pt - *a), &ab) / sqlen;
+
+            if t >= -1.0e-7 && t <= 1.0 + 1.0e-7 && na::approx_eq(&na::norm(&(*a + ab * t - *pt)), &0.0) {
+                on_segment.push((t, i));
+            }
+        }
+
+        on_segment.sort_by(|t1, t2| t1.partial_cmp(t2).unwrap());
+
+        assert!(on_segment.len() >= 2);
+        assert!(na::approx_eq(&on_segment[0].val0(), &0.0));
+        assert!(na::approx_eq(&on_segment[on_segment.len() - 1].val0(), &1.0));
+
+        for w in on_segment.windows(2) {
+            let i1 = w[0].val1() as u32;
+            let i2 = w[1].val1() as u32;
+
+            assert!(idx.iter().any(|t| {
+                (t.x == i1 || t.y == i1 || t.z == i1) && (t.x == i2 || t.y == i2 || t.z == i2)
+            }));
+        }
+    }
+
+    fn assert_has_loop_constraints(mesh: &::procedural::TriMesh2<f64>, pts: &[Pnt2<f64>]) {
+        for i in range(0, pts.len()) {
+            assert_has_constraint(mesh, &pts[i], &pts[(i + 1) % pts.len()]);
+        }
+    }
+
+    fn smallest_angle(a: &Pnt2<f64>, b: &Pnt2<f64>, c: &Pnt2<f64>) -> f64 {
+        let angle = |p: &Pnt2<f64>, q: &Pnt2<f64>, r: &Pnt2<f64>| {
+            let u = na::normalize(&(*q - *p));
+            let v = na::normalize(&(*r - *p));
+
+            na::dot(&u, &v).max(-1.0).min(1.0).acos()
+        };
+
+        angle(a, b, c).min(angle(b, c, a)).min(angle(c, a, b))
+    }
+
+    #[test]
+    fn test_square_with_hole() {
+        let outer = Polyline::new(vec!(Pnt2::new(0.0f64, 0.0), Pnt2::new(4.0, 0.0),
+                                       Pnt2::new(4.0, 4.0), Pnt2::new(0.0, 4.0)), None);
+        let hole  = Polyline::new(vec!(Pnt2::new(1.0f64, 1.0), Pnt2::new(3.0, 1.0),
+                                       Pnt2::new(3.0, 3.0), Pnt2::new(1.0, 3.0)), None);
+
+        let mesh = super::constrained_triangulate(&[ outer.clone() ], &[ hole.clone() ], &[], None).unwrap();
+
+        assert!(na::approx_eq(&area(&mesh), &12.0));
+        assert_has_loop_constraints(&mesh, outer.coords.as_slice());
+        assert_has_loop_constraints(&mesh, hole.coords.as_slice());
+    }
+
+    #[test]
+    fn test_constraints_and_min_angle_refinement() {
+        let outer = Polyline::new(vec!(Pnt2::new(0.0f64, 0.0), Pnt2::new(4.0, 0.0),
+                                       Pnt2::new(4.0, 4.0), Pnt2::new(0.0, 4.0)), None);
+        let hole  = Polyline::new(vec!(Pnt2::new(1.0f64, 1.0), Pnt2::new(3.0, 1.0),
+                                       Pnt2::new(3.0, 3.0), Pnt2::new(1.0, 3.0)), None);
+        let edges = [
+            (Pnt2::new(0.5f64, 0.5), Pnt2::new(3.5, 0.5)),
+            (Pnt2::new(3.5f64, 1.0), Pnt2::new(3.5, 3.5))
+        ];
+
+        let min_angle = 0.35f64;
+        let params    = super::RefinementParams::new(min_angle, 0.25);
+        let mesh      = super::constrained_triangulate(&[ outer.clone() ], &[ hole.clone() ],
+                                                       &edges, Some(params)).unwrap();
+
+        assert!(na::approx_eq(&area(&mesh), &12.0));
+        assert_has_loop_constraints(&mesh, outer.coords.as_slice());
+        assert_has_loop_constraints(&mesh, hole.coords.as_slice());
+
+        for &(ref a, ref b) in edges.iter() {
+            assert_has_constraint(&mesh, a, b);
+        }
+
+        for t in mesh.indices.clone().unwrap_unified().iter() {
+            let a = &mesh.coords[t.x as uint];
+            let b = &mesh.coords[t.y as uint];
+            let c = &mesh.coords[t.z as uint];
+
+            assert!(smallest_angle(a, b, c) >= min_angle - 1.0e-7);
+            assert!(utils::triangle_area(a, b, c) <= 0.25 + 1.0e-7);
+        }
+    }
+
+    #[test]
+    fn test_concave_polygon_refinement() {
+        let outer = Polyline::new(vec!(Pnt2::new(0.0f64, 0.0), Pnt2::new(4.0, 0.0),
+                                       Pnt2::new(4.0, 4.0), Pnt2::new(2.0, 1.0),
+                                       Pnt2::new(0.0, 4.0)), None);
+
+        let params = super::RefinementParams::new(0.35f64, 0.5);
+        let mesh   = super::constrained_triangulate(&[ outer.clone() ], &[], &[], Some(params)).unwrap();
+
+        assert!(na::approx_eq(&area(&mesh), &10.0));
+        assert_has_loop_constraints(&mesh, outer.coords.as_slice());
+        assert!(mesh.num_triangles() > 3);
+    }
+}
@@ -2,11 +2,17 @@
 
 pub use utils::center::center;
 pub use utils::triangulate::triangulate;
+pub use utils::constrained_triangulate::{constrained_triangulate, ConstrainedTriangulator,
+                                          ConstrainedTriangulationError, RefinementParams};
 pub use utils::project_homogeneous::{project_homogeneous, project_homogeneous_to};
 pub use utils::triangle::{circumcircle, is_affinely_dependent_triangle3,
                           is_affinely_dependent_triangle, is_point_in_triangle, triangle_area,
-                          triangle_perimeter};
-pub use utils::tetrahedron::{tetrahedron_volume, tetrahedron_signed_volume, tetrahedron_center};
+                          triangle_perimeter, triangles_intersect3};
+pub use utils::tetrahedron::{tetrahedron_volume, tetrahedron_signed_volume, tetrahedron_center,
+                             tetrahedron_circumsphere};
+pub use utils::tetrahedralize::{tetrahedralize, constrained_tetrahedralize, Tetrahedralizer,
+                                ConstrainedTetrahedralizationError};
+pub use utils::voronoi::voronoi_cells;
 pub use utils::cleanup::remove_unused_points;
 pub use utils::derivatives::{dcos, dsin, binom};
 pub use utils::optimization::{maximize_with_newton, newton, minimize_with_bfgs, bfgs,
@@ -19,14 +25,18 @@ pub use utils::as_bytes::AsBytes;
 pub use utils::cov::{cov, cov_and_center, center_reduce};
 pub use utils::sort::sort3;
 pub use utils::cross3::cross3;
+pub use utils::parallel::parallel_map;
 
 
 pub mod symbolic;
 pub mod data;
 mod center;
 mod triangulate;
+mod constrained_triangulate;
 mod project_homogeneous;
 mod tetrahedron;
+mod tetrahedralize;
+mod voronoi;
 mod triangle;
 mod cleanup;
 mod derivatives;
@@ -39,3 +49,4 @@ pub mod as_bytes;
 mod cov;
 mod sort;
 mod cross3;
+mod parallel;
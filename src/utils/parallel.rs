@@ -0,0 +1,87 @@
+//! Simple data-parallel helpers based on `std` threads.
+
+use std::mem;
+use std::sync::{Arc, Future};
+use na;
+
+
+/// Applies a function to each input on several threads, all sharing the same immutable object.
+///
+/// The inputs are split into at most `nthreads` contiguous chunks, each processed on its own
+/// thread. The results are returned in the same order as the inputs.
+pub fn parallel_map<S, I, R>(shared: &Arc<S>, inputs: Vec<I>, nthreads: uint, f: fn(&S, I) -> R)
+                             -> Vec<R>
+    where S: Send + Sync,
+          I: Send,
+          R: Send {
+    let len       = inputs.len();
+    let nthreads  = na::max(nthreads, 1);
+    let chunk_len = (len + nthreads - 1) / nthreads;
+
+    if len == 0 {
+        return Vec::new();
+    }
+
+    let mut futures = Vec::with_capacity(nthreads);
+    let mut chunk   = Vec::with_capacity(chunk_len);
+
+    for input in inputs.into_iter() {
+        chunk.push(input);
+
+        if chunk.len() == chunk_len {
+            let full = mem::replace(&mut chunk, Vec::with_capacity(chunk_len));
+            futures.push(spawn_chunk(shared.clone(), full, f));
+        }
+    }
+
+    if !chunk.is_empty() {
+        futures.push(spawn_chunk(shared.clone(), chunk, f));
+    }
+
+    let mut res = Vec::with_capacity(len);
+
+    for future in futures.into_iter() {
+        res.extend(future.unwrap().into_iter());
+    }
+
+    res
+}
+
+fn spawn_chunk<S, I, R>(shared: Arc<S>, chunk: Vec<I>, f: fn(&S, I) -> R) -> Future<Vec<R>>
+    where S: Send + Sync,
+          I: Send,
+          R: Send {
+    Future::spawn(proc() {
+        chunk.into_iter().map(|input| f(&*shared, input)).collect()
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    fn add(a: &uint, b: uint) -> uint {
+        *a + b
+    }
+
+    #[test]
+    fn test_parallel_map_keeps_order() {
+        let inputs: Vec<uint> = range(0u, 1000).collect();
+        let res = super::parallel_map(&Arc::new(10u), inputs, 7, add);
+
+        assert!(res == range(10u, 1010).collect::<Vec<uint>>());
+    }
+
+    #[test]
+    fn test_parallel_map_thread_counts() {
+        let shared = Arc::new(3u);
+
+        // No thread, more threads than inputs, and no inputs.
+        for nthreads in [ 0u, 1, 2, 5, 100 ].iter() {
+            let res = super::parallel_map(&shared, vec!(1u, 2, 3, 4, 5), *nthreads, add);
+
+            assert!(res == vec!(4u, 5, 6, 7, 8));
+            assert!(super::parallel_map(&shared, Vec::new(), *nthreads, add).is_empty());
+        }
+    }
+}
@@ -0,0 +1,456 @@
+//! Point cloud and closed mesh tetrahedralization.
+
+use std::collections::{HashMap, HashSet};
+use std::collections::hash_map::{Vacant, Occupied};
+use na::{Pnt3, Vec3, Vec4};
+use na;
+use procedural::{TetMesh, TetMesh3, TriMesh3, IndexBuffer};
+use ray::Ray;
+use ray;
+use utils;
+use bounding_volume;
+use math::Scalar;
+
+struct Tetrahedron<N> {
+    idx:                    Vec4<uint>, // positively oriented.
+    circumsphere_center:    Pnt3<N>,
+    circumsphere_sq_radius: N
+}
+
+impl<N: Scalar> Tetrahedron<N> {
+    pub fn new(idx: Vec4<uint>, pts: &[Pnt3<N>]) -> Tetrahedron<N> {
+        let mut idx = idx;
+
+        if utils::tetrahedron_signed_volume(&pts[idx.x], &pts[idx.y], &pts[idx.z], &pts[idx.w]) < na::zero() {
+            let y = idx.y;
+            idx.y = idx.z;
+            idx.z = y;
+        }
+
+        match utils::tetrahedron_circumsphere(&pts[idx.x], &pts[idx.y], &pts[idx.z], &pts[idx.w]) {
+            Some((center, radius)) => {
+                Tetrahedron {
+                    idx:                    idx,
+                    circumsphere_center:    center,
+                    circumsphere_sq_radius: radius * radius
+                }
+            },
+            None => {
+                // The tetrahedron is flat: it will never be removed.
+                Tetrahedron {
+                    idx:                    idx,
+                    circumsphere_center:    pts[idx.x].clone(),
+                    circumsphere_sq_radius: na::zero()
+                }
+            }
+        }
+    }
+
+    pub fn circumsphere_contains_point(&self, pt: &Pnt3<N>) -> bool {
+        na::sqdist(pt, &self.circumsphere_center) < self.circumsphere_sq_radius
+    }
+
+    // Faces oriented toward the exterior of the tetrahedron.
+    pub fn faces(&self) -> [Vec3<uint>, ..4] {
+        let t = &self.idx;
+
+        [ Vec3::new(t.x, t.z, t.y), Vec3::new(t.x, t.y, t.w),
+          Vec3::new(t.y, t.z, t.w), Vec3::new(t.x, t.w, t.z) ]
+    }
+
+    pub fn edges(&self) -> [(uint, uint), ..6] {
+        let t = &self.idx;
+
+        [ sorted2(t.x, t.y), sorted2(t.x, t.z), sorted2(t.x, t.w),
+          sorted2(t.y, t.z), sorted2(t.y, t.w), sorted2(t.z, t.w) ]
+    }
+}
+
+/// Incremental Delaunay tetrahedralization utility.
+pub struct Tetrahedralizer<N> {
+    vertices:   Vec<Pnt3<N>>,
+    tetrahedra: Vec<Tetrahedron<N>>,
+    faces:      HashMap<(uint, uint, uint), (uint, Vec3<uint>)>
+}
+
+impl<N: Scalar> Tetrahedralizer<N> {
+    /// Creates a new Tetrahedralizer.
+    ///
+    /// The four given points form a super-tetrahedron that must contain every point that will be
+    /// added afterward.
+    pub fn new(a: Pnt3<N>, b: Pnt3<N>, c: Pnt3<N>, d: Pnt3<N>) -> Tetrahedralizer<N> {
+        let vertices = vec!(a, b, c, d);
+
+        Tetrahedralizer {
+            tetrahedra: vec!(Tetrahedron::new(Vec4::new(0, 1, 2, 3), vertices.as_slice())),
+            vertices:   vertices,
+            faces:      HashMap::new()
+        }
+    }
+
+    /// Creates a new Tetrahedralizer with a super-tetrahedron enclosing the given ball.
+    pub fn new_enclosing_ball(center: &Pnt3<N>, radius: N) -> Tetrahedralizer<N> {
+        // The inscribed sphere of this regular tetrahedron has a radius equal to `s / sqrt(3)`.
+        let s: N = (radius + na::one()) * na::cast(10.0f64);
+
+        Tetrahedralizer::new(*center + Vec3::new( s,  s,  s),
+                             *center + Vec3::new( s, -s, -s),
+                             *center + Vec3::new(-s,  s, -s),
+                             *center + Vec3::new(-s, -s,  s))
+    }
+
+    /// Adds a point to the tetrahedralized set.
+    ///
+    /// Returns the index of the point on the output mesh.
+    pub fn add_point(&mut self, pt: Pnt3<N>) -> uint {
+        self.remove_containing_tetrahedra_and_register_faces(&pt);
+
+        let ipt = self.vertices.len();
+        self.vertices.push(pt);
+
+        for &(num, ref f) in self.faces.values() {
+            if num == 1 {
+                let t = Tetrahedron::new(Vec4::new(f.x, f.y, f.z, ipt), self.vertices.as_slice());
+
+                self.tetrahedra.push(t)
+            }
+        }
+
+        ipt - 4
+    }
+
+    /// Returns the result of the tetrahedralization.
+    ///
+    /// The output vertices are the added points, in the same order. Flat tetrahedra are discarded.
+    pub fn to_tetmesh(self) -> TetMesh3<N> {
+        let mut idx = Vec::with_capacity(self.tetrahedra.len());
+
+        for t in self.tetrahedra.iter() {
+            if t.idx.x > 3 && t.idx.y > 3 && t.idx.z > 3 && t.idx.w > 3 &&
+               !na::is_zero(&t.circumsphere_sq_radius) {
+                idx.push(Vec4::new((t.idx.x - 4) as u32, (t.idx.y - 4) as u32,
+                                   (t.idx.z - 4) as u32, (t.idx.w - 4) as u32));
+            }
+        }
+
+        TetMesh::new(self.vertices.slice_from(4).to_vec(), idx)
+    }
+
+    /// The edges between the added points on the current tetrahedralization.
+    ///
+    /// Edges of flat tetrahedra and of tetrahedra touching the super-tetrahedron are reported
+    /// too. Only the edges involving a super-tetrahedron vertex are ignored. Each edge `(i, j)`
+    /// is given once, with `i < j` being point indices as returned by `add_point`.
+    pub fn delaunay_edges(&self) -> Vec<(uint, uint)> {
+        self.edges().into_iter().filter(|&(a, _)| a > 3).map(|(a, b)| (a - 4, b - 4)).collect()
+    }
+
+    fn remove_containing_tetrahedra_and_register_faces(&mut self, pt: &Pnt3<N>) {
+        self.faces.clear();
+
+        let mut i = 0;
+
+        while i != self.tetrahedra.len() { // the len might change inside of the loop
+            if self.tetrahedra[i].circumsphere_contains_point(pt) {
+                {
+                    let t = &self.tetrahedra[i];
+
+                    for f in t.faces().iter() {
+                        match self.faces.entry(sorted3(f)) {
+                            Occupied(mut entry) => *entry.get_mut().mut0() += 1,
+                            Vacant(entry)       => { let _ = entry.set((1, f.clone())); }
+                        };
+                    }
+                }
+
+                let _ = self.tetrahedra.swap_remove(i);
+            }
+            else {
+                i = i + 1;
+            }
+        }
+    }
+
+    fn edges(&self) -> HashSet<(uint, uint)> {
+        let mut res = HashSet::new();
+
+        for t in self.tetrahedra.iter() {
+            for e in t.edges().iter() {
+                let _ = res.insert(*e);
+            }
+        }
+
+        res
+    }
+
+    fn face_keys(&self) -> HashSet<(uint, uint, uint)> {
+        let mut res = HashSet::new();
+
+        for t in self.tetrahedra.iter() {
+            for f in t.faces().iter() {
+                let _ = res.insert(sorted3(f));
+            }
+        }
+
+        res
+    }
+}
+
+/// Computes the Delaunay tetrahedralization of a set of 3d points.
+///
+/// The vertices of the output mesh are exactly the input points, in the same order. The points
+/// are assumed to be distinct.
+pub fn tetrahedralize<N: Scalar>(pts: &[Pnt3<N>]) -> TetMesh3<N> {
+    if pts.is_empty() {
+        return TetMesh::new(Vec::new(), Vec::new());
+    }
+
+    let (center, radius) = bounding_volume::point_cloud_bounding_sphere(pts);
+    let mut tetrahedralizer = Tetrahedralizer::new_enclosing_ball(&center, radius);
+
+    for pt in pts.iter() {
+        let _ = tetrahedralizer.add_point(pt.clone());
+    }
+
+    tetrahedralizer.to_tetmesh()
+}
+
+/// Errors returned by `constrained_tetrahedralize`.
+#[deriving(Clone, Show)]
+pub enum ConstrainedTetrahedralizationError<N> {
+    /// The edge between the two given vertices is not shared by exactly two triangles.
+    NonClosedMesh(uint, uint),
+    /// The boundary could not be recovered with the allowed number of Steiner points.
+    ///
+    /// The tetrahedralization obtained so far is given. It does not conform to the boundary.
+    UnrecoveredBoundary(TetMesh3<N>)
+}
+
+/// Computes a tetrahedralization of the volume enclosed by a closed triangle mesh.
+///
+/// The result conforms to the mesh boundary: missing boundary edges and triangles are recovered
+/// by inserting at most `max_steiner_points` additional points on the boundary. Then, only the
+/// tetrahedra lying inside of the mesh are kept. The first vertices of the output are the mesh
+/// vertices, followed by the Steiner points.
+///
+/// Returns an error if the mesh is not closed, or if the boundary could not be fully recovered.
+pub fn constrained_tetrahedralize<N: Scalar>(mesh: &TriMesh3<N>, max_steiner_points: uint)
+                                             -> Result<TetMesh3<N>, ConstrainedTetrahedralizationError<N>> {
+    // Only the vertex positions matter: unifying the index buffer would duplicate the vertices
+    // having several normals or uvs.
+    let original_tris: Vec<Vec3<u32>> = match mesh.indices {
+        IndexBuffer::Unified(ref idx) => idx.clone(),
+        IndexBuffer::Split(ref idx)   => idx.iter().map(|t| Vec3::new(t.x.x, t.y.x, t.z.x)).collect()
+    };
+    let original_pts = mesh.coords.clone();
+
+    if original_pts.is_empty() || original_tris.is_empty() {
+        return Ok(TetMesh::new(original_pts, Vec::new()));
+    }
+
+    /*
+     * Check that the mesh is closed.
+     */
+    let mut edge_count = HashMap::new();
+
+    for t in original_tris.iter() {
+        for e in [ sorted2(t.x as uint, t.y as uint),
+                   sorted2(t.y as uint, t.z as uint),
+                   sorted2(t.z as uint, t.x as uint) ].iter() {
+            match edge_count.entry(*e) {
+                Occupied(mut entry) => *entry.get_mut() += 1u,
+                Vacant(entry)       => { let _ = entry.set(1u); }
+            }
+        }
+    }
+
+    for (&(a, b), &n) in edge_count.iter() {
+        if n != 2 {
+            return Err(ConstrainedTetrahedralizationError::NonClosedMesh(a, b));
+        }
+    }
+
+    /*
+     * Tetrahedralize the boundary points.
+     */
+    let (center, radius) = bounding_volume::point_cloud_bounding_sphere(original_pts.as_slice());
+    let mut tetrahedralizer = Tetrahedralizer::new_enclosing_ball(&center, radius);
+
+    for pt in original_pts.iter() {
+        let _ = tetrahedralizer.add_point(pt.clone());
+    }
+
+    /*
+     * Recover the boundary with Steiner points.
+     */
+    let mut surface: Vec<Vec3<uint>> = original_tris.iter().map(|t| {
+        Vec3::new(t.x as uint + 4, t.y as uint + 4, t.z as uint + 4)
+    }).collect();
+    let mut num_steiner_points = 0u;
+    let mut recovered          = false;
+
+    loop {
+        let edges = tetrahedralizer.edges();
+        let mut missing_edge = None;
+
+        for t in surface.iter() {
+            for e in [ sorted2(t.x, t.y), sorted2(t.y, t.z), sorted2(t.z, t.x) ].iter() {
+                if !edges.contains(e) {
+                    missing_edge = Some(*e);
+                    break;
+                }
+            }
+
+            if missing_edge.is_some() {
+                break;
+            }
+        }
+
+        let missing_face = if missing_edge.is_none() {
+            let faces = tetrahedralizer.face_keys();
+
+            surface.iter().position(|t| !faces.contains(&sorted3(t)))
+        }
+        else {
+            None
+        };
+
+        if missing_edge.is_none() && missing_face.is_none() {
+            recovered = true;
+            break;
+        }
+
+        if num_steiner_points == max_steiner_points {
+            break;
+        }
+
+        num_steiner_points = num_steiner_points + 1;
+
+        match missing_edge {
+            Some((a, b)) => {
+                // Split the edge and every boundary triangle sharing it.
+                let mid = na::center(&tetrahedralizer.vertices[a], &tetrahedralizer.vertices[b]);
+                let m   = tetrahedralizer.add_point(mid) + 4;
+                let mut new_surface = Vec::with_capacity(surface.len() + 2);
+
+                for t in surface.iter() {
+                    let is = [ t.x, t.y, t.z ];
+
+                    match range(0u, 3).find(|&i| sorted2(is[i], is[(i + 1) % 3]) == (a, b)) {
+                        Some(i) => {
+                            new_surface.push(Vec3::new(is[i], m, is[(i + 2) % 3]));
+                            new_surface.push(Vec3::new(m, is[(i + 1) % 3], is[(i + 2) % 3]));
+                        },
+                        None => new_surface.push(t.clone())
+                    }
+                }
+
+                surface = new_surface;
+            },
+            None => {
+                // Split the face at its center.
+                let t      = surface.swap_remove(missing_face.unwrap()).unwrap();
+                let center = utils::center(&[ tetrahedralizer.vertices[t.x].clone(),
+                                              tetrahedralizer.vertices[t.y].clone(),
+                                              tetrahedralizer.vertices[t.z].clone() ]);
+                let c      = tetrahedralizer.add_point(center) + 4;
+
+                surface.push(Vec3::new(t.x, t.y, c));
+                surface.push(Vec3::new(t.y, t.z, c));
+                surface.push(Vec3::new(t.z, t.x, c));
+            }
+        }
+    }
+
+    /*
+     * Keep the interior tetrahedra only.
+     */
+    let tetmesh = tetrahedralizer.to_tetmesh();
+    let dir     = na::normalize(&Vec3::new(na::cast::<f64, N>(0.5773), na::cast(0.5774), na::cast(0.5775)));
+    let mut idx = Vec::new();
+
+    for t in tetmesh.indices.iter() {
+        let center = utils::tetrahedron_center(&tetmesh.coords[t.x as uint], &tetmesh.coords[t.y as uint],
+                                               &tetmesh.coords[t.z as uint], &tetmesh.coords[t.w as uint]);
+        let ray     = Ray::new(center, dir);
+        let mut hits = 0u;
+
+        for tri in original_tris.iter() {
+            let a = &original_pts[tri.x as uint];
+            let b = &original_pts[tri.y as uint];
+            let c = &original_pts[tri.z as uint];
+
+            if ray::triangle_ray_intersection(a, b, c, &ray).is_some() {
+                hits = hits + 1;
+            }
+        }
+
+        if hits % 2 == 1 {
+            idx.push(t.clone());
+        }
+    }
+
+    let res = TetMesh::new(tetmesh.coords, idx);
+
+    if recovered {
+        Ok(res)
+    }
+    else {
+        Err(ConstrainedTetrahedralizationError::UnrecoveredBoundary(res))
+    }
+}
+
+#[inline]
+fn sorted2(a: uint, b: uint) -> (uint, uint) {
+    if a > b { (b, a) } else { (a, b) }
+}
+
+#[inline]
+fn sorted3(f: &Vec3<uint>) -> (uint, uint, uint) {
+    let mut a = f.x;
+    let mut b = f.y;
+    let mut c = f.z;
+
+    let (a, b, c) = utils::sort3(&mut a, &mut b, &mut c);
+
+    (*a, *b, *c)
+}
+
+#[cfg(test)]
+mod test {
+    use na::{Pnt3, Vec3};
+    use na;
+    use procedural::{TriMesh, IndexBuffer};
+    use utils::tetrahedralize::ConstrainedTetrahedralizationError;
+
+    #[test]
+    fn test_tetrahedralize_tetrahedron() {
+        let pts = [ Pnt3::new(0.0f64, 0.0, 0.0), Pnt3::new(1.0, 0.0, 0.0),
+                    Pnt3::new(0.0, 1.0, 0.0),    Pnt3::new(0.0, 0.0, 1.0),
+                    Pnt3::new(0.1, 0.2, 0.15) ];
+
+        let mesh = super::tetrahedralize(pts.as_slice());
+
+        assert!(mesh.num_tetrahedra() == 4);
+        assert!(na::approx_eq(&mesh.volume(), &(1.0 / 6.0)));
+    }
+
+    #[test]
+    fn test_constrained_tetrahedralize() {
+        let pts  = vec!(Pnt3::new(0.0f64, 0.0, 0.0), Pnt3::new(1.0, 0.0, 0.0),
+                        Pnt3::new(0.0, 1.0, 0.0),    Pnt3::new(0.0, 0.0, 1.0));
+        let tris = vec!(Vec3::new(0u32, 2, 1), Vec3::new(0, 1, 3), Vec3::new(1, 2, 3), Vec3::new(0, 3, 2));
+
+        let closed = TriMesh::new(pts.clone(), None, None, Some(IndexBuffer::Unified(tris.clone())));
+        let mesh   = super::constrained_tetrahedralize(&closed, 10).unwrap();
+
+        assert!(na::approx_eq(&mesh.volume(), &(1.0 / 6.0)));
+
+        let open = TriMesh::new(pts, None, None, Some(IndexBuffer::Unified(tris.slice_to(3).to_vec())));
+
+        match super::constrained_tetrahedralize(&open, 10) {
+            Err(ConstrainedTetrahedralizationError::NonClosedMesh(_, _)) => { },
+            _ => panic!("An open mesh must not be tetrahedralized.")
+        }
+    }
+}
@@ -1,7 +1,7 @@
-use na::{Mat3, Axpy, ScalarMul};
+use na::{Mat3, Vec3, Axpy, ScalarMul};
 use na;
 use utils;
-use math::{Scalar, Point};
+use math::{Scalar, Point, Vect};
 
 /// Computes the volume of a tetrahedron.
 #[inline]
@@ -44,3 +44,35 @@ pub fn tetrahedron_center<N, P>(p1: &P, p2: &P, p3: &P, p4: &P) -> P
           P: Axpy<N> + ScalarMul<N> + Clone {
     utils::center(&[ p1.clone(), p2.clone(), p3.clone(), p4.clone() ])
 }
+
+/// Computes the circumscribed sphere of a tetrahedron.
+///
+/// Returns `None` if the tetrahedron is degenerate.
+pub fn tetrahedron_circumsphere<N, P, V>(p1: &P, p2: &P, p3: &P, p4: &P) -> Option<(P, N)>
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> {
+    assert!(na::dim::<P>() == 3);
+
+    let p1p2 = *p2 - *p1;
+    let p1p3 = *p3 - *p1;
+    let p1p4 = *p4 - *p1;
+
+    let mat = Mat3::new(p1p2[0], p1p2[1], p1p2[2],
+                        p1p3[0], p1p3[1], p1p3[2],
+                        p1p4[0], p1p4[1], p1p4[2]);
+
+    let _0_5: N = na::cast(0.5f64);
+    let rhs     = Vec3::new(na::sqnorm(&p1p2) * _0_5, na::sqnorm(&p1p3) * _0_5, na::sqnorm(&p1p4) * _0_5);
+
+    na::inv(&mat).map(|inv| {
+        let shift      = inv * rhs;
+        let mut center = p1.clone();
+
+        center[0] = center[0] + shift.x;
+        center[1] = center[1] + shift.y;
+        center[2] = center[2] + shift.z;
+
+        (center, na::norm(&shift))
+    })
+}
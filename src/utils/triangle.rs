@@ -1,5 +1,5 @@
 use std::num::Float;
-use na::{Cross, Norm, Dim, Zero};
+use na::{Cross, Norm, Dim, Zero, Pnt3, Vec3};
 use na;
 use bounding_volume;
 use utils;
@@ -119,6 +119,59 @@ pub fn is_point_in_triangle<N, P, V>(p: &P, p1: &P, p2: &P, p3: &P) -> bool
     d13 >= na::zero() && d13 <= na::sqnorm(&p3p1)
 }
 
+/// Tests if two 3D triangles intersect.
+///
+/// This is an exact test based on the separating axis theorem. Triangles touching at a single
+/// point or along an edge are considered intersecting.
+pub fn triangles_intersect3<N: Scalar>(pa1: &Pnt3<N>, pb1: &Pnt3<N>, pc1: &Pnt3<N>,
+                                       pa2: &Pnt3<N>, pb2: &Pnt3<N>, pc2: &Pnt3<N>)
+                                       -> bool {
+    let t1 = [ *pa1, *pb1, *pc1 ];
+    let t2 = [ *pa2, *pb2, *pc2 ];
+
+    let e1 = [ *pb1 - *pa1, *pc1 - *pb1, *pa1 - *pc1 ];
+    let e2 = [ *pb2 - *pa2, *pc2 - *pb2, *pa2 - *pc2 ];
+
+    let n1 = na::cross(&e1[0], &e1[1]);
+    let n2 = na::cross(&e2[0], &e2[1]);
+
+    // The cross products of the edges are enough for the general case. The axes orthogonal to
+    // the edges on each triangle plane handle the coplanar case.
+    let mut axes: Vec<Vec3<N>> = vec!(n1, n2);
+
+    for i in range(0u, 3) {
+        for j in range(0u, 3) {
+            axes.push(na::cross(&e1[i], &e2[j]));
+        }
+
+        axes.push(na::cross(&n1, &e1[i]));
+        axes.push(na::cross(&n2, &e2[i]));
+    }
+
+    for axis in axes.iter() {
+        if na::is_zero(&na::sqnorm(axis)) {
+            continue;
+        }
+
+        let (min1, max1) = project_triangle(&t1, axis);
+        let (min2, max2) = project_triangle(&t2, axis);
+
+        if max1 < min2 || max2 < min1 {
+            return false;
+        }
+    }
+
+    true
+}
+
+fn project_triangle<N: Scalar>(t: &[Pnt3<N>, ..3], axis: &Vec3<N>) -> (N, N) {
+    let d0 = na::dot(t[0].as_vec(), axis);
+    let d1 = na::dot(t[1].as_vec(), axis);
+    let d2 = na::dot(t[2].as_vec(), axis);
+
+    (na::min(na::min(d0, d1), d2), na::max(na::max(d0, d1), d2))
+}
+
 #[cfg(test)]
 mod test {
     use na;
@@ -132,4 +185,24 @@ mod test {
 
         assert!(na::approx_eq(&super::triangle_area(&pa, &pb, &pc), &10.0));
     }
+
+    #[test]
+    fn test_triangles_intersect3() {
+        let a = Pnt3::new(0.0f64, 0.0, 0.0);
+        let b = Pnt3::new(2.0f64, 0.0, 0.0);
+        let c = Pnt3::new(0.0f64, 2.0, 0.0);
+
+        // Crossing the first triangle.
+        let d = Pnt3::new(0.5f64, 0.5, -1.0);
+        let e = Pnt3::new(0.5f64, 0.5, 1.0);
+        let f = Pnt3::new(3.0f64, 3.0, 1.0);
+
+        // Above the first triangle.
+        let g = Pnt3::new(0.5f64, 0.5, 0.5);
+        let h = Pnt3::new(0.5f64, 0.5, 1.0);
+        let i = Pnt3::new(3.0f64, 3.0, 1.0);
+
+        assert!(super::triangles_intersect3(&a, &b, &c, &d, &e, &f));
+        assert!(!super::triangles_intersect3(&a, &b, &c, &g, &h, &i));
+    }
 }
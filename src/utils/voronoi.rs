@@ -0,0 +1,213 @@
+//! Voronoi diagram of a 3d point cloud.
+
+use std::num::Float;
+use na::{Pnt3, Vec3};
+use na;
+use bounding_volume::AABB;
+use bounding_volume;
+use shape::{Convex, Convex3};
+use utils::Tetrahedralizer;
+use utils;
+use math::Scalar;
+
+/// Computes the Voronoi cells of a set of 3d points, clipped by an axis-aligned box.
+///
+/// The cells are computed from the dual Delaunay tetrahedralization of the points. The i-th
+/// returned cell is the one of the i-th point. The points are assumed to be distinct.
+pub fn voronoi_cells<N: Scalar>(pts: &[Pnt3<N>], bounds: &AABB<Pnt3<N>>) -> Vec<Convex3<N>> {
+    let mut neighbors: Vec<Vec<uint>> = Vec::from_fn(pts.len(), |_| Vec::new());
+
+    if !pts.is_empty() {
+        // The adjacency is read from the whole tetrahedralization: dropping the flat tetrahedra
+        // or those touching the super-tetrahedron would miss some neighbors.
+        let (center, radius) = bounding_volume::point_cloud_bounding_sphere(pts);
+        let mut tetrahedralizer = Tetrahedralizer::new_enclosing_ball(&center, radius);
+
+        for pt in pts.iter() {
+            let _ = tetrahedralizer.add_point(pt.clone());
+        }
+
+        for &(a, b) in tetrahedralizer.delaunay_edges().iter() {
+            neighbors[a].push(b);
+            neighbors[b].push(a);
+        }
+    }
+
+    let _0_5: N = na::cast(0.5f64);
+    let mut res = Vec::with_capacity(pts.len());
+
+    for (i, p) in pts.iter().enumerate() {
+        let mut cell = box_faces(bounds);
+
+        for j in neighbors[i].iter() {
+            let q      = &pts[*j];
+            let normal = *q - *p;
+            let middle = *p + normal * _0_5;
+
+            cell = clip_polyhedron(cell, &normal, na::dot(&normal, middle.as_vec()));
+
+            if cell.is_empty() {
+                break;
+            }
+        }
+
+        let mut vertices = Vec::new();
+
+        for f in cell.iter() {
+            for pt in f.iter() {
+                if !vertices.iter().any(|v| na::approx_eq(v, pt)) {
+                    vertices.push(pt.clone());
+                }
+            }
+        }
+
+        res.push(Convex::new(vertices));
+    }
+
+    res
+}
+
+// Returns the six faces of the box, each face being a convex polygon.
+fn box_faces<N: Scalar>(aabb: &AABB<Pnt3<N>>) -> Vec<Vec<Pnt3<N>>> {
+    let m = aabb.mins();
+    let n = aabb.maxs();
+
+    let p = [
+        Pnt3::new(m.x, m.y, m.z), Pnt3::new(n.x, m.y, m.z),
+        Pnt3::new(n.x, n.y, m.z), Pnt3::new(m.x, n.y, m.z),
+        Pnt3::new(m.x, m.y, n.z), Pnt3::new(n.x, m.y, n.z),
+        Pnt3::new(n.x, n.y, n.z), Pnt3::new(m.x, n.y, n.z)
+    ];
+
+    let faces = [
+        [ 0u, 3, 2, 1 ], [ 4u, 5, 6, 7 ], [ 0u, 1, 5, 4 ],
+        [ 2u, 3, 7, 6 ], [ 0u, 4, 7, 3 ], [ 1u, 2, 6, 5 ]
+    ];
+
+    faces.iter().map(|f| f.iter().map(|i| p[*i].clone()).collect()).collect()
+}
+
+// Clips a convex polyhedron by the half-space `dot(normal, x) <= offset`.
+fn clip_polyhedron<N: Scalar>(faces:  Vec<Vec<Pnt3<N>>>,
+                              normal: &Vec3<N>,
+                              offset: N)
+                              -> Vec<Vec<Pnt3<N>>> {
+    let eps: N = na::cast(1.0e-7f64);
+    let eps    = eps * (na::norm(normal) + offset.abs() + na::one());
+
+    let mut res    = Vec::with_capacity(faces.len() + 1);
+    let mut on_cap = Vec::new();
+
+    for face in faces.into_iter() {
+        let mut clipped = Vec::with_capacity(face.len() + 1);
+
+        for i in range(0, face.len()) {
+            let a  = &face[i];
+            let b  = &face[(i + 1) % face.len()];
+            let da = na::dot(normal, a.as_vec()) - offset;
+            let db = na::dot(normal, b.as_vec()) - offset;
+
+            if da <= eps {
+                clipped.push(a.clone());
+
+                if da >= -eps {
+                    on_cap.push(a.clone());
+                }
+            }
+
+            if (da < -eps && db > eps) || (da > eps && db < -eps) {
+                let t   = da / (da - db);
+                let pt  = *a + (*b - *a) * t;
+
+                on_cap.push(pt.clone());
+                clipped.push(pt);
+            }
+        }
+
+        if clipped.len() >= 3 {
+            res.push(clipped);
+        }
+    }
+
+    if res.is_empty() {
+        return res;
+    }
+
+    // Build the cap polygon by sorting the points lying on the clipping plane by angle.
+    let mut cap: Vec<Pnt3<N>> = Vec::new();
+
+    for pt in on_cap.into_iter() {
+        if !cap.iter().any(|c| na::approx_eq(c, &pt)) {
+            cap.push(pt);
+        }
+    }
+
+    if cap.len() >= 3 {
+        let center = utils::center(cap.as_slice());
+        let (u, v) = plane_basis(normal);
+
+        let mut angles: Vec<(N, Pnt3<N>)> = cap.into_iter().map(|pt| {
+            let d = pt - center;
+            (na::dot(&d, &v).atan2(na::dot(&d, &u)), pt)
+        }).collect();
+
+        angles.sort_by(|a, b| a.val0().partial_cmp(&b.val0()).unwrap());
+
+        res.push(angles.into_iter().map(|(_, pt)| pt).collect());
+    }
+
+    res
+}
+
+// Computes two vectors orthogonal to `n` and to each other.
+fn plane_basis<N: Scalar>(n: &Vec3<N>) -> (Vec3<N>, Vec3<N>) {
+    let axis = if n.x.abs() <= n.y.abs() && n.x.abs() <= n.z.abs() {
+        Vec3::new(na::one(), na::zero(), na::zero())
+    }
+    else if n.y.abs() <= n.z.abs() {
+        Vec3::new(na::zero(), na::one(), na::zero())
+    }
+    else {
+        Vec3::new(na::zero(), na::zero(), na::one())
+    };
+
+    let u = na::normalize(&na::cross(n, &axis));
+    let v = na::cross(n, &u);
+
+    (u, v)
+}
+
+#[cfg(test)]
+mod test {
+    use std::rand::{IsaacRng, Rng};
+    use na::Pnt3;
+    use na;
+    use bounding_volume::AABB;
+    use volumetric::Volumetric;
+
+    #[test]
+    fn test_voronoi_two_points() {
+        let pts    = [ Pnt3::new(-1.0f64, 0.0, 0.0), Pnt3::new(1.0, 0.0, 0.0) ];
+        let bounds = AABB::new(Pnt3::new(-2.0, -1.0, -1.0), Pnt3::new(2.0, 1.0, 1.0));
+        let cells  = super::voronoi_cells(pts.as_slice(), &bounds);
+
+        assert!(cells.len() == 2);
+        assert!(na::approx_eq(&cells[0].volume(), &8.0));
+        assert!(na::approx_eq(&cells[1].volume(), &8.0));
+    }
+
+    #[test]
+    fn test_voronoi_cells_fill_bounds() {
+        let mut rng = IsaacRng::new_unseeded();
+        let pts: Vec<Pnt3<f64>> = range(0u, 30).map(|_| {
+            Pnt3::new(rng.gen::<f64>() * 2.0 - 1.0, rng.gen::<f64>() * 2.0 - 1.0, rng.gen::<f64>() * 2.0 - 1.0)
+        }).collect();
+        let bounds = AABB::new(Pnt3::new(-1.0, -1.0, -1.0), Pnt3::new(1.0, 1.0, 1.0));
+        let cells  = super::voronoi_cells(pts.as_slice(), &bounds);
+
+        let total = cells.iter().fold(0.0, |acc, cell| acc + cell.volume());
+
+        assert!(cells.len() == pts.len());
+        assert!(na::approx_eq_eps(&total, &8.0, &1.0e-6));
+    }
+}
@@ -1,7 +1,8 @@
 //! Volume and inertia tensor computation.
 
 #[doc(inline)]
-pub use volumetric::volumetric::{Volumetric, InertiaTensor};
+pub use volumetric::volumetric::{Volumetric, InertiaTensor, InertiaTensorExt, principal_inertia,
+                                 parallel_axis_theorem};
 
 pub use volumetric::volumetric_ball::{ball_volume, ball_surface, ball_center_of_mass,
                                       ball_unit_angular_inertia};
@@ -15,6 +16,11 @@ pub use volumetric::volumetric_convex::{convex_mesh_surface, convex_mesh_volume_
                                         convex_mesh_mass_properties, convex_hull_surface,
                                         convex_hull_volume, convex_hull_center_of_mass,
                                         convex_hull_unit_angular_inertia};
+pub use volumetric::volumetric_mesh::{MeshVolumetricError, check_closed_mesh, mesh_surface,
+                                      closed_mesh_volume_and_center_of_mass,
+                                      closed_mesh_mass_properties};
+pub use volumetric::volumetric_polygon::{polygon_perimeter, polygon_area, polygon_center_of_mass,
+                                         polygon_unit_angular_inertia, polygon_mass_properties};
 
 #[doc(hidden)]
 pub mod volumetric;
@@ -25,3 +31,5 @@ mod volumetric_cuboid;
 mod volumetric_cone;
 mod volumetric_compound;
 mod volumetric_convex;
+mod volumetric_mesh;
+mod volumetric_polygon;
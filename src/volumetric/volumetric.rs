@@ -1,7 +1,8 @@
 //! Traits to compute inertial properties.
 
+use std::num::Float;
 use na;
-use na::{Pnt2, Pnt3, Vec1, Vec3, Iso2, Iso3, Mat1, Mat3};
+use na::{Pnt2, Pnt3, Vec1, Vec3, Iso2, Iso3, Mat1, Mat3, Rot3, Col};
 use math::Scalar;
 
 /// Trait implemented by inertia tensors.
@@ -18,6 +19,58 @@ pub trait InertiaTensor<N, P, AV, M> {
     fn to_relative_wrt_point(&self, N, &P) -> Self;
 }
 
+/// Additional operations on inertia tensors.
+///
+/// They are not part of `InertiaTensor` so that user-defined inertia tensors are not required to
+/// implement them.
+pub trait InertiaTensorExt<N, P, AV, M>: InertiaTensor<N, P, AV, M> {
+    /// Computes this inertia tensor relative to the center of mass of its object.
+    ///
+    /// This is the inverse of `to_relative_wrt_point`: `self` is relative to a point such that the
+    /// center of mass is located at `pt`.
+    fn to_relative_wrt_center_of_mass(&self, mass: N, pt: &P) -> Self;
+
+    /// Computes the inverse of this inertia tensor, transformed from local space to world space.
+    ///
+    /// Principal moments negligible with regard to the largest one are set to zero instead of
+    /// being inverted.
+    fn inv_to_world_space(&self, &M) -> Self;
+}
+
+/// Computes the principal moments and the principal axes of a 3d inertia tensor.
+///
+/// Returns the principal moments `d` and a rotation `r` such that `inertia = r * diag(d) * r⁻¹`,
+/// i.e., the columns of `r` are the principal axes.
+pub fn principal_inertia<N: Scalar>(inertia: &Mat3<N>) -> (Vec3<N>, Rot3<N>) {
+    let (eigvec, eigval) = na::eigen_qr(inertia, &Float::epsilon(), 1000);
+
+    let mut x = na::normalize(&eigvec.col(0));
+    let y     = na::normalize(&eigvec.col(1));
+    let z     = na::normalize(&eigvec.col(2));
+
+    // Ensure the frame is right-handed.
+    if na::dot(&na::cross(&x, &y), &z) < na::zero() {
+        x = -x;
+    }
+
+    let basis = Mat3::new(
+        x.x, y.x, z.x,
+        x.y, y.y, z.y,
+        x.z, y.z, z.z
+    );
+
+    (eigval, unsafe { Rot3::new_with_mat(basis) })
+}
+
+/// Applies the parallel axis theorem to a 3d inertia tensor.
+///
+/// `inertia` is expressed relative to the center of mass of an object with the mass `mass`. The
+/// result is expressed relative to a point `shift` away from the center of mass.
+#[inline]
+pub fn parallel_axis_theorem<N: Scalar>(inertia: &Mat3<N>, mass: N, shift: &Vec3<N>) -> Mat3<N> {
+    inertia.to_relative_wrt_point(mass, &(na::orig::<Pnt3<N>>() + *shift))
+}
+
 /// Trait implemented by objects which have a mass, a center of mass, and an inertia tensor.
 pub trait Volumetric<N: Scalar, P, I: Mul<N, I>> {
     /// Computes the surface of this object.
@@ -70,6 +123,23 @@ impl<N: Scalar> InertiaTensor<N, Pnt2<N>, Vec1<N>, Iso2<N>> for Mat1<N> {
     }
 }
 
+impl<N: Scalar> InertiaTensorExt<N, Pnt2<N>, Vec1<N>, Iso2<N>> for Mat1<N> {
+    #[inline]
+    fn to_relative_wrt_center_of_mass(&self, mass: N, pt: &Pnt2<N>) -> Mat1<N> {
+        *self - Mat1::new(mass * na::sqnorm(pt.as_vec()))
+    }
+
+    #[inline]
+    fn inv_to_world_space(&self, _: &Iso2<N>) -> Mat1<N> {
+        if self.m11.abs() <= Float::epsilon() {
+            self.clone()
+        }
+        else {
+            Mat1::new(na::one::<N>() / self.m11)
+        }
+    }
+}
+
 impl<N: Scalar> InertiaTensor<N, Pnt3<N>, Vec3<N>, Iso3<N>> for Mat3<N> {
     #[inline]
     fn apply(&self, av: &Vec3<N>) -> Vec3<N> {
@@ -94,3 +164,77 @@ impl<N: Scalar> InertiaTensor<N, Pnt3<N>, Vec3<N>, Iso3<N>> for Mat3<N> {
         *self + (diagm - na::outer(pt.as_vec(), pt.as_vec())) * mass
     }
 }
+
+impl<N: Scalar> InertiaTensorExt<N, Pnt3<N>, Vec3<N>, Iso3<N>> for Mat3<N> {
+    #[inline]
+    fn to_relative_wrt_center_of_mass(&self, mass: N, pt: &Pnt3<N>) -> Mat3<N> {
+        let zero = na::zero::<Mat3<N>>();
+
+        *self - zero.to_relative_wrt_point(mass, pt)
+    }
+
+    fn inv_to_world_space(&self, t: &Iso3<N>) -> Mat3<N> {
+        let (moments, rot) = principal_inertia(self);
+
+        // The eigenvalues of a singular tensor are zero up to rounding errors.
+        let _eps: N = Float::epsilon();
+        let max     = moments.x.abs().max(moments.y.abs()).max(moments.z.abs());
+        let eps     = max * _eps * na::cast(100.0f64);
+
+        let inv = |m: N| if m.abs() <= eps { na::zero() } else { na::one::<N>() / m };
+        let inv_moments = Mat3::new(
+            inv(moments.x), na::zero(),     na::zero(),
+            na::zero(),     inv(moments.y), na::zero(),
+            na::zero(),     na::zero(),     inv(moments.z)
+        );
+
+        let basis     = *rot.submat();
+        let inv_local = basis * inv_moments * na::transpose(&basis);
+
+        inv_local.to_world_space(t)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use na::{Mat3, Iso3, Vec3};
+    use na;
+    use volumetric::InertiaTensorExt;
+
+    #[test]
+    fn test_principal_inertia() {
+        let inertia = Mat3::new(2.0f64, 1.0, 0.0,
+                                1.0,    2.0, 0.0,
+                                0.0,    0.0, 5.0);
+
+        let (moments, rot) = super::principal_inertia(&inertia);
+
+        let basis = *rot.submat();
+        let diag  = Mat3::new(moments.x, 0.0,       0.0,
+                              0.0,       moments.y, 0.0,
+                              0.0,       0.0,       moments.z);
+
+        assert!(na::approx_eq(&(basis * diag * na::transpose(&basis)), &inertia));
+        assert!(na::approx_eq(&(moments.x + moments.y + moments.z), &9.0));
+        assert!(na::approx_eq(&na::det(&basis), &1.0));
+    }
+
+    #[test]
+    fn test_inv_to_world_space_singular() {
+        let rot   = Iso3::new(na::zero(), Vec3::new(0.3f64, -0.2, 0.5));
+        let basis = *rot.rotation.submat();
+        let diag  = Mat3::new(2.0f64, 0.0, 0.0,
+                              0.0,    4.0, 0.0,
+                              0.0,    0.0, 0.0);
+        let inv_diag = Mat3::new(0.5f64, 0.0,  0.0,
+                                 0.0,    0.25, 0.0,
+                                 0.0,    0.0,  0.0);
+
+        // The null moment of this tensor is not exactly zero after the eigendecomposition.
+        let inertia  = basis * diag * na::transpose(&basis);
+        let expected = basis * inv_diag * na::transpose(&basis);
+        let inv      = inertia.inv_to_world_space(&na::one());
+
+        assert!(na::approx_eq(&inv, &expected));
+    }
+}
@@ -1,16 +1,16 @@
-// XXX: implement this for 2d too.
-
-use na::{FloatVec, Outer, EigenQR, Pnt3, Mat3, Zero};
+use na::{FloatVec, Outer, EigenQR, Pnt2, Pnt3, Mat1, Mat3, Zero};
 use na;
 use utils;
 use procedural::{TriMesh, IndexBuffer};
 use procedural;
 use volumetric::Volumetric;
 use math::{Scalar, Point, Vect};
-use shape::Convex3;
+use volumetric::volumetric_polygon;
+use shape::{Convex2, Convex3};
 
 
-fn tetrahedron_unit_inertia_tensor_wrt_point<N, P, V, I>(point: &P, p1: &P, p2: &P, p3: &P, p4: &P) -> I
+/// The unit inertia tensor of a tetrahedron, expressed relative to a given point.
+pub fn tetrahedron_unit_inertia_tensor_wrt_point<N, P, V, I>(point: &P, p1: &P, p2: &P, p3: &P, p4: &P) -> I
     where N: Scalar,
           P: Point<N, V>,
           V: FloatVec<N>,
@@ -173,14 +173,15 @@ pub fn convex_hull_surface<N, P, V, M>(dim: uint, points: &[P]) -> N
 
     match dim {
         2 => {
-            unimplemented!()
+            let convex_polyline = procedural::convex_hull2(points);
+            volumetric_polygon::polygon_perimeter(&convex_polyline)
         }
         3 => {
             let convex_mesh = procedural::convex_hull3(points);
             unsafe { convex_mesh_surface(&convex_mesh) }
         }
         _ => {
-            unimplemented!()
+            unreachable!()
         }
     }
 }
@@ -195,14 +196,15 @@ pub fn convex_hull_volume<N, P, V, M>(dim: uint, points: &[P]) -> N
 
     match dim {
         2 => {
-            unimplemented!()
+            let convex_polyline = procedural::convex_hull2(points);
+            volumetric_polygon::polygon_area(&convex_polyline)
         }
         3 => {
             let convex_mesh = procedural::convex_hull3(points);
             unsafe { convex_mesh_volume_and_center_of_mass(&convex_mesh).val0() }
         }
         _ => {
-            unimplemented!()
+            unreachable!()
         }
     }
 }
@@ -217,14 +219,15 @@ pub fn convex_hull_center_of_mass<N, P, V, M>(dim: uint, points: &[P]) -> P
 
     match dim {
         2 => {
-            unimplemented!()
+            let convex_polyline = procedural::convex_hull2(points);
+            volumetric_polygon::polygon_center_of_mass(&convex_polyline)
         }
         3 => {
             let convex_mesh = procedural::convex_hull3(points);
             unsafe { convex_mesh_volume_and_center_of_mass(&convex_mesh).val1() }
         }
         _ => {
-            unimplemented!()
+            unreachable!()
         }
     }
 }
@@ -240,7 +243,8 @@ pub fn convex_hull_unit_angular_inertia<N, P, V, M, I>(dim: uint, points: &[P])
 
     match dim {
         2 => {
-            unimplemented!()
+            let convex_polyline = procedural::convex_hull2(points);
+            volumetric_polygon::polygon_unit_angular_inertia(&convex_polyline)
         }
         3 => {
             let convex_mesh = procedural::convex_hull3(points);
@@ -251,7 +255,7 @@ pub fn convex_hull_unit_angular_inertia<N, P, V, M, I>(dim: uint, points: &[P])
             }
         }
         _ => {
-            unimplemented!()
+            unreachable!()
         }
     }
 }
@@ -279,11 +283,34 @@ impl<N: Scalar> Volumetric<N, Pnt3<N>, Mat3<N>> for Convex3<N> {
     }
 }
 
+impl<N: Scalar> Volumetric<N, Pnt2<N>, Mat1<N>> for Convex2<N> {
+    fn surface(&self) -> N {
+        convex_hull_surface(2, self.points())
+    }
+
+    fn volume(&self) -> N {
+        convex_hull_volume(2, self.points())
+    }
+
+    fn center_of_mass(&self) -> Pnt2<N> {
+        convex_hull_center_of_mass(2, self.points())
+    }
+
+    fn unit_angular_inertia(&self) -> Mat1<N> {
+        convex_hull_unit_angular_inertia(2, self.points())
+    }
+
+    fn mass_properties(&self, density: N) -> (N, Pnt2<N>, Mat1<N>) {
+        let convex_polyline = procedural::convex_hull2(self.points());
+        volumetric_polygon::polygon_mass_properties(&convex_polyline, density)
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use na::Vec3;
+    use na::{Vec2, Vec3, Pnt2, Iso2, Mat1};
     use na;
-    use shape::{Convex, Cuboid};
+    use shape::{Convex, Convex2, Cuboid, CompoundData};
     use procedural;
     use volumetric::Volumetric;
 
@@ -320,4 +347,41 @@ mod test {
         assert!(na::approx_eq(&actual_m, &expected_m),
                 format!("Masses do not match: actual {}, expected: {}.", actual_m, expected_m));
     }
+
+    fn unit_square() -> Convex2<f64> {
+        Convex::new(vec!(Pnt2::new(-0.5f64, -0.5), Pnt2::new(0.5, -0.5),
+                         Pnt2::new(0.5, 0.5), Pnt2::new(-0.5, 0.5)))
+    }
+
+    #[test]
+    fn test_convex2_mass_properties() {
+        let square = unit_square();
+
+        // The polar moment of a unit square wrt. its center is (1 + 1) / 12.
+        let (m, c, i) = square.mass_properties(1.0);
+
+        assert!(na::approx_eq(&m, &1.0));
+        assert!(na::approx_eq(&c, &Pnt2::new(0.0, 0.0)));
+        assert!(na::approx_eq(&i, &Mat1::new(1.0 / 6.0)));
+        assert!(na::approx_eq(&square.volume(), &1.0));
+        assert!(na::approx_eq(&square.surface(), &4.0));
+        assert!(na::approx_eq(&square.unit_angular_inertia(), &Mat1::new(1.0 / 6.0)));
+    }
+
+    #[test]
+    fn test_compound2_mass_properties() {
+        let mut data = CompoundData::new();
+
+        data.push_shape(Iso2::new(Vec2::new(1.0f64, 0.0), na::zero()), unit_square(), 2.0);
+        data.push_shape(Iso2::new(Vec2::new(3.0f64, 0.0), na::zero()), unit_square(), 2.0);
+
+        // Each part has a mass of 2 and a polar moment of 2 / 6 wrt. its center, and is at a
+        // distance of 1 from the center of mass.
+        let (m, c, i): (f64, Pnt2<f64>, Mat1<f64>) = data.mass_properties(1.0);
+
+        assert!(na::approx_eq(&m, &4.0));
+        assert!(na::approx_eq(&c, &Pnt2::new(2.0, 0.0)));
+        assert!(na::approx_eq(&i, &Mat1::new(2.0 * (2.0 / 6.0 + 2.0))));
+        assert!(na::approx_eq(&data.surface(), &8.0));
+    }
 }
@@ -0,0 +1,310 @@
+use std::collections::HashMap;
+use std::collections::hash_map::{Vacant, Occupied};
+use na::{Pnt3, Mat3};
+use na;
+use utils;
+use procedural::{TriMesh3, IndexBuffer};
+use shape::Mesh3;
+use volumetric::Volumetric;
+use volumetric::volumetric_convex;
+use math::Scalar;
+
+
+/// Errors returned when computing the mass properties of a triangle mesh.
+#[deriving(Clone, Show, PartialEq, Eq)]
+pub enum MeshVolumetricError {
+    /// The edge between the two given vertices is used by only one triangle.
+    OpenMesh(uint, uint),
+    /// The edge between the two given vertices is shared by more than two triangles.
+    NonManifoldMesh(uint, uint),
+    /// The two triangles sharing the edge between the two given vertices have incompatible
+    /// orientations.
+    InconsistentOrientation(uint, uint)
+}
+
+/// Checks that a triangle mesh is closed, manifold, and consistently oriented.
+///
+/// The triangles are given as a flat buffer of vertex indices, three indices per triangle.
+/// Two triangles are considered adjacent only if they share the same vertex indices.
+pub fn check_closed_mesh(indices: &[uint]) -> Result<(), MeshVolumetricError> {
+    assert!(indices.len() % 3 == 0);
+
+    // Maps each directed edge to the number of triangles using it.
+    let mut edges: HashMap<(uint, uint), uint> = HashMap::new();
+
+    for t in indices.chunks(3) {
+        for &(a, b) in [ (t[0], t[1]), (t[1], t[2]), (t[2], t[0]) ].iter() {
+            match edges.entry((a, b)) {
+                Occupied(mut entry) => *entry.get_mut() += 1,
+                Vacant(entry)       => { let _ = entry.set(1); }
+            }
+        }
+    }
+
+    for (&(a, b), &n) in edges.iter() {
+        let opposite = edges.get(&(b, a)).map(|n| *n).unwrap_or(0);
+
+        if n + opposite > 2 {
+            return Err(MeshVolumetricError::NonManifoldMesh(a, b));
+        }
+        else if n == 2 {
+            return Err(MeshVolumetricError::InconsistentOrientation(a, b));
+        }
+        else if opposite == 0 {
+            return Err(MeshVolumetricError::OpenMesh(a, b));
+        }
+    }
+
+    Ok(())
+}
+
+/// The surface of a triangle mesh.
+pub fn mesh_surface<N: Scalar>(coords: &[Pnt3<N>], indices: &[uint]) -> N {
+    let mut surface = na::zero::<N>();
+
+    for t in indices.chunks(3) {
+        surface = surface + utils::triangle_area(&coords[t[0]], &coords[t[1]], &coords[t[2]]);
+    }
+
+    surface
+}
+
+/// The volume and center of mass of a closed triangle mesh.
+///
+/// The volume is integrated using the signed tetrahedra formed by each triangle and an arbitrary
+/// reference point. Thus, the mesh does not have to be convex but it must be closed and
+/// consistently oriented. Inward-oriented meshes are handled as well.
+pub fn closed_mesh_volume_and_center_of_mass<N: Scalar>(coords:  &[Pnt3<N>],
+                                                        indices: &[uint])
+                                                        -> Result<(N, Pnt3<N>), MeshVolumetricError> {
+    try!(check_closed_mesh(indices));
+
+    Ok(unchecked_volume_and_center_of_mass(coords, indices))
+}
+
+/// The mass properties of a closed triangle mesh.
+///
+/// Returns the mass, the center of mass, and the angular inertia tensor (relative to the center
+/// of mass) of the volume enclosed by the mesh. The mesh does not have to be convex but it must be
+/// closed and consistently oriented.
+pub fn closed_mesh_mass_properties<N: Scalar>(coords:  &[Pnt3<N>],
+                                              indices: &[uint],
+                                              density: N)
+                                              -> Result<(N, Pnt3<N>, Mat3<N>), MeshVolumetricError> {
+    try!(check_closed_mesh(indices));
+
+    let (volume, com) = unchecked_volume_and_center_of_mass(coords, indices);
+
+    if na::is_zero(&volume) {
+        return Ok((na::zero(), com, na::zero()));
+    }
+
+    let mut itot = na::zero::<Mat3<N>>();
+
+    for t in indices.chunks(3) {
+        let p2 = &coords[t[0]];
+        let p3 = &coords[t[1]];
+        let p4 = &coords[t[2]];
+
+        let vol = utils::tetrahedron_signed_volume(&com, p2, p3, p4);
+        let ipart: Mat3<N> =
+            volumetric_convex::tetrahedron_unit_inertia_tensor_wrt_point(&com, &com, p2, p3, p4);
+
+        itot = itot + ipart * vol;
+    }
+
+    if volume < na::zero() {
+        // The mesh is oriented inward.
+        Ok((-volume * density, com, itot * (-density)))
+    }
+    else {
+        Ok((volume * density, com, itot * density))
+    }
+}
+
+// NOTE: the returned volume is negative if the mesh is oriented inward.
+fn unchecked_volume_and_center_of_mass<N: Scalar>(coords: &[Pnt3<N>], indices: &[uint]) -> (N, Pnt3<N>) {
+    let geometric_center = utils::center(coords);
+
+    let mut res = na::orig::<Pnt3<N>>();
+    let mut vol = na::zero::<N>();
+
+    for t in indices.chunks(3) {
+        let p2 = &coords[t[0]];
+        let p3 = &coords[t[1]];
+        let p4 = &coords[t[2]];
+
+        let volume = utils::tetrahedron_signed_volume(&geometric_center, p2, p3, p4);
+        let center = utils::tetrahedron_center(&geometric_center, p2, p3, p4);
+
+        res = res + *center.as_vec() * volume;
+        vol = vol + volume;
+    }
+
+    if na::is_zero(&vol) {
+        (vol, geometric_center)
+    }
+    else {
+        (vol, res / vol)
+    }
+}
+
+fn closed_mesh_volume_and_center_of_mass_or_fail<N: Scalar>(coords: &[Pnt3<N>], indices: &[uint])
+                                                             -> (N, Pnt3<N>) {
+    match closed_mesh_volume_and_center_of_mass(coords, indices) {
+        Ok(res) => res,
+        Err(e)  => panic!("Unable to compute the volume of a mesh that is not closed, manifold, \
+                           and consistently oriented: {}.", e)
+    }
+}
+
+fn closed_mesh_mass_properties_or_fail<N: Scalar>(coords: &[Pnt3<N>], indices: &[uint], density: N)
+                                                  -> (N, Pnt3<N>, Mat3<N>) {
+    match closed_mesh_mass_properties(coords, indices, density) {
+        Ok(res) => res,
+        Err(e)  => panic!("Unable to compute the mass properties of a mesh that is not closed, \
+                           manifold, and consistently oriented: {}.", e)
+    }
+}
+
+fn trimesh_flat_indices<N: Scalar>(mesh: &TriMesh3<N>) -> Vec<uint> {
+    let mut res = Vec::with_capacity(mesh.num_triangles() * 3);
+
+    match mesh.indices {
+        IndexBuffer::Unified(ref idx) => {
+            for t in idx.iter() {
+                res.push(t.x as uint);
+                res.push(t.y as uint);
+                res.push(t.z as uint);
+            }
+        },
+        IndexBuffer::Split(ref idx) => {
+            // Only the vertex positions matter.
+            for t in idx.iter() {
+                res.push(t.x.x as uint);
+                res.push(t.y.x as uint);
+                res.push(t.z.x as uint);
+            }
+        }
+    }
+
+    res
+}
+
+/// The volumetric properties of a `Mesh3`.
+///
+/// The mesh must be closed, manifold, and consistently oriented. Otherwise, those methods fail.
+/// Use `closed_mesh_volume_and_center_of_mass` or `closed_mesh_mass_properties` to get an error
+/// instead.
+impl<N: Scalar> Volumetric<N, Pnt3<N>, Mat3<N>> for Mesh3<N> {
+    fn surface(&self) -> N {
+        mesh_surface(self.vertices().as_slice(), self.indices().as_slice())
+    }
+
+    fn volume(&self) -> N {
+        closed_mesh_volume_and_center_of_mass_or_fail(self.vertices().as_slice(),
+                                                      self.indices().as_slice()).val0()
+    }
+
+    fn center_of_mass(&self) -> Pnt3<N> {
+        closed_mesh_volume_and_center_of_mass_or_fail(self.vertices().as_slice(),
+                                                      self.indices().as_slice()).val1()
+    }
+
+    fn unit_angular_inertia(&self) -> Mat3<N> {
+        let (vol, _, i) = self.mass_properties(na::one());
+
+        if na::is_zero(&vol) {
+            i
+        }
+        else {
+            i * (na::one::<N>() / vol)
+        }
+    }
+
+    fn mass_properties(&self, density: N) -> (N, Pnt3<N>, Mat3<N>) {
+        closed_mesh_mass_properties_or_fail(self.vertices().as_slice(), self.indices().as_slice(), density)
+    }
+}
+
+/// The volumetric properties of a `TriMesh3`.
+///
+/// The mesh must be closed, manifold, and consistently oriented. Otherwise, those methods fail.
+/// Use `closed_mesh_volume_and_center_of_mass` or `closed_mesh_mass_properties` to get an error
+/// instead.
+impl<N: Scalar> Volumetric<N, Pnt3<N>, Mat3<N>> for TriMesh3<N> {
+    fn surface(&self) -> N {
+        mesh_surface(self.coords.as_slice(), trimesh_flat_indices(self).as_slice())
+    }
+
+    fn volume(&self) -> N {
+        closed_mesh_volume_and_center_of_mass_or_fail(self.coords.as_slice(),
+                                                      trimesh_flat_indices(self).as_slice()).val0()
+    }
+
+    fn center_of_mass(&self) -> Pnt3<N> {
+        closed_mesh_volume_and_center_of_mass_or_fail(self.coords.as_slice(),
+                                                      trimesh_flat_indices(self).as_slice()).val1()
+    }
+
+    fn unit_angular_inertia(&self) -> Mat3<N> {
+        let (vol, _, i) = self.mass_properties(na::one());
+
+        if na::is_zero(&vol) {
+            i
+        }
+        else {
+            i * (na::one::<N>() / vol)
+        }
+    }
+
+    fn mass_properties(&self, density: N) -> (N, Pnt3<N>, Mat3<N>) {
+        closed_mesh_mass_properties_or_fail(self.coords.as_slice(),
+                                            trimesh_flat_indices(self).as_slice(),
+                                            density)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use na::{Pnt3, Vec3};
+    use na;
+    use shape::Cuboid;
+    use procedural;
+    use volumetric::Volumetric;
+
+    #[test]
+    fn test_closed_mesh_inertia_tensor() {
+        let mut mesh = procedural::cuboid(&Vec3::new(2.0f64, 4.0, 6.0));
+        mesh.translate_by(&Vec3::new(10.0, -5.0, 3.0));
+
+        let cuboid = Cuboid::new(Vec3::new(1.0f64, 2.0, 3.0));
+
+        let (actual_m, actual_c, actual_i) = mesh.mass_properties(2.5);
+        let (expected_m, _, expected_i)    = cuboid.mass_properties(2.5);
+
+        assert!(na::approx_eq(&actual_m, &expected_m));
+        assert!(na::approx_eq(&actual_c, &Pnt3::new(10.0, -5.0, 3.0)));
+        assert!(na::approx_eq(&actual_i, &expected_i),
+                format!("Inertia tensors do not match: actual {}, expected: {}.", actual_i, expected_i));
+    }
+
+    #[test]
+    fn test_open_mesh() {
+        let mesh = procedural::cuboid(&Vec3::new(1.0f64, 1.0, 1.0));
+
+        let mut flat: Vec<uint> = Vec::new();
+
+        for t in mesh.indices.unwrap_split().iter() {
+            flat.push(t.x.x as uint);
+            flat.push(t.y.x as uint);
+            flat.push(t.z.x as uint);
+        }
+
+        assert!(super::check_closed_mesh(flat.as_slice()).is_ok());
+
+        flat.truncate(flat.len() - 3);
+
+        assert!(super::check_closed_mesh(flat.as_slice()).is_err());
+    }
+}
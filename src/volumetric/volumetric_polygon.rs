@@ -0,0 +1,149 @@
+use na::Zero;
+use na;
+use procedural::Polyline;
+use utils;
+use math::{Scalar, Point, Vect};
+
+
+// Computes the signed area, the signed area-weighted centroid, and the signed polar moment of
+// inertia of a 2d polygon. The two last are relative to the first vertex of the polygon.
+fn polygon_signed_integrals<N, P, V>(polygon: &Polyline<N, P, V>) -> (N, V, N)
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> {
+    assert!(na::dim::<P>() == 2);
+
+    let pts = polygon.coords.as_slice();
+
+    let mut area    = na::zero::<N>();
+    let mut center  = na::zero::<V>();
+    let mut inertia = na::zero::<N>();
+
+    if pts.len() < 3 {
+        return (area, center, inertia);
+    }
+
+    let origin = &pts[0];
+
+    for i in range(1, pts.len() - 1) {
+        // All the vectors are relative to the first vertex, to improve the numerical stability.
+        let a = pts[i] - *origin;
+        let b = pts[i + 1] - *origin;
+
+        let cross = a[0] * b[1] - a[1] * b[0];
+
+        area   = area + cross;
+        center = center + (a + b) * cross;
+
+        inertia = inertia +
+                  (a[0] * a[0] + a[0] * b[0] + b[0] * b[0] +
+                   a[1] * a[1] + a[1] * b[1] + b[1] * b[1]) * cross;
+    }
+
+    let _2:  N = na::cast(2.0f64);
+    let _6:  N = na::cast(6.0f64);
+    let _12: N = na::cast(12.0f64);
+
+    (area / _2, center / _6, inertia / _12)
+}
+
+/// The perimeter of a closed 2d polygon.
+pub fn polygon_perimeter<N, P, V>(polygon: &Polyline<N, P, V>) -> N
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> {
+    let pts = polygon.coords.as_slice();
+    let mut res = na::zero::<N>();
+
+    for i in range(0, pts.len()) {
+        res = res + na::dist(&pts[i], &pts[(i + 1) % pts.len()]);
+    }
+
+    res
+}
+
+/// The area of a simple 2d polygon.
+///
+/// The polygon may be oriented clockwise or counterclockwise. Its last vertex is implicitly
+/// linked to the first one.
+pub fn polygon_area<N, P, V>(polygon: &Polyline<N, P, V>) -> N
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> {
+    let (area, _, _) = polygon_signed_integrals(polygon);
+
+    if area < na::zero() { -area } else { area }
+}
+
+/// The center of mass of a simple 2d polygon.
+pub fn polygon_center_of_mass<N, P, V>(polygon: &Polyline<N, P, V>) -> P
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N> {
+    let (area, wcenter, _) = polygon_signed_integrals(polygon);
+
+    if na::is_zero(&area) {
+        utils::center(polygon.coords.as_slice())
+    }
+    else {
+        polygon.coords[0] + wcenter / area
+    }
+}
+
+/// The unit angular inertia of a simple 2d polygon, relative to its center of mass.
+pub fn polygon_unit_angular_inertia<N, P, V, I>(polygon: &Polyline<N, P, V>) -> I
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          I: Zero + IndexMut<(uint, uint), N> {
+    let (area, wcenter, inertia) = polygon_signed_integrals(polygon);
+    let mut res = na::zero::<I>();
+
+    if !na::is_zero(&area) {
+        // Parallel axis theorem: move from the first vertex to the center of mass.
+        let center = wcenter / area;
+        res[(0, 0)] = inertia / area - na::sqnorm(&center);
+    }
+
+    res
+}
+
+/// The mass, center of mass, and angular inertia tensor of a simple 2d polygon.
+pub fn polygon_mass_properties<N, P, V, I>(polygon: &Polyline<N, P, V>, density: N) -> (N, P, I)
+    where N: Scalar,
+          P: Point<N, V>,
+          V: Vect<N>,
+          I: Zero + Mul<N, I> + IndexMut<(uint, uint), N> {
+    let mass            = polygon_area(polygon) * density;
+    let com             = polygon_center_of_mass(polygon);
+    let unit_inertia: I = polygon_unit_angular_inertia(polygon);
+
+    (mass, com, unit_inertia * mass)
+}
+
+#[cfg(test)]
+mod test {
+    use na::{Pnt2, Vec2, Mat1};
+    use na;
+    use shape::Cuboid;
+    use procedural::{Polyline, Polyline2};
+    use volumetric::Volumetric;
+
+    #[test]
+    fn test_polygon_mass_properties() {
+        // A clockwise 2x4 rectangle.
+        let polygon: Polyline2<f64> = Polyline::new(vec!(
+            Pnt2::new(1.0, 1.0), Pnt2::new(1.0, 5.0), Pnt2::new(3.0, 5.0), Pnt2::new(3.0, 1.0)
+        ), None);
+
+        let cuboid = Cuboid::new(Vec2::new(1.0f64, 2.0));
+
+        let (m, c, i): (f64, Pnt2<f64>, Mat1<f64>) = super::polygon_mass_properties(&polygon, 3.0);
+        let (expected_m, _, expected_i) = cuboid.mass_properties(3.0);
+
+        assert!(na::approx_eq(&m, &expected_m));
+        assert!(na::approx_eq(&c, &Pnt2::new(2.0, 3.0)));
+        assert!(na::approx_eq(&i, &expected_i));
+        assert!(na::approx_eq(&super::polygon_perimeter(&polygon), &12.0));
+    }
+}